@@ -15,6 +15,7 @@ pub struct Cmd {
 pub enum Command {
   Compile(handle::Compile),
   Run(handle::Run),
+  Test(handle::Test),
 }
 
 impl Cmd {
@@ -26,6 +27,7 @@ impl Cmd {
     match self.command {
       Command::Compile(ref command) => command.handle().await,
       Command::Run(ref command) => command.handle().await,
+      Command::Test(ref command) => command.handle().await,
     }
   }
 }
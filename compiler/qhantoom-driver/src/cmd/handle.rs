@@ -1,5 +1,7 @@
 mod compile;
 mod run;
+mod test;
 
 pub use compile::Compile;
 pub use run::Run;
+pub use test::Test;
@@ -2,9 +2,11 @@ use crate::cmd::settings::compile::Settings;
 use crate::cmd::settings::Backend;
 use crate::common::{EXIT_FAILURE, EXIT_SUCCESS};
 
-use qhantoom::front::{analyzer, parser};
+use qhantoom::back::codegen;
+use qhantoom::front::{analyzer, expander, loader, monomorphizer, parser};
 
 use std::any::Any;
+use std::path::PathBuf;
 use std::{process, thread};
 
 #[derive(clap::Parser)]
@@ -12,9 +14,15 @@ pub struct Compile {
   /// print the AST of the program
   #[clap(short, long)]
   ast: bool,
+  /// print the program once its macros are expanded
+  #[clap(long)]
+  expand: bool,
   /// specify the path name of the program
   #[clap(short, long)]
   input: String,
+  /// add a directory in which the loaded modules are searched, can be repeated
+  #[clap(short = 'L', long)]
+  search_path: Vec<String>,
   /// print the ir of the program
   #[clap(long)]
   ir: bool,
@@ -27,8 +35,10 @@ impl Compile {
   pub async fn handle(&self) {
     let settings = Settings {
       ast: self.ast,
+      expand: self.expand,
       input: self.input.clone(),
       ir: self.ir,
+      search_paths: self.search_path.iter().map(PathBuf::from).collect(),
       backend: Backend::from(self.backend.clone()),
     };
 
@@ -41,29 +51,46 @@ impl Compile {
 
 async fn compile(
   settings: Settings,
-) -> Result<(), Box<(dyn Any + Send + 'static)>> {
+) -> Result<(), Box<dyn Any + Send + 'static>> {
   thread::spawn(move || compiling(settings)).join()
 }
 
 fn compiling(settings: Settings) {
   println!("compiling");
 
-  let program = parser::parse(settings.input);
-  println!("{:?}", program);
+  let (mut program, errors) = parser::parse(settings.input);
+
+  if !errors.is_empty() {
+    eprintln!("\n💥 {} syntax error(s) found", errors.len());
+  }
+
+  loader::load(&mut program, &settings.search_paths);
+  expander::expand(&mut program);
+  loader::qualify(&mut program);
+
+  if settings.expand {
+    println!("{program}");
+    return;
+  }
 
   let _ = analyzer::analyze(&program);
 
-  // uncomment to run code generation
-  // let codegen = codegen::cranelift::aot::generate(&program);
-
-  // match codegen.build(settings.ir) {
-  //   Ok(done) => {
-  //     println!("{:?}", program);
-  //     done();
-  //   }
-  //   Err(error) => {
-  //     eprint!("{error}");
-  //     eprintln!("💥 i couldn't compile `project-name`\n");
-  //   }
-  // }
+  // the program is printed once analyzed to show the inferred types
+  if settings.ast {
+    println!("{:?}", program);
+  }
+
+  monomorphizer::monomorphize(&mut program);
+
+  let codegen = match settings.backend {
+    Backend::Cranelift => codegen::cranelift::aot::generate(&program),
+  };
+
+  match codegen.build(settings.ir) {
+    Ok(done) => done(),
+    Err(error) => {
+      eprint!("{error}");
+      eprintln!("💥 i couldn't compile `project-name`\n");
+    }
+  }
 }
@@ -15,7 +15,7 @@ impl Run {
   }
 }
 
-async fn run() -> Result<(), Box<(dyn Any + Send + 'static)>> {
+async fn run() -> Result<(), Box<dyn Any + Send + 'static>> {
   thread::spawn(running).join()
 }
 
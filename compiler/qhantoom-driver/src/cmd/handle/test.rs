@@ -0,0 +1,320 @@
+use crate::cmd::settings::test::{Format, Settings};
+use crate::cmd::settings::Backend;
+use crate::common::{EXIT_FAILURE, EXIT_SUCCESS};
+
+use qhantoom::back::codegen;
+use qhantoom::front::{analyzer, expander, loader, monomorphizer, parser};
+
+use std::any::Any;
+use std::os::unix::process::ExitStatusExt;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, Instant};
+use std::{process, thread};
+
+#[derive(clap::Parser)]
+pub struct Test {
+  /// only run the tests whose name contains this filter
+  filter: Option<String>,
+  /// specify the path names of the programs, can be repeated
+  #[clap(short, long, required = true)]
+  input: Vec<String>,
+  /// add a directory in which the loaded modules are searched, can be repeated
+  #[clap(short = 'L', long)]
+  search_path: Vec<String>,
+  /// list the tests without running them
+  #[clap(long)]
+  list: bool,
+  /// specify the output format: human or json (one object per line)
+  #[clap(short, long, value_enum, default_value_t = Format::Human)]
+  format: Format,
+  /// specify the backend you want to use
+  #[clap(short, long, default_value = "cranelift")]
+  backend: String,
+}
+
+impl Test {
+  pub async fn handle(&self) {
+    let settings = Settings {
+      inputs: self.input.clone(),
+      filter: self.filter.clone(),
+      list: self.list,
+      search_paths: self.search_path.iter().map(PathBuf::from).collect(),
+      format: self.format,
+      backend: Backend::from(self.backend.clone()),
+    };
+
+    match test(settings).await {
+      Ok(true) => process::exit(EXIT_SUCCESS),
+      Ok(false) | Err(_) => process::exit(EXIT_FAILURE),
+    }
+  }
+}
+
+async fn test(
+  settings: Settings,
+) -> Result<bool, Box<dyn Any + Send + 'static>> {
+  thread::spawn(move || testing(settings)).join()
+}
+
+// the outcome of a test, the exit code is missing when the test is killed by
+// a signal
+struct Outcome {
+  name: String,
+  exit_code: Option<i32>,
+  signal: Option<i32>,
+  duration: Duration,
+  stdout: String,
+}
+
+impl Outcome {
+  fn is_ok(&self) -> bool {
+    self.exit_code == Some(0)
+  }
+}
+
+#[derive(Default)]
+struct Summary {
+  passed: usize,
+  failed: usize,
+  filtered_out: usize,
+}
+
+// returns whether every test passed
+fn testing(settings: Settings) -> bool {
+  let start = Instant::now();
+  let mut summary = Summary::default();
+
+  for input in &settings.inputs {
+    let Some(exe) = build(input, &settings) else {
+      return false;
+    };
+
+    let (names, filtered_out) = match list_tests(&exe) {
+      Ok(names) => filter_tests(names, &settings.filter),
+      Err(error) => {
+        eprintln!("💥 i couldn't list the tests of `{input}`: {error}\n");
+        return false;
+      }
+    };
+
+    summary.filtered_out += filtered_out;
+
+    if settings.list {
+      for name in &names {
+        print_listed(settings.format, input, name);
+      }
+
+      continue;
+    }
+
+    if settings.format == Format::Human {
+      println!("\nrunning {} tests from `{input}`", names.len());
+    }
+
+    for name in names {
+      let outcome = match run_test(&exe, &name) {
+        Ok(outcome) => outcome,
+        Err(error) => {
+          eprintln!("💥 i couldn't run the test `{name}`: {error}\n");
+          return false;
+        }
+      };
+
+      match outcome.is_ok() {
+        true => summary.passed += 1,
+        false => summary.failed += 1,
+      }
+
+      print_outcome(settings.format, input, &outcome);
+    }
+  }
+
+  if !settings.list {
+    print_summary(settings.format, &summary, start.elapsed());
+  }
+
+  summary.failed == 0
+}
+
+// compiles the units of the program into the test executable and returns
+// its path. an analysis error aborts the process after its reports
+fn build(input: &str, settings: &Settings) -> Option<String> {
+  let (mut program, errors) = parser::parse(input.to_string());
+
+  if !errors.is_empty() {
+    eprintln!("\n💥 {} syntax error(s) found", errors.len());
+  }
+
+  loader::load(&mut program, &settings.search_paths);
+  expander::expand(&mut program);
+  loader::qualify(&mut program);
+
+  let _ = analyzer::analyze_tests(&program);
+
+  monomorphizer::monomorphize(&mut program);
+
+  let codegen = match settings.backend {
+    Backend::Cranelift => codegen::cranelift::aot::generate_tests(&program),
+  };
+
+  match codegen.build_tests() {
+    Ok(exe) => Some(exe),
+    Err(error) => {
+      eprint!("{error}");
+      eprintln!("💥 i couldn't compile the tests of `{input}`\n");
+      None
+    }
+  }
+}
+
+fn list_tests(exe: &str) -> Result<Vec<String>, String> {
+  let output = Command::new(exe)
+    .arg("--list")
+    .output()
+    .map_err(|error| error.to_string())?;
+
+  let names = String::from_utf8_lossy(&output.stdout)
+    .lines()
+    .map(|name| name.to_string())
+    .collect();
+
+  Ok(names)
+}
+
+// returns the kept tests and the number of tests filtered out
+fn filter_tests(
+  names: Vec<String>,
+  filter: &Option<String>,
+) -> (Vec<String>, usize) {
+  let Some(filter) = filter else {
+    return (names, 0);
+  };
+  let count = names.len();
+
+  let names = names
+    .into_iter()
+    .filter(|name| name.contains(filter.as_str()))
+    .collect::<Vec<_>>();
+
+  let filtered_out = count - names.len();
+
+  (names, filtered_out)
+}
+
+// each test runs in a process of its own so that a crash only fails it
+fn run_test(exe: &str, name: &str) -> Result<Outcome, String> {
+  let start = Instant::now();
+
+  let output = Command::new(exe)
+    .arg(name)
+    .output()
+    .map_err(|error| error.to_string())?;
+
+  Ok(Outcome {
+    name: name.to_string(),
+    exit_code: output.status.code(),
+    signal: output.status.signal(),
+    duration: start.elapsed(),
+    stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+  })
+}
+
+fn print_listed(format: Format, input: &str, name: &str) {
+  match format {
+    Format::Human => println!("{input}: {name}"),
+    Format::Json => println!(
+      "{{\"type\":\"test\",\"file\":{},\"name\":{}}}",
+      json_str(input),
+      json_str(name),
+    ),
+  }
+}
+
+fn print_outcome(format: Format, input: &str, outcome: &Outcome) {
+  match format {
+    Format::Human => {
+      let status = match (outcome.is_ok(), outcome.exit_code, outcome.signal) {
+        (true, ..) => "ok".to_string(),
+        (false, Some(code), _) => format!("FAILED (exit status: {code})"),
+        (false, None, Some(signal)) => format!("FAILED (signal: {signal})"),
+        (false, None, None) => "FAILED".to_string(),
+      };
+
+      println!(
+        "test {} ... {status} ({})",
+        outcome.name,
+        millis(outcome.duration),
+      );
+
+      if !outcome.is_ok() && !outcome.stdout.is_empty() {
+        println!("---- {} stdout ----\n{}", outcome.name, outcome.stdout);
+      }
+    }
+    Format::Json => println!(
+      "{{\"type\":\"test\",\"file\":{},\"name\":{},\"status\":\"{}\",\
+       \"exit_code\":{},\"signal\":{},\"duration_ms\":{:.3},\"stdout\":{}}}",
+      json_str(input),
+      json_str(&outcome.name),
+      if outcome.is_ok() { "ok" } else { "failed" },
+      json_num(outcome.exit_code),
+      json_num(outcome.signal),
+      outcome.duration.as_secs_f64() * 1000.0,
+      json_str(&outcome.stdout),
+    ),
+  }
+}
+
+fn print_summary(format: Format, summary: &Summary, duration: Duration) {
+  match format {
+    Format::Human => println!(
+      "\ntest result: {}. {} passed; {} failed; {} filtered out; \
+       finished in {}",
+      if summary.failed == 0 { "ok" } else { "FAILED" },
+      summary.passed,
+      summary.failed,
+      summary.filtered_out,
+      millis(duration),
+    ),
+    Format::Json => println!(
+      "{{\"type\":\"summary\",\"passed\":{},\"failed\":{},\
+       \"filtered_out\":{},\"duration_ms\":{:.3}}}",
+      summary.passed,
+      summary.failed,
+      summary.filtered_out,
+      duration.as_secs_f64() * 1000.0,
+    ),
+  }
+}
+
+fn millis(duration: Duration) -> String {
+  format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
+
+fn json_num(number: Option<i32>) -> String {
+  match number {
+    Some(number) => number.to_string(),
+    None => "null".to_string(),
+  }
+}
+
+fn json_str(text: &str) -> String {
+  let mut json = String::with_capacity(text.len() + 2);
+
+  json.push('"');
+
+  for ch in text.chars() {
+    match ch {
+      '"' => json.push_str("\\\""),
+      '\\' => json.push_str("\\\\"),
+      '\n' => json.push_str("\\n"),
+      '\r' => json.push_str("\\r"),
+      '\t' => json.push_str("\\t"),
+      ch if ch.is_control() => json.push_str(&format!("\\u{:04x}", ch as u32)),
+      ch => json.push(ch),
+    }
+  }
+
+  json.push('"');
+  json
+}
@@ -1,4 +1,5 @@
 pub mod compile;
+pub mod test;
 
 #[derive(Debug)]
 pub enum Backend {
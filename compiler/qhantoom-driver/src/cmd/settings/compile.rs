@@ -1,9 +1,13 @@
 use crate::cmd::settings::Backend;
 
+use std::path::PathBuf;
+
 #[derive(Debug)]
 pub struct Settings {
   pub ast: bool,
+  pub expand: bool,
   pub input: String,
   pub ir: bool,
+  pub search_paths: Vec<PathBuf>,
   pub backend: Backend,
 }
@@ -0,0 +1,21 @@
+use crate::cmd::settings::Backend;
+
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub struct Settings {
+  pub inputs: Vec<String>,
+  pub filter: Option<String>,
+  pub list: bool,
+  pub search_paths: Vec<PathBuf>,
+  pub format: Format,
+  pub backend: Backend,
+}
+
+// a format that clap does not know is a usage error
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+  Human,
+  // one json object per line
+  Json,
+}
@@ -1,22 +1,26 @@
+use super::harness;
+use super::runtime;
 use super::translator::Translator;
 
 use crate::back::codegen::cranelift::interface::{
-  CompiledFunction, DataContextBuilder, VariableBuilder,
+  data_name, declare_prelude_layouts, ty_kind, Aliases, CompiledFunction,
+  CompiledVal, DataContextBuilder, DataLayout, VariableBuilder,
 };
 
 use crate::front::parser::ast::{
-  Ext, Fun, Item, ItemKind, Program, Prototype, ReturnTy, Ty,
+  AsTy, Data, Decl, Enum, ExprKind, Ext, Fun, Impl, Item, ItemKind, Lit,
+  LitKind, Program, Prototype, ReturnTy, Ty, TyAlias, TyKind, Unit,
 };
 
-use crate::util::constant::{PATH_DIRECTORY, PROGRAM_ENTRY};
+use crate::util::constant::{PATH_DIRECTORY, PROGRAM_ENTRY, TEST_EXE};
 use crate::util::pack;
 
-use codegen::ir::{FuncRef, GlobalValue};
+use codegen::ir::FuncRef;
 use cranelift::prelude::{Block as CBlock, *};
 use cranelift_codegen::settings::Flags;
 use cranelift_codegen::{settings, Context};
 use cranelift_module::default_libcall_names;
-use cranelift_module::{FuncId, Linkage, Module};
+use cranelift_module::{DataContext, DataId, FuncId, Linkage, Module};
 use cranelift_native::builder;
 use cranelift_object::{ObjectBuilder, ObjectModule};
 use cranelift_preopt::optimize;
@@ -25,21 +29,40 @@ use std::collections::HashMap;
 
 pub type BuildResult = Result<Box<dyn FnOnce()>, String>;
 
+// the function that computes the `val` items whose value is not a literal,
+// the entry point calls it first
+const VALS_INIT: &str = "qh::vals_init";
+
 pub fn generate(program: &Program) -> Codegen<'_> {
   Codegen::new(program).generate()
 }
 
+// the program is compiled with its units and without its `main` function,
+// whose place is taken by the entry point that runs the tests
+pub fn generate_tests(program: &Program) -> Codegen<'_> {
+  Codegen::new(program).with_tests().generate()
+}
+
 pub struct Codegen<'a> {
   builder_context: FunctionBuilderContext,
   module: ObjectModule,
   program: &'a Program,
-  blocks: Vec<CBlock>,
+  blocks: Vec<(CBlock, CBlock)>,
   ctx: Context,
   ir: String,
   funs: HashMap<String, CompiledFunction>,
-  globals: HashMap<String, GlobalValue>,
+  globals: HashMap<String, DataId>,
+  vals: HashMap<String, CompiledVal>,
+  // the `val` items computed by the function `VALS_INIT`
+  vals_init: Vec<&'a Decl>,
+  datas: HashMap<String, DataLayout>,
+  aliases: Aliases,
   data_ctx_builder: DataContextBuilder,
   variable_builder: VariableBuilder,
+  // the number of lambdas translated so far, it makes their names unique
+  lambda_count: u32,
+  // the name and the function of each test, when the tests are compiled
+  tests: Option<Vec<(String, FuncId)>>,
 }
 
 impl<'a> Codegen<'a> {
@@ -50,6 +73,10 @@ impl<'a> Codegen<'a> {
       .set("opt_level", "speed_and_size")
       .expect("set optlevel");
 
+    // the object file is linked by gcc, which produces a position
+    // independent executable by default
+    flag_builder.set("is_pic", "true").expect("set is_pic");
+
     let isa_builder = builder().unwrap();
     let isa = isa_builder.finish(Flags::new(flag_builder)).unwrap();
 
@@ -68,34 +95,284 @@ impl<'a> Codegen<'a> {
       ir: String::new(),
       funs: HashMap::new(),
       globals: HashMap::new(),
+      vals: HashMap::new(),
+      vals_init: vec![],
+      datas: HashMap::new(),
+      aliases: HashMap::new(),
       data_ctx_builder: DataContextBuilder::default(),
       variable_builder: VariableBuilder::default(),
+      lambda_count: 0,
+      tests: None,
     }
   }
 
+  fn with_tests(mut self) -> Self {
+    self.tests = Some(vec![]);
+    self
+  }
+
   fn generate(mut self) -> Self {
+    runtime::generate(&mut self.module, &mut self.funs);
+
+    let vals_init = self
+      .module
+      .declare_function(
+        VALS_INIT,
+        Linkage::Local,
+        &runtime::signature(&self.module, 0),
+      )
+      .unwrap();
+
+    self.funs.insert(
+      VALS_INIT.to_string(),
+      CompiledFunction::new(vals_init, false, 0, None),
+    );
+
+    // an alias can be used before the item that declares it
+    for item in &self.program.items {
+      if let ItemKind::Type(alias) = &item.kind {
+        self.generate_item_ty_alias(alias);
+      }
+    }
+
     for item in &self.program.items {
       self.generate_item(item);
     }
 
+    self.generate_vals_init(vals_init);
+
+    if self.tests.is_none() {
+      return self;
+    }
+
+    // the tests can call every function of the program
+    for (index, item) in self.program.items.iter().enumerate() {
+      if let ItemKind::Unit(unit) = &item.kind {
+        self.generate_unit(index, unit);
+      }
+    }
+
+    if let Some(tests) = &self.tests {
+      harness::generate(&mut self.module, tests, vals_init);
+    }
+
     self
   }
 
-  fn generate_item(&mut self, item: &Item) {
+  fn generate_item(&mut self, item: &'a Item) {
     match &item.kind {
+      ItemKind::Fun(fun)
+        if self.tests.is_some()
+          && fun.prototype.name.to_string() == PROGRAM_ENTRY => {}
       ItemKind::Fun(fun) => self.generate_item_fun(fun),
       ItemKind::Ext(ext) => self.generate_item_ext(ext, Linkage::Import),
-      _ => panic!("generate item"),
+      ItemKind::Val(decl) => self.generate_item_val(decl),
+      ItemKind::Data(data) => self.generate_item_data(data),
+      ItemKind::Enum(enumeration) => self.generate_item_enum(enumeration),
+      ItemKind::Impl(imp) => self.generate_item_impl(imp),
+      // the default methods of a behavior are copied in its impls
+      ItemKind::Load(_)
+      | ItemKind::Bind(_)
+      | ItemKind::Behavior(_)
+      | ItemKind::Type(_)
+      | ItemKind::Unit(_) => {}
+      // the macros are expanded before the code is generated
+      ItemKind::Macro(_) | ItemKind::MacroCall(_) => unreachable!(),
+    }
+  }
+
+  // a literal is written in its data object, the value of another `val` is
+  // computed at the start of the program
+  fn generate_item_val(&mut self, decl: &'a Decl) {
+    let name = decl.pattern.to_string();
+
+    let ty = match &decl.ty {
+      Some(ty) => ty.to_owned(),
+      None => decl.inferred_ty.borrow().to_owned().unwrap(),
+    };
+
+    declare_prelude_layouts(&ty, None, &self.aliases, &mut self.datas);
+
+    let data = data_name(&ty, None, &self.aliases);
+
+    let size = match &data {
+      Some(data_name) => self.datas[data_name].size,
+      None => DataLayout::WORD,
+    };
+
+    let val_ty = match ty_kind(&ty, &self.aliases) {
+      TyKind::F32 | TyKind::F64 => types::F64,
+      _ => types::I64,
+    };
+
+    let id = self
+      .module
+      .declare_data(&format!("val::{name}"), Linkage::Local, true, false)
+      .unwrap();
+
+    let mut data_ctx = DataContext::new();
+
+    match &decl.value.kind {
+      ExprKind::Lit(lit) => self.define_val_lit(&mut data_ctx, lit),
+      _ => {
+        data_ctx.define_zeroinit(size as usize);
+        self.vals_init.push(decl);
+      }
     }
+
+    self.module.define_data(id, &data_ctx).unwrap();
+    self.vals.insert(name, CompiledVal::new(id, val_ty, data));
+  }
+
+  // a literal is written in the data object of its `val`, a string as the
+  // address of the data object of the literal
+  fn define_val_lit(&mut self, data_ctx: &mut DataContext, lit: &Lit) {
+    let bytes = match &lit.kind {
+      LitKind::Int(num) => num.to_le_bytes(),
+      LitKind::Float(num) => num.to_le_bytes(),
+      LitKind::Bool(boolean) => (*boolean as i64).to_le_bytes(),
+      LitKind::Char(ch) => (*ch as i64).to_le_bytes(),
+      LitKind::Str(string) => return self.define_val_str(data_ctx, string),
+      // the bytes of a byte string are ASCII, each one is its own character
+      LitKind::ByteStr(bytes) => {
+        let string = bytes.iter().map(|byte| *byte as char).collect();
+
+        return self.define_val_str(data_ctx, &string);
+      }
+    };
+
+    data_ctx.define(bytes.into());
+  }
+
+  fn define_val_str(&mut self, data_ctx: &mut DataContext, string: &String) {
+    let id = self.data_ctx_builder.declare_str(
+      &mut self.module,
+      &mut self.globals,
+      string,
+    );
+
+    // a zeroed data object goes to `.bss`, where it cannot be relocated
+    data_ctx.define(Box::new([0; DataLayout::WORD as usize]));
+
+    let str_gv = self.module.declare_data_in_data(id, data_ctx);
+
+    data_ctx.write_data_addr(0, str_gv, 0);
   }
 
   fn generate_item_fun(&mut self, fun: &Fun) {
-    self.generate_fun(fun);
+    self.generate_fun(&fun.prototype.name.to_string(), fun, None);
+  }
+
+  fn generate_item_ty_alias(&mut self, alias: &TyAlias) {
+    for (name, ty) in alias.tys() {
+      self.aliases.insert(name, ty.to_owned());
+    }
+  }
+
+  fn generate_item_data(&mut self, data: &Data) {
+    for field in &data.fields {
+      declare_prelude_layouts(&field.ty, None, &self.aliases, &mut self.datas);
+    }
+
+    let layout = DataLayout::new(data, &self.datas, &self.aliases);
+
+    self.datas.insert(data.name.to_string(), layout);
+  }
+
+  fn generate_item_enum(&mut self, enumeration: &Enum) {
+    for ty in enumeration.variants.iter().flat_map(|variant| &variant.tys) {
+      declare_prelude_layouts(ty, None, &self.aliases, &mut self.datas);
+    }
+
+    let layout = DataLayout::with_enum(enumeration, &self.datas, &self.aliases);
+
+    self.datas.insert(enumeration.name.to_string(), layout);
   }
 
-  fn generate_fun(&mut self, fun: &Fun) {
+  fn generate_item_impl(&mut self, imp: &Impl) {
+    let data_name = imp.ty.to_string();
+
+    // declare every function first so that they can call each other
+    for fun in &imp.funs {
+      let func_name = format!("{data_name}::{}", fun.prototype.name);
+
+      self
+        .generate_prototype(
+          &func_name,
+          &fun.prototype,
+          Linkage::Export,
+          Some(&data_name),
+        )
+        .unwrap();
+    }
+
+    for fun in &imp.funs {
+      let func_name = format!("{data_name}::{}", fun.prototype.name);
+
+      self.generate_fun(&func_name, fun, Some(&data_name));
+    }
+  }
+
+  // a mock is compiled under a name of its own, and takes the place of the
+  // function that it replaces while the tests of its unit are compiled
+  fn generate_unit(&mut self, index: usize, unit: &Unit) {
+    let mut replaced = vec![];
+
+    for mock in unit.mocks() {
+      let name = mock.prototype.name.to_string();
+      let func_name = format!("unit{index}::mock::{name}");
+
+      self
+        .generate_prototype(&func_name, &mock.prototype, Linkage::Local, None)
+        .unwrap();
+
+      let mock_fun = self.funs[&func_name].clone();
+
+      replaced.push((name.to_owned(), self.funs.insert(name, mock_fun)));
+    }
+
+    for mock in unit.mocks() {
+      let func_name = format!("unit{index}::mock::{}", mock.prototype.name);
+
+      self.generate_fun(&func_name, mock, None);
+    }
+
+    for test in unit.tests() {
+      let name = test.prototype.name.to_string();
+      let func_name = format!("unit{index}::test::{name}");
+
+      self.generate_fun(&func_name, test, None);
+
+      if let (Some(fun), Some(tests)) =
+        (self.funs.get(&func_name), &mut self.tests)
+      {
+        tests.push((name, fun.id));
+      }
+    }
+
+    for (name, fun) in replaced.into_iter().rev() {
+      match fun {
+        Some(fun) => self.funs.insert(name, fun),
+        None => self.funs.remove(&name),
+      };
+    }
+  }
+
+  fn generate_fun(
+    &mut self,
+    func_name: &str,
+    fun: &Fun,
+    self_data: Option<&String>,
+  ) {
     let signature = &mut self.ctx.func.signature;
     let params = &fun.prototype.inputs;
+    let output = data_name(&fun.prototype.as_ty(), self_data, &self.aliases);
+
+    // a returned data is written in a stack slot of the caller, its address
+    // is passed as the first parameter
+    if output.is_some() {
+      signature.params.push(AbiParam::new(types::I64));
+    }
 
     for _param in params {
       signature.params.push(AbiParam::new(types::I64));
@@ -103,10 +380,8 @@ impl<'a> Codegen<'a> {
 
     signature.returns.push(AbiParam::new(types::I64));
 
-    let func_name = fun.prototype.name.to_string();
-
     let func_id = self
-      .generate_prototype(&fun.prototype, Linkage::Export)
+      .generate_prototype(func_name, &fun.prototype, Linkage::Export, self_data)
       .unwrap();
 
     let mut builder =
@@ -125,10 +400,23 @@ impl<'a> Codegen<'a> {
     builder.switch_to_block(entry_block);
     builder.seal_block(entry_block);
 
+    if func_name == PROGRAM_ENTRY {
+      let vals_init = self.funs[VALS_INIT].id;
+      let vals_init = self.module.declare_func_in_func(vals_init, builder.func);
+
+      builder.ins().call(vals_init, &[]);
+    }
+
     let mut vars = HashMap::new();
+    let mut data_vars = HashMap::new();
+    let offset = output.is_some() as usize;
+
+    let sret = output
+      .as_ref()
+      .map(|_| builder.block_params(entry_block)[0]);
 
     for (i, input) in params.iter().enumerate() {
-      let val = builder.block_params(entry_block)[i];
+      let val = builder.block_params(entry_block)[i + offset];
 
       let variable =
         self
@@ -136,9 +424,13 @@ impl<'a> Codegen<'a> {
           .create_var(&mut builder, val, types::I64);
 
       vars.insert(input.pattern.to_string(), variable);
+
+      if let Some(data) = data_name(&input.ty, self_data, &self.aliases) {
+        data_vars.insert(input.pattern.to_string(), data);
+      }
     }
 
-    if let Some(ref mut func) = self.funs.get_mut(&func_name) {
+    if let Some(ref mut func) = self.funs.get_mut(func_name) {
       func.defined = true;
     }
 
@@ -147,19 +439,26 @@ impl<'a> Codegen<'a> {
       module: &mut self.module,
       funs: &self.funs,
       globals: &mut self.globals,
+      vals: &self.vals,
       vars,
-      program: self.program,
       ty: types::I64,
+      datas: &mut self.datas,
+      aliases: &self.aliases,
+      data_tys: HashMap::new(),
+      data_vars,
+      sret,
+      self_data: self_data.cloned(),
       blocks: &mut self.blocks,
       data_ctx_builder: &mut self.data_ctx_builder,
       variable_builder: &mut self.variable_builder,
+      lambda_count: &mut self.lambda_count,
     };
 
     let return_value = match translator.translate(&fun.body) {
       Ok(value) => value,
       Err(_e) => {
         translator.builder.finalize();
-        self.funs.remove(&func_name);
+        self.funs.remove(func_name);
         return; // TODO: error
       }
     };
@@ -175,8 +474,51 @@ impl<'a> Codegen<'a> {
     self.module.clear_context(&mut self.ctx);
   }
 
+  fn generate_vals_init(&mut self, func_id: FuncId) {
+    self.ctx.func.signature = runtime::signature(&self.module, 0);
+
+    let mut builder =
+      FunctionBuilder::new(&mut self.ctx.func, &mut self.builder_context);
+
+    let entry_block = builder.create_block();
+    builder.switch_to_block(entry_block);
+    builder.seal_block(entry_block);
+
+    let mut translator = Translator {
+      builder,
+      module: &mut self.module,
+      funs: &self.funs,
+      globals: &mut self.globals,
+      vals: &self.vals,
+      vars: HashMap::new(),
+      ty: types::I64,
+      datas: &mut self.datas,
+      aliases: &self.aliases,
+      data_tys: HashMap::new(),
+      data_vars: HashMap::new(),
+      sret: None,
+      self_data: None,
+      blocks: &mut self.blocks,
+      data_ctx_builder: &mut self.data_ctx_builder,
+      variable_builder: &mut self.variable_builder,
+      lambda_count: &mut self.lambda_count,
+    };
+
+    let value = translator.translate_vals(&self.vals_init);
+
+    translator.builder.ins().return_(&[value]);
+    translator.builder.finalize();
+
+    optimize(&mut self.ctx, self.module.isa()).unwrap();
+
+    self.module.define_function(func_id, &mut self.ctx).unwrap();
+    self.module.clear_context(&mut self.ctx);
+  }
+
   fn generate_item_ext(&mut self, ext: &Ext, linkage: Linkage) {
-    match self.generate_prototype(&ext.prototype, linkage) {
+    let func_name = ext.prototype.name.to_string();
+
+    match self.generate_prototype(&func_name, &ext.prototype, linkage, None) {
       Ok(_func_id) => {}
       Err(_error) => {}
     }
@@ -184,11 +526,21 @@ impl<'a> Codegen<'a> {
 
   fn generate_prototype(
     &mut self,
+    func_name: &str,
     prototype: &Prototype,
     linkage: Linkage,
+    self_data: Option<&String>,
   ) -> Result<FuncId, String> {
-    let func_name = &prototype.name.to_string();
     let params = &prototype.inputs;
+    let output = data_name(&prototype.as_ty(), self_data, &self.aliases);
+
+    for ty in params
+      .iter()
+      .map(|param| &param.ty)
+      .chain([&prototype.as_ty()])
+    {
+      declare_prelude_layouts(ty, self_data, &self.aliases, &mut self.datas);
+    }
 
     match self.funs.get(func_name) {
       Some(func) => {
@@ -210,6 +562,10 @@ impl<'a> Codegen<'a> {
       None => {
         let mut signature = self.module.make_signature();
 
+        if output.is_some() {
+          signature.params.push(AbiParam::new(types::I64));
+        }
+
         for _param in params.iter() {
           signature.params.push(AbiParam::new(types::I64));
         }
@@ -226,9 +582,19 @@ impl<'a> Codegen<'a> {
             Err(e) => return Err(format!("{e}")),
           };
 
+        // the strings are handed to an external function as C strings
+        let c_strs = match linkage {
+          Linkage::Import => params
+            .iter()
+            .map(|param| ty_kind(&param.ty, &self.aliases) == TyKind::Str)
+            .collect(),
+          _ => vec![],
+        };
+
         self.funs.insert(
           func_name.to_string(),
-          CompiledFunction::new(id, false, params.len()),
+          CompiledFunction::new(id, false, params.len(), output)
+            .with_c_strs(c_strs),
         );
 
         Ok(id)
@@ -240,9 +606,14 @@ impl<'a> Codegen<'a> {
     let object = self.module.finish();
     let bytes = object.emit().unwrap();
 
+    let exe = match self.tests {
+      Some(_) => TEST_EXE,
+      None => PROGRAM_ENTRY,
+    };
+
     Ok(Box::new(move || {
-      let path_object_file = format!("{PATH_DIRECTORY}/{PROGRAM_ENTRY}.o");
-      let path_exe_file = format!("{PATH_DIRECTORY}/{PROGRAM_ENTRY}");
+      let path_object_file = format!("{PATH_DIRECTORY}/{exe}.o");
+      let path_exe_file = format!("{PATH_DIRECTORY}/{exe}");
 
       pack::make_dir(PATH_DIRECTORY);
       pack::make_file(&path_object_file, &bytes);
@@ -253,6 +624,24 @@ impl<'a> Codegen<'a> {
       }
     }))
   }
+
+  // the test executable is made without printing anything, so that the
+  // output of the tests is all there is. it returns the path of the executable
+  pub fn build_tests(self) -> Result<String, String> {
+    let object = self.module.finish();
+    let bytes = object.emit().map_err(|error| error.to_string())?;
+    let path_object_file = format!("{PATH_DIRECTORY}/{TEST_EXE}.o");
+    let path_exe_file = format!("{PATH_DIRECTORY}/{TEST_EXE}");
+
+    pack::make_exe_quietly(
+      PATH_DIRECTORY,
+      &path_object_file,
+      &path_exe_file,
+      &bytes,
+    )?;
+
+    Ok(path_exe_file)
+  }
 }
 
 fn make_c_fun(
@@ -271,16 +660,14 @@ fn make_c_fun(
   signature.returns.push(AbiParam::new(types::I64));
 
   let id = module
-    .declare_function(name, Linkage::Export, &signature)
+    .declare_function(name, Linkage::Import, &signature)
     .unwrap();
 
+  let c_strs = args.iter().map(|arg| arg.kind == TyKind::Str).collect();
+
   funs.insert(
     name.to_string(),
-    CompiledFunction {
-      defined: false,
-      id,
-      param_count: 1,
-    },
+    CompiledFunction::new(id, false, args.len(), None).with_c_strs(c_strs),
   );
 
   module.declare_func_in_func(id, builder.func)
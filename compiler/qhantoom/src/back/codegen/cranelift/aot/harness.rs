@@ -0,0 +1,278 @@
+use super::runtime::signature;
+
+use crate::util::constant::PROGRAM_ENTRY;
+
+use cranelift::prelude::*;
+use cranelift_module::{DataContext, DataId, FuncId, Linkage, Module};
+use cranelift_object::ObjectModule;
+
+// the entry point of the test executable. without arguments, it runs every
+// test in a process of its own, a test fails when its process does not exit
+// with a status of zero. `--list` prints the name of every test and a name
+// runs that test alone, in the process of the executable. the `val` items are
+// computed first, by `vals_init`
+pub fn generate(
+  module: &mut ObjectModule,
+  tests: &[(String, FuncId)],
+  vals_init: FuncId,
+) {
+  let entry_signature = signature(module, 2);
+  let entry = module
+    .declare_function(PROGRAM_ENTRY, Linkage::Export, &entry_signature)
+    .unwrap();
+
+  let fflush = declare_c_fun(module, "fflush", 1);
+  let fork = declare_c_fun(module, "fork", 0);
+  let waitpid = declare_c_fun(module, "waitpid", 3);
+  let exit = declare_c_fun(module, "exit", 1);
+  let printf = declare_c_fun(module, "printf", 4);
+  let puts = declare_c_fun(module, "puts", 1);
+  let strcmp = declare_c_fun(module, "strcmp", 2);
+
+  let list = declare_c_str(module, "qh::test::list", "--list");
+  let running =
+    declare_c_str(module, "qh::test::running", "running %ld tests\n");
+  let line = declare_c_str(module, "qh::test::line", "test %s ... %s\n");
+  let ok = declare_c_str(module, "qh::test::ok", "ok");
+  let failed = declare_c_str(module, "qh::test::failed", "FAILED");
+  let summary = declare_c_str(
+    module,
+    "qh::test::summary",
+    "\ntest result: %s. %ld passed; %ld failed\n",
+  );
+  let unknown =
+    declare_c_str(module, "qh::test::unknown", "no test is named `%s`\n");
+
+  let names = tests
+    .iter()
+    .enumerate()
+    .map(|(index, (name, _))| {
+      declare_c_str(module, &format!("qh::test::name{index}"), name)
+    })
+    .collect::<Vec<_>>();
+
+  let mut ctx = module.make_context();
+  let mut builder_ctx = FunctionBuilderContext::new();
+
+  ctx.func.signature = entry_signature;
+
+  let mut builder = FunctionBuilder::new(&mut ctx.func, &mut builder_ctx);
+  let entry_block = builder.create_block();
+  let arg_block = builder.create_block();
+  let list_block = builder.create_block();
+  let run_all_block = builder.create_block();
+
+  builder.append_block_params_for_function_params(entry_block);
+  builder.switch_to_block(entry_block);
+  builder.seal_block(entry_block);
+
+  let fflush = module.declare_func_in_func(fflush, builder.func);
+  let fork = module.declare_func_in_func(fork, builder.func);
+  let waitpid = module.declare_func_in_func(waitpid, builder.func);
+  let exit = module.declare_func_in_func(exit, builder.func);
+  let printf = module.declare_func_in_func(printf, builder.func);
+  let puts = module.declare_func_in_func(puts, builder.func);
+  let strcmp = module.declare_func_in_func(strcmp, builder.func);
+  let vals_init = module.declare_func_in_func(vals_init, builder.func);
+
+  builder.ins().call(vals_init, &[]);
+
+  let list = c_str(module, &mut builder, list);
+  let running = c_str(module, &mut builder, running);
+  let line = c_str(module, &mut builder, line);
+  let ok = c_str(module, &mut builder, ok);
+  let failed = c_str(module, &mut builder, failed);
+  let summary = c_str(module, &mut builder, summary);
+  let unknown = c_str(module, &mut builder, unknown);
+
+  let names = names
+    .into_iter()
+    .map(|name| c_str(module, &mut builder, name))
+    .collect::<Vec<_>>();
+
+  let zero = builder.ins().iconst(types::I64, 0);
+  let argc = builder.block_params(entry_block)[0];
+  let argc = builder.ins().ireduce(types::I32, argc);
+  let argv = builder.block_params(entry_block)[1];
+  let has_arg = builder.ins().icmp_imm(IntCC::SignedGreaterThan, argc, 1);
+
+  builder.ins().brnz(has_arg, arg_block, &[]);
+  builder.ins().jump(run_all_block, &[]);
+
+  builder.switch_to_block(arg_block);
+  builder.seal_block(arg_block);
+
+  let arg = builder.ins().load(types::I64, MemFlags::trusted(), argv, 8);
+  let is_list = is_same_str(&mut builder, strcmp, arg, list);
+
+  // the argument is compared to the name of each test in turn
+  let mut select_block = builder.create_block();
+
+  builder.ins().brnz(is_list, list_block, &[]);
+  builder.ins().jump(select_block, &[]);
+
+  builder.switch_to_block(list_block);
+  builder.seal_block(list_block);
+
+  for name in &names {
+    builder.ins().call(puts, &[*name]);
+  }
+
+  builder.ins().return_(&[zero]);
+
+  for ((_, test), name) in tests.iter().zip(&names) {
+    let run_block = builder.create_block();
+    let next_block = builder.create_block();
+
+    builder.switch_to_block(select_block);
+    builder.seal_block(select_block);
+
+    let is_test = is_same_str(&mut builder, strcmp, arg, *name);
+
+    builder.ins().brnz(is_test, run_block, &[]);
+    builder.ins().jump(next_block, &[]);
+
+    builder.switch_to_block(run_block);
+    builder.seal_block(run_block);
+
+    let test = module.declare_func_in_func(*test, builder.func);
+
+    builder.ins().call(test, &[]);
+    builder.ins().return_(&[zero]);
+
+    select_block = next_block;
+  }
+
+  builder.switch_to_block(select_block);
+  builder.seal_block(select_block);
+  builder.ins().call(printf, &[unknown, arg, zero, zero]);
+
+  let exit_code = builder.ins().iconst(types::I64, 2);
+
+  builder.ins().return_(&[exit_code]);
+
+  builder.switch_to_block(run_all_block);
+  builder.seal_block(run_all_block);
+
+  let count = builder.ins().iconst(types::I64, tests.len() as i64);
+
+  builder.ins().call(printf, &[running, count, zero, zero]);
+
+  let status_slot = builder.create_sized_stack_slot(StackSlotData::new(
+    StackSlotKind::ExplicitSlot,
+    8,
+  ));
+
+  let mut passed_count = zero;
+  let mut failed_count = zero;
+
+  for ((_, test), name) in tests.iter().zip(&names) {
+    let child_block = builder.create_block();
+    let parent_block = builder.create_block();
+
+    // the buffered output would be written twice otherwise
+    builder.ins().call(fflush, &[zero]);
+
+    let call = builder.ins().call(fork, &[]);
+    let pid = builder.inst_results(call)[0];
+    let pid = builder.ins().ireduce(types::I32, pid);
+    let is_child = builder.ins().icmp_imm(IntCC::Equal, pid, 0);
+
+    builder.ins().brnz(is_child, child_block, &[]);
+    builder.ins().jump(parent_block, &[]);
+
+    builder.switch_to_block(child_block);
+    builder.seal_block(child_block);
+
+    let test = module.declare_func_in_func(*test, builder.func);
+
+    builder.ins().call(test, &[]);
+    builder.ins().call(exit, &[zero]);
+    builder.ins().trap(TrapCode::UnreachableCodeReached);
+
+    builder.switch_to_block(parent_block);
+    builder.seal_block(parent_block);
+
+    let pid = builder.ins().uextend(types::I64, pid);
+    let status = builder.ins().stack_addr(types::I64, status_slot, 0);
+
+    builder.ins().stack_store(zero, status_slot, 0);
+    builder.ins().call(waitpid, &[pid, status, zero]);
+
+    let status = builder.ins().stack_load(types::I32, status_slot, 0);
+    let is_ok = builder.ins().icmp_imm(IntCC::Equal, status, 0);
+    let word = builder.ins().select(is_ok, ok, failed);
+
+    builder.ins().call(printf, &[line, *name, word, zero]);
+
+    let is_ok = builder.ins().bint(types::I64, is_ok);
+    let is_failed = builder.ins().bxor_imm(is_ok, 1);
+
+    passed_count = builder.ins().iadd(passed_count, is_ok);
+    failed_count = builder.ins().iadd(failed_count, is_failed);
+  }
+
+  let has_failed = builder.ins().icmp_imm(IntCC::NotEqual, failed_count, 0);
+  let word = builder.ins().select(has_failed, failed, ok);
+
+  builder
+    .ins()
+    .call(printf, &[summary, word, passed_count, failed_count]);
+
+  let exit_code = builder.ins().bint(types::I64, has_failed);
+
+  builder.ins().return_(&[exit_code]);
+  builder.finalize();
+
+  module.define_function(entry, &mut ctx).unwrap();
+}
+
+// every input is given as a word, like the inputs of an external function
+fn declare_c_fun(
+  module: &mut ObjectModule,
+  name: &str,
+  param_count: usize,
+) -> FuncId {
+  let signature = signature(module, param_count);
+
+  module
+    .declare_function(name, Linkage::Import, &signature)
+    .unwrap()
+}
+
+fn c_str(
+  module: &mut ObjectModule,
+  builder: &mut FunctionBuilder,
+  id: DataId,
+) -> Value {
+  let data = module.declare_data_in_func(id, builder.func);
+
+  builder.ins().symbol_value(types::I64, data)
+}
+
+fn declare_c_str(module: &mut ObjectModule, name: &str, text: &str) -> DataId {
+  let id = module
+    .declare_data(name, Linkage::Local, false, false)
+    .unwrap();
+  let mut data_ctx = DataContext::new();
+  let mut bytes = text.as_bytes().to_vec();
+
+  bytes.push(0);
+  data_ctx.define(bytes.into_boxed_slice());
+  module.define_data(id, &data_ctx).unwrap();
+
+  id
+}
+
+fn is_same_str(
+  builder: &mut FunctionBuilder,
+  strcmp: codegen::ir::FuncRef,
+  lhs: Value,
+  rhs: Value,
+) -> Value {
+  let call = builder.ins().call(strcmp, &[lhs, rhs]);
+  let order = builder.inst_results(call)[0];
+  let order = builder.ins().ireduce(types::I32, order);
+
+  builder.ins().icmp_imm(IntCC::Equal, order, 0)
+}
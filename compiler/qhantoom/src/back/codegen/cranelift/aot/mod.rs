@@ -1,4 +1,6 @@
 mod codegen;
+mod harness;
+mod runtime;
 mod translator;
 
-pub use codegen::generate;
+pub use codegen::{generate, generate_tests};
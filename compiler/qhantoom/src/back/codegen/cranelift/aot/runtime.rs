@@ -0,0 +1,273 @@
+use crate::back::codegen::cranelift::interface::{
+  CompiledFunction, DataContextBuilder,
+};
+
+use cranelift::prelude::*;
+use cranelift_module::{DataContext, DataId, FuncId, Linkage, Module};
+use cranelift_object::ObjectModule;
+
+use std::collections::HashMap;
+
+// the functions on which the generated code relies, they are written in
+// cranelift ir and emitted in every program
+pub const ALLOC: &str = "qh::alloc";
+pub const STR_CONCAT: &str = "qh::str_concat";
+pub const STR_EQ: &str = "qh::str_eq";
+
+// the allocator hands out memory from chunks that it asks to `malloc`, the
+// memory is never given back
+const CHUNK_SIZE: i64 = 64 * 1024;
+
+pub fn generate(
+  module: &mut ObjectModule,
+  funs: &mut HashMap<String, CompiledFunction>,
+) {
+  let alloc = generate_alloc(module);
+
+  funs.insert(
+    ALLOC.to_string(),
+    CompiledFunction::new(alloc, true, 1, None),
+  );
+
+  let str_concat = generate_str_concat(module, alloc);
+
+  funs.insert(
+    STR_CONCAT.to_string(),
+    CompiledFunction::new(str_concat, true, 2, None),
+  );
+
+  let str_eq = generate_str_eq(module);
+
+  funs.insert(
+    STR_EQ.to_string(),
+    CompiledFunction::new(str_eq, true, 2, None),
+  );
+}
+
+pub fn signature(module: &ObjectModule, param_count: usize) -> Signature {
+  let mut signature = module.make_signature();
+
+  for _ in 0..param_count {
+    signature.params.push(AbiParam::new(types::I64));
+  }
+
+  signature.returns.push(AbiParam::new(types::I64));
+  signature
+}
+
+fn declare_word(module: &mut ObjectModule, name: &str) -> DataId {
+  let id = module
+    .declare_data(name, Linkage::Local, true, false)
+    .unwrap();
+  let mut data_ctx = DataContext::new();
+
+  data_ctx.define_zeroinit(8);
+  module.define_data(id, &data_ctx).unwrap();
+
+  id
+}
+
+// `alloc(size)` bumps the pointer of the current chunk by `size` rounded up
+// to a word, a new chunk is taken when the current one is full
+fn generate_alloc(module: &mut ObjectModule) -> FuncId {
+  let heap = declare_word(module, "qh::heap");
+  let heap_end = declare_word(module, "qh::heap_end");
+
+  let malloc_signature = signature(module, 1);
+  let malloc = module
+    .declare_function("malloc", Linkage::Import, &malloc_signature)
+    .unwrap();
+
+  let alloc_signature = signature(module, 1);
+  let alloc = module
+    .declare_function(ALLOC, Linkage::Local, &alloc_signature)
+    .unwrap();
+
+  let mut ctx = module.make_context();
+  let mut builder_ctx = FunctionBuilderContext::new();
+
+  ctx.func.signature = alloc_signature;
+
+  let mut builder = FunctionBuilder::new(&mut ctx.func, &mut builder_ctx);
+  let entry_block = builder.create_block();
+  let grow_block = builder.create_block();
+  let bump_block = builder.create_block();
+
+  builder.append_block_params_for_function_params(entry_block);
+  builder.switch_to_block(entry_block);
+
+  let size = builder.block_params(entry_block)[0];
+  let size = builder.ins().iadd_imm(size, 7);
+  let size = builder.ins().band_imm(size, -8);
+
+  let heap = module.declare_data_in_func(heap, builder.func);
+  let heap = builder.ins().symbol_value(types::I64, heap);
+  let heap_end = module.declare_data_in_func(heap_end, builder.func);
+  let heap_end = builder.ins().symbol_value(types::I64, heap_end);
+
+  let ptr = builder.ins().load(types::I64, MemFlags::trusted(), heap, 0);
+  let end = builder
+    .ins()
+    .load(types::I64, MemFlags::trusted(), heap_end, 0);
+  let next = builder.ins().iadd(ptr, size);
+  let is_full = builder.ins().icmp(IntCC::UnsignedGreaterThan, next, end);
+
+  builder.ins().brnz(is_full, grow_block, &[]);
+  builder.ins().jump(bump_block, &[]);
+  builder.seal_block(entry_block);
+
+  builder.switch_to_block(grow_block);
+
+  let chunk_size = builder.ins().iconst(types::I64, CHUNK_SIZE);
+  let is_large =
+    builder
+      .ins()
+      .icmp(IntCC::UnsignedGreaterThan, size, chunk_size);
+
+  let chunk_size = builder.ins().select(is_large, size, chunk_size);
+  let malloc = module.declare_func_in_func(malloc, builder.func);
+  let call = builder.ins().call(malloc, &[chunk_size]);
+  let chunk = builder.inst_results(call)[0];
+  let chunk_next = builder.ins().iadd(chunk, size);
+  let chunk_end = builder.ins().iadd(chunk, chunk_size);
+
+  builder
+    .ins()
+    .store(MemFlags::trusted(), chunk_next, heap, 0);
+  builder
+    .ins()
+    .store(MemFlags::trusted(), chunk_end, heap_end, 0);
+  builder.ins().return_(&[chunk]);
+  builder.seal_block(grow_block);
+
+  builder.switch_to_block(bump_block);
+  builder.ins().store(MemFlags::trusted(), next, heap, 0);
+  builder.ins().return_(&[ptr]);
+  builder.seal_block(bump_block);
+
+  builder.finalize();
+  module.define_function(alloc, &mut ctx).unwrap();
+
+  alloc
+}
+
+// `str_concat(lhs, rhs)` copies the bytes of both strings after the pair of
+// a new string, the layout of a string is the one of a string literal
+fn generate_str_concat(module: &mut ObjectModule, alloc: FuncId) -> FuncId {
+  let str_concat_signature = signature(module, 2);
+  let str_concat = module
+    .declare_function(STR_CONCAT, Linkage::Local, &str_concat_signature)
+    .unwrap();
+
+  let mut ctx = module.make_context();
+  let mut builder_ctx = FunctionBuilderContext::new();
+
+  ctx.func.signature = str_concat_signature;
+
+  let mut builder = FunctionBuilder::new(&mut ctx.func, &mut builder_ctx);
+  let entry_block = builder.create_block();
+
+  builder.append_block_params_for_function_params(entry_block);
+  builder.switch_to_block(entry_block);
+  builder.seal_block(entry_block);
+
+  let lhs = builder.block_params(entry_block)[0];
+  let rhs = builder.block_params(entry_block)[1];
+
+  let lhs_ptr = builder.ins().load(types::I64, MemFlags::trusted(), lhs, 0);
+  let lhs_len = builder.ins().load(types::I64, MemFlags::trusted(), lhs, 8);
+  let rhs_ptr = builder.ins().load(types::I64, MemFlags::trusted(), rhs, 0);
+  let rhs_len = builder.ins().load(types::I64, MemFlags::trusted(), rhs, 8);
+  let len = builder.ins().iadd(lhs_len, rhs_len);
+
+  let size = builder
+    .ins()
+    .iadd_imm(len, DataContextBuilder::STR_HEADER_SIZE + 1);
+
+  let alloc = module.declare_func_in_func(alloc, builder.func);
+  let call = builder.ins().call(alloc, &[size]);
+  let string = builder.inst_results(call)[0];
+
+  let bytes = builder
+    .ins()
+    .iadd_imm(string, DataContextBuilder::STR_HEADER_SIZE);
+
+  builder.ins().store(MemFlags::trusted(), bytes, string, 0);
+  builder.ins().store(MemFlags::trusted(), len, string, 8);
+
+  let config = module.target_config();
+
+  builder.call_memcpy(config, bytes, lhs_ptr, lhs_len);
+
+  let rhs_bytes = builder.ins().iadd(bytes, lhs_len);
+
+  builder.call_memcpy(config, rhs_bytes, rhs_ptr, rhs_len);
+
+  let end = builder.ins().iadd(bytes, len);
+  let nul = builder.ins().iconst(types::I8, 0);
+
+  builder.ins().store(MemFlags::trusted(), nul, end, 0);
+  builder.ins().return_(&[string]);
+  builder.finalize();
+
+  module.define_function(str_concat, &mut ctx).unwrap();
+
+  str_concat
+}
+
+// `str_eq(lhs, rhs)` is `1` when both strings have the same length and the
+// same bytes, `0` otherwise
+fn generate_str_eq(module: &mut ObjectModule) -> FuncId {
+  let str_eq_signature = signature(module, 2);
+  let str_eq = module
+    .declare_function(STR_EQ, Linkage::Local, &str_eq_signature)
+    .unwrap();
+
+  let mut ctx = module.make_context();
+  let mut builder_ctx = FunctionBuilderContext::new();
+
+  ctx.func.signature = str_eq_signature;
+
+  let mut builder = FunctionBuilder::new(&mut ctx.func, &mut builder_ctx);
+  let entry_block = builder.create_block();
+  let bytes_block = builder.create_block();
+  let unequal_block = builder.create_block();
+
+  builder.append_block_params_for_function_params(entry_block);
+  builder.switch_to_block(entry_block);
+
+  let lhs = builder.block_params(entry_block)[0];
+  let rhs = builder.block_params(entry_block)[1];
+
+  let lhs_len = builder.ins().load(types::I64, MemFlags::trusted(), lhs, 8);
+  let rhs_len = builder.ins().load(types::I64, MemFlags::trusted(), rhs, 8);
+  let is_same_len = builder.ins().icmp(IntCC::Equal, lhs_len, rhs_len);
+
+  builder.ins().brz(is_same_len, unequal_block, &[]);
+  builder.ins().jump(bytes_block, &[]);
+  builder.seal_block(entry_block);
+
+  builder.switch_to_block(bytes_block);
+
+  let lhs_ptr = builder.ins().load(types::I64, MemFlags::trusted(), lhs, 0);
+  let rhs_ptr = builder.ins().load(types::I64, MemFlags::trusted(), rhs, 0);
+  let config = module.target_config();
+  let diff = builder.call_memcmp(config, lhs_ptr, rhs_ptr, lhs_len);
+  let is_eq = builder.ins().icmp_imm(IntCC::Equal, diff, 0);
+  let is_eq = builder.ins().bint(types::I64, is_eq);
+
+  builder.ins().return_(&[is_eq]);
+  builder.seal_block(bytes_block);
+
+  builder.switch_to_block(unequal_block);
+
+  let zero = builder.ins().iconst(types::I64, 0);
+
+  builder.ins().return_(&[zero]);
+  builder.seal_block(unequal_block);
+
+  builder.finalize();
+  module.define_function(str_eq, &mut ctx).unwrap();
+
+  str_eq
+}
@@ -1,49 +1,136 @@
+use super::runtime;
+
 use crate::back::codegen::cranelift::interface::{
-  CompiledFunction, DataContextBuilder, VariableBuilder,
+  data_name, declare_prelude_layouts, option_name, result_name, ty_kind,
+  Aliases, CompiledFunction, CompiledVal, DataContextBuilder, DataLayout,
+  FieldLayout, VariableBuilder, VariantLayout,
 };
 
 use crate::front::parser::ast::*;
 
 use cranelift::prelude::{
-  types, Block as CBlock, EntityRef, FunctionBuilder, InstBuilder, IntCC,
-  Value, Variable,
+  types, AbiParam, Block as CBlock, FloatCC, FunctionBuilder,
+  FunctionBuilderContext, InstBuilder, IntCC, JumpTableData, MemFlags,
+  Signature, StackSlotData, StackSlotKind, TrapCode, Value, Variable,
 };
 
-use cranelift_codegen::ir::GlobalValue;
-use cranelift_module::Module;
+use cranelift_module::{DataId, FuncId, FuncOrDataId, Linkage, Module};
 use cranelift_object::ObjectModule;
+use cranelift_preopt::optimize;
 
 use std::collections::HashMap;
 
+// a jump table is only used for the enums with small tags
+const JUMP_TABLE_MAX_LEN: i64 = 256;
+
 pub struct Translator<'a> {
   pub builder: FunctionBuilder<'a>,
   pub module: &'a mut ObjectModule,
   pub funs: &'a HashMap<String, CompiledFunction>,
-  pub globals: &'a mut HashMap<String, GlobalValue>,
+  pub globals: &'a mut HashMap<String, DataId>,
+  pub vals: &'a HashMap<String, CompiledVal>,
   pub vars: HashMap<String, Variable>,
-  pub program: &'a Program,
   pub ty: types::Type,
-  pub blocks: &'a mut Vec<CBlock>,
+  pub datas: &'a mut HashMap<String, DataLayout>,
+  pub aliases: &'a Aliases,
+  pub data_tys: HashMap<Value, String>,
+  pub data_vars: HashMap<String, String>,
+  pub sret: Option<Value>,
+  pub self_data: Option<String>,
+  // the blocks to which `continue` and `break` jump in each enclosing loop
+  pub blocks: &'a mut Vec<(CBlock, CBlock)>,
   pub variable_builder: &'a mut VariableBuilder,
   pub data_ctx_builder: &'a mut DataContextBuilder,
+  pub lambda_count: &'a mut u32,
 }
 
 impl<'a> Translator<'a> {
   pub fn translate(&mut self, block: &Block) -> Result<Value, String> {
-    let mut value = Value::new(0);
+    let mut value = self.translate_expr_lit_int(&0);
 
     for stmt in &block.stmts {
       value = self.translate_stmt(stmt);
     }
 
-    Ok(value)
+    let value = self.translate_word(value);
+
+    Ok(self.translate_return_value(value))
+  }
+
+  // the `val` items whose value is not a literal are computed before the
+  // program starts, in the order in which they are declared
+  pub fn translate_vals(&mut self, decls: &[&Decl]) -> Value {
+    for decl in decls {
+      let value = self.translate_expr(&decl.value);
+      let val = self.vals[&decl.pattern.to_string()].to_owned();
+      let ptr = self.translate_val_addr(&val);
+
+      match &val.data {
+        Some(data_name) => {
+          let size = self.datas[data_name].size;
+
+          self.copy_data(ptr, 0, value, size);
+        }
+        None => {
+          let value = self.translate_word(value);
+
+          self.builder.ins().store(MemFlags::trusted(), value, ptr, 0);
+        }
+      }
+    }
+
+    self.translate_expr_lit_int(&0)
+  }
+
+  // a returned data is copied to the stack slot given by the caller
+  fn translate_return_value(&mut self, value: Value) -> Value {
+    let Some(sret) = self.sret else {
+      return value;
+    };
+
+    match self.data_tys.get(&value).cloned() {
+      Some(data_name) => {
+        let size = self.datas[&data_name].size;
+
+        self.copy_data(sret, 0, value, size);
+        sret
+      }
+      None => value,
+    }
+  }
+
+  fn copy_data(&mut self, dst: Value, offset: i32, src: Value, size: u32) {
+    for word in (0..size).step_by(DataLayout::WORD as usize) {
+      let value = self.builder.ins().load(
+        types::I64,
+        MemFlags::trusted(),
+        src,
+        word as i32,
+      );
+
+      self.builder.ins().store(
+        MemFlags::trusted(),
+        value,
+        dst,
+        offset + word as i32,
+      );
+    }
   }
 
   fn translate_stmt(&mut self, stmt: &Stmt) -> Value {
     match &stmt.kind {
       StmtKind::Decl(decl) => self.translate_stmt_decl(decl),
       StmtKind::Expr(expr) => self.translate_stmt_expr(expr),
-      _ => panic!(),
+      // an alias only matters to the typechecker
+      StmtKind::Item(item) if matches!(item.kind, ItemKind::Type(_)) => {
+        self.translate_expr_lit_int(&0)
+      }
+      // a `val` inside of a block is a local that cannot be assigned, the
+      // typechecker rejects the other items
+      StmtKind::Item(item) => match &item.kind {
+        ItemKind::Val(decl) => self.translate_decl(decl),
+        _ => unreachable!(),
+      },
     }
   }
 
@@ -53,14 +140,38 @@ impl<'a> Translator<'a> {
 
   fn translate_decl(&mut self, decl: &Decl) -> Value {
     let value = self.translate_stmt_expr(&decl.value);
+    let value = self.translate_word(value);
+    let ty = self.builder.func.dfg.value_type(value);
 
-    let var =
-      self
-        .variable_builder
-        .create_var(&mut self.builder, value, types::I64);
+    let var = self
+      .variable_builder
+      .create_var(&mut self.builder, value, ty);
 
     self.vars.insert(decl.pattern.to_string(), var);
 
+    if let Some(data_name) = self.data_tys.get(&value) {
+      self
+        .data_vars
+        .insert(decl.pattern.to_string(), data_name.to_string());
+    }
+
+    // the type of the declaration also names the payloads of `None` and
+    // `Err`, which their value does not know
+    let ty = match &decl.ty {
+      Some(ty) => Some(ty.to_owned()),
+      None => decl.inferred_ty.borrow().to_owned(),
+    };
+
+    if let Some(ty) = ty {
+      let self_data = self.self_data.as_ref();
+
+      declare_prelude_layouts(&ty, self_data, self.aliases, self.datas);
+
+      if let Some(data_name) = data_name(&ty, self_data, self.aliases) {
+        self.data_vars.insert(decl.pattern.to_string(), data_name);
+      }
+    }
+
     value
   }
 
@@ -83,8 +194,38 @@ impl<'a> Translator<'a> {
       ExprKind::While(condition, body) => {
         self.translate_expr_while(condition, body)
       }
+      ExprKind::Break(value) => self.translate_expr_break(value),
+      ExprKind::Continue => self.translate_expr_continue(),
+      ExprKind::When(condition, consequence, alternative) => {
+        self.translate_expr_if_else(condition, consequence, Some(alternative))
+      }
+      ExprKind::IfElse(condition, consequence, maybe_alternative) => self
+        .translate_expr_if_else(
+          condition,
+          consequence,
+          maybe_alternative.as_deref(),
+        ),
       ExprKind::Return(value) => self.translate_expr_return(value),
       ExprKind::Block(block) => self.translate_expr_block(block),
+      ExprKind::Path(..) => self.translate_expr_path(expr),
+      ExprKind::Receiver => self.translate_expr_id(&".".into()),
+      ExprKind::Field(lhs, name) => self.translate_expr_field(lhs, name),
+      ExprKind::Struct(name, fields) => {
+        self.translate_expr_struct(name, fields)
+      }
+      ExprKind::Match(scrutinee, arms) => {
+        self.translate_expr_match(scrutinee, arms)
+      }
+      ExprKind::Is(lhs, pattern) => self.translate_expr_is(lhs, pattern),
+      ExprKind::For(name, range, maybe_step, body) => {
+        self.translate_expr_for(name, range, maybe_step, body)
+      }
+      ExprKind::ForEach(iterable, body) => {
+        self.translate_expr_for_each(iterable, body)
+      }
+      ExprKind::Array(elements) => self.translate_expr_array(elements),
+      ExprKind::Lambda(lambda) => self.translate_expr_lambda(lambda),
+      ExprKind::Try(operand) => self.translate_expr_try(operand),
       _ => todo!(),
     }
   }
@@ -94,7 +235,9 @@ impl<'a> Translator<'a> {
       LitKind::Bool(boolean) => self.translate_expr_lit_bool(boolean),
       LitKind::Int(num) => self.translate_expr_lit_int(num),
       LitKind::Float(num) => self.translate_expr_lit_float(num),
+      LitKind::Char(ch) => self.translate_expr_lit_char(ch),
       LitKind::Str(s) => self.translate_expr_lit_str(s),
+      LitKind::ByteStr(bytes) => self.translate_expr_lit_byte_str(bytes),
     }
   }
 
@@ -110,8 +253,9 @@ impl<'a> Translator<'a> {
     self.builder.ins().f64const(*num)
   }
 
-  fn _translate_expr_char(&mut self, _ch: &char) -> Value {
-    todo!()
+  // a character is stored as its unicode scalar value
+  fn translate_expr_lit_char(&mut self, ch: &char) -> Value {
+    self.builder.ins().iconst(types::I64, *ch as i64)
   }
 
   fn translate_expr_lit_str(&mut self, string: &String) -> Value {
@@ -123,18 +267,57 @@ impl<'a> Translator<'a> {
     )
   }
 
+  // the bytes of a byte string are ASCII, each one is its own character
+  fn translate_expr_lit_byte_str(&mut self, bytes: &[u8]) -> Value {
+    let string = bytes.iter().map(|byte| *byte as char).collect();
+
+    self.translate_expr_lit_str(&string)
+  }
+
   fn translate_expr_id(&mut self, name: &String) -> Value {
     if let Some(decl) = self.vars.get(&name.to_string()) {
-      return self.builder.use_var(*decl);
+      let value = self.builder.use_var(*decl);
+
+      if let Some(data_name) = self.data_vars.get(name) {
+        self.data_tys.insert(value, data_name.to_string());
+      }
+
+      return value;
     }
 
-    if let Some(_fun) = self.funs.get(&name.to_string()) {
-      todo!();
+    if let Some(val) = self.vals.get(name).cloned() {
+      return self.translate_val(&val);
+    }
+
+    if self.funs.contains_key(name) {
+      return self.translate_fun_closure(name);
+    }
+
+    if let Some(value) = self.translate_expr_prelude_variant(name, &[]) {
+      return value;
     }
 
     panic!("translate expr id")
   }
 
+  fn translate_val(&mut self, val: &CompiledVal) -> Value {
+    let ptr = self.translate_val_addr(val);
+
+    match &val.data {
+      Some(data_name) => {
+        self.data_tys.insert(ptr, data_name.to_string());
+        ptr
+      }
+      None => self.builder.ins().load(val.ty, MemFlags::trusted(), ptr, 0),
+    }
+  }
+
+  fn translate_val_addr(&mut self, val: &CompiledVal) -> Value {
+    let global = self.module.declare_data_in_func(val.id, self.builder.func);
+
+    self.builder.ins().symbol_value(types::I64, global)
+  }
+
   fn translate_expr_un_op(&mut self, op: &UnOp, rhs: &Expr) -> Value {
     let rhs = self.translate_stmt_expr(rhs);
 
@@ -160,12 +343,17 @@ impl<'a> Translator<'a> {
     op: &BinOp,
     rhs: &Expr,
   ) -> Value {
+    if let BinOpKind::As = op.node {
+      return self.translate_expr_bin_op_as(lhs, rhs);
+    }
+
     let lhs = self.translate_stmt_expr(lhs);
     let rhs = self.translate_stmt_expr(rhs);
 
     match &op.node {
       BinOpKind::Add => self.translate_expr_bin_op_add(lhs, rhs),
       BinOpKind::Sub => self.translate_expr_bin_op_sub(lhs, rhs),
+      BinOpKind::Concat => self.translate_expr_bin_op_concat(lhs, rhs),
       BinOpKind::Mul => self.translate_expr_bin_op_mul(lhs, rhs),
       BinOpKind::Div => self.translate_expr_bin_op_div(lhs, rhs),
       BinOpKind::Rem => self.translate_expr_bin_op_rem(lhs, rhs),
@@ -186,6 +374,53 @@ impl<'a> Translator<'a> {
     }
   }
 
+  // every value is a word, a cast converts between integers and floats and
+  // truncates the integer to the width of the type it is cast to
+  fn translate_expr_bin_op_as(&mut self, lhs: &Expr, rhs: &Expr) -> Value {
+    let value = self.translate_stmt_expr(lhs);
+    let value_ty = self.builder.func.dfg.value_type(value);
+    let kind = ty_kind(&Ty::from(rhs), self.aliases);
+
+    let value = if value_ty.is_bool() {
+      self.builder.ins().bint(self.ty, value)
+    } else {
+      value
+    };
+
+    match kind {
+      TyKind::F32 | TyKind::F64 if value_ty.is_float() => value,
+      TyKind::F32 | TyKind::F64 => {
+        self.builder.ins().fcvt_from_sint(types::F64, value)
+      }
+      _ if value_ty.is_float() => {
+        let value = self.builder.ins().fcvt_to_sint_sat(self.ty, value);
+
+        self.translate_expr_truncate(value, &kind)
+      }
+      _ => self.translate_expr_truncate(value, &kind),
+    }
+  }
+
+  fn translate_expr_truncate(&mut self, value: Value, kind: &TyKind) -> Value {
+    match kind {
+      TyKind::U8 => self.builder.ins().band_imm(value, 0xff),
+      TyKind::U16 => self.builder.ins().band_imm(value, 0xffff),
+      TyKind::U32 => self.builder.ins().band_imm(value, 0xffff_ffff),
+      TyKind::S8 | TyKind::S16 | TyKind::S32 => {
+        let ty = match kind {
+          TyKind::S8 => types::I8,
+          TyKind::S16 => types::I16,
+          _ => types::I32,
+        };
+
+        let value = self.builder.ins().ireduce(ty, value);
+
+        self.builder.ins().sextend(self.ty, value)
+      }
+      _ => value,
+    }
+  }
+
   fn translate_expr_bin_op_add(&mut self, lhs: Value, rhs: Value) -> Value {
     self.builder.ins().iadd(lhs, rhs)
   }
@@ -194,6 +429,10 @@ impl<'a> Translator<'a> {
     self.builder.ins().isub(lhs, rhs)
   }
 
+  fn translate_expr_bin_op_concat(&mut self, lhs: Value, rhs: Value) -> Value {
+    self.translate_runtime_call(runtime::STR_CONCAT, &[lhs, rhs])
+  }
+
   fn translate_expr_bin_op_mul(&mut self, lhs: Value, rhs: Value) -> Value {
     self.builder.ins().imul(lhs, rhs)
   }
@@ -285,6 +524,7 @@ impl<'a> Translator<'a> {
     rhs: &Expr,
   ) -> Value {
     let rhs = self.translate_expr(rhs);
+    let rhs = self.translate_word(rhs);
     let variable = self.vars.get(&lhs.to_string()).unwrap();
 
     self.builder.def_var(*variable, rhs);
@@ -299,6 +539,7 @@ impl<'a> Translator<'a> {
     rhs: &Expr,
   ) -> Value {
     let rhs = self.translate_stmt_expr(rhs);
+    let rhs = self.translate_word(rhs);
 
     match &lhs.kind {
       ExprKind::Identifier(name) => {
@@ -314,6 +555,8 @@ impl<'a> Translator<'a> {
           BinOpKind::BitAnd => self.translate_expr_bin_op_bit_and(lhs, rhs),
           BinOpKind::BitXor => self.translate_expr_bin_op_bit_xor(lhs, rhs),
           BinOpKind::BitOr => self.translate_expr_bin_op_bit_or(lhs, rhs),
+          BinOpKind::Shl => self.translate_expr_bin_op_shl(lhs, rhs),
+          BinOpKind::Shr => self.translate_expr_bin_op_shr(lhs, rhs),
           _ => panic!("binary operation not valid"),
         };
 
@@ -331,7 +574,7 @@ impl<'a> Translator<'a> {
 
     self.builder.ins().jump(body_block, &[]);
     self.builder.switch_to_block(body_block);
-    self.blocks.push(end_block);
+    self.blocks.push((body_block, end_block));
     self.builder.switch_to_block(body_block);
 
     for stmt in &body.stmts {
@@ -358,14 +601,173 @@ impl<'a> Translator<'a> {
 
     self.builder.ins().brz(condition_value, end_block, &[]);
     self.builder.ins().jump(body_block, &[]);
-    self.blocks.push(end_block);
+    self.blocks.push((header_block, end_block));
+    self.builder.seal_block(body_block);
+    self.builder.switch_to_block(body_block);
+
+    for stmt in &body.stmts {
+      self.translate_stmt(stmt);
+    }
+
+    self.builder.ins().jump(header_block, &[]);
+    self.blocks.pop();
+    self.builder.seal_block(header_block);
+    self.builder.seal_block(end_block);
+    self.builder.switch_to_block(end_block);
+    self.builder.ins().iconst(self.ty, 0)
+  }
+
+  fn translate_expr_for(
+    &mut self,
+    name: &Expr,
+    range: &Expr,
+    maybe_step: &Option<PBox<Expr>>,
+    body: &Block,
+  ) -> Value {
+    let ExprKind::BinOp(start, op, end) = &range.kind else {
+      unreachable!()
+    };
+
+    let start = self.translate_stmt_expr(start);
+    let end = self.translate_stmt_expr(end);
+
+    let step = match maybe_step {
+      Some(step) => self.translate_stmt_expr(step),
+      None => self.builder.ins().iconst(self.ty, 1),
+    };
+
+    // a range only counts upwards, the program stops instead of looping
+    // forever when the step does not move the counter
+    if maybe_step.is_some() {
+      let is_stuck =
+        self
+          .builder
+          .ins()
+          .icmp_imm(IntCC::SignedLessThanOrEqual, step, 0);
+
+      self.builder.ins().trapnz(is_stuck, TrapCode::User(0));
+    }
+
+    let cond = range_cond(op);
+
+    let name = name.to_string();
+
+    self.translate_counted_loop(&name, start, end, step, cond, None, body)
+  }
+
+  // an array is walked by its index, the element is loaded in the body
+  fn translate_expr_for_each(
+    &mut self,
+    iterable: &Expr,
+    body: &Block,
+  ) -> Value {
+    let name = "it".to_string();
+
+    if let ExprKind::BinOp(start, op, end) = &iterable.kind {
+      if let BinOpKind::Range | BinOpKind::RangeInclusive = op.node {
+        let start = self.translate_stmt_expr(start);
+        let end = self.translate_stmt_expr(end);
+        let step = self.builder.ins().iconst(self.ty, 1);
+        let cond = range_cond(op);
+
+        return self
+          .translate_counted_loop(&name, start, end, step, cond, None, body);
+      }
+    }
+
+    let array = self.translate_expr(iterable);
+    let start = self.builder.ins().iconst(self.ty, 0);
+    let end = self
+      .builder
+      .ins()
+      .load(self.ty, MemFlags::trusted(), array, 0);
+    let step = self.builder.ins().iconst(self.ty, 1);
+    let cond = IntCC::SignedLessThan;
+
+    self.translate_counted_loop(
+      &name,
+      start,
+      end,
+      step,
+      cond,
+      Some(array),
+      body,
+    )
+  }
+
+  // the counter is stepped in the latch, so that the body may assign the loop
+  // variable without changing the number of iterations
+  #[allow(clippy::too_many_arguments)]
+  fn translate_counted_loop(
+    &mut self,
+    name: &String,
+    start: Value,
+    end: Value,
+    step: Value,
+    cond: IntCC,
+    maybe_array: Option<Value>,
+    body: &Block,
+  ) -> Value {
+    let counter =
+      self
+        .variable_builder
+        .create_var(&mut self.builder, start, self.ty);
+
+    let header_block = self.builder.create_block();
+    let body_block = self.builder.create_block();
+    let latch_block = self.builder.create_block();
+    let end_block = self.builder.create_block();
+
+    self.builder.ins().jump(header_block, &[]);
+    self.builder.switch_to_block(header_block);
+
+    let index = self.builder.use_var(counter);
+    let condition_value = self.builder.ins().icmp(cond, index, end);
+
+    self.builder.ins().brz(condition_value, end_block, &[]);
+    self.builder.ins().jump(body_block, &[]);
+    self.blocks.push((latch_block, end_block));
     self.builder.seal_block(body_block);
     self.builder.switch_to_block(body_block);
 
+    let value = match maybe_array {
+      Some(array) => {
+        let offset = self.builder.ins().imul_imm(index, 8);
+        let ptr = self.builder.ins().iadd(array, offset);
+
+        // the length of the array is stored before its elements
+        self
+          .builder
+          .ins()
+          .load(self.ty, MemFlags::trusted(), ptr, 8)
+      }
+      None => index,
+    };
+
+    let var =
+      self
+        .variable_builder
+        .create_var(&mut self.builder, value, self.ty);
+
+    let shadowed = self.vars.insert(name.to_string(), var);
+
     for stmt in &body.stmts {
       self.translate_stmt(stmt);
     }
 
+    match shadowed {
+      Some(shadowed) => self.vars.insert(name.to_string(), shadowed),
+      None => self.vars.remove(name),
+    };
+
+    self.builder.ins().jump(latch_block, &[]);
+    self.builder.seal_block(latch_block);
+    self.builder.switch_to_block(latch_block);
+
+    let index = self.builder.use_var(counter);
+    let next_index = self.builder.ins().iadd(index, step);
+
+    self.builder.def_var(counter, next_index);
     self.builder.ins().jump(header_block, &[]);
     self.blocks.pop();
     self.builder.seal_block(header_block);
@@ -374,6 +776,121 @@ impl<'a> Translator<'a> {
     self.builder.ins().iconst(self.ty, 0)
   }
 
+  // the value given to `break` is computed but the value of a loop is unused
+  fn translate_expr_break(&mut self, value: &Option<PBox<Expr>>) -> Value {
+    if let Some(value) = value {
+      self.translate_stmt_expr(value);
+    }
+
+    let (_, end_block) = self.blocks[self.blocks.len() - 1];
+
+    self.translate_jump_out(end_block)
+  }
+
+  fn translate_expr_continue(&mut self) -> Value {
+    let (next_block, _) = self.blocks[self.blocks.len() - 1];
+
+    self.translate_jump_out(next_block)
+  }
+
+  // the code following a jump out of the current block cannot be reached, it
+  // is written in a block of its own
+  fn translate_jump_out(&mut self, block: CBlock) -> Value {
+    self.builder.ins().jump(block, &[]);
+
+    let new_block = self.builder.create_block();
+
+    self.builder.seal_block(new_block);
+    self.builder.switch_to_block(new_block);
+    self.translate_expr_lit_int(&0)
+  }
+
+  // the value of an `if` is the value of the branch taken, an `if` without
+  // `else` has no value
+  fn translate_expr_if_else(
+    &mut self,
+    condition: &Expr,
+    consequence: &Expr,
+    maybe_alternative: Option<&Expr>,
+  ) -> Value {
+    let (condition, binding) = match &condition.kind {
+      ExprKind::Is(lhs, pattern) => {
+        let value = self.translate_expr(lhs);
+        let value = self.translate_word(value);
+
+        (
+          self.translate_is_test(value, pattern),
+          Some((value, pattern)),
+        )
+      }
+      _ => (self.translate_stmt_expr(condition), None),
+    };
+
+    let then_block = self.builder.create_block();
+    let else_block = self.builder.create_block();
+    let end_block = self.builder.create_block();
+
+    self.builder.ins().brz(condition, else_block, &[]);
+    self.builder.ins().jump(then_block, &[]);
+    self.builder.seal_block(then_block);
+    self.builder.seal_block(else_block);
+    self.builder.switch_to_block(then_block);
+
+    // the names bound by an `is` condition are only read once its variant is
+    // known to match, and they live in the consequence only
+    let vars = self.vars.to_owned();
+    let data_vars = self.data_vars.to_owned();
+
+    if let Some((value, pattern)) = binding {
+      self.translate_pattern_bindings(value, pattern);
+    }
+
+    let value = self.translate_stmt_expr(consequence);
+    let data_name = self.data_tys.get(&value).cloned();
+
+    self.vars = vars;
+    self.data_vars = data_vars;
+
+    let Some(alternative) = maybe_alternative else {
+      self.builder.ins().jump(end_block, &[]);
+      self.builder.switch_to_block(else_block);
+      self.builder.ins().jump(end_block, &[]);
+      self.builder.seal_block(end_block);
+      self.builder.switch_to_block(end_block);
+
+      return self.translate_expr_lit_int(&0);
+    };
+
+    let value = self.translate_word(value);
+    let ty = self.builder.func.dfg.value_type(value);
+
+    self.builder.append_block_param(end_block, ty);
+    self.builder.ins().jump(end_block, &[value]);
+    self.builder.switch_to_block(else_block);
+
+    let value = self.translate_stmt_expr(alternative);
+    let value = self.translate_word(value);
+
+    // the branches of an `if` used as a statement may have values of
+    // different types, the value is not used then
+    let value = match self.builder.func.dfg.value_type(value) {
+      value_ty if value_ty == ty => value,
+      _ => self.builder.ins().bitcast(ty, value),
+    };
+
+    self.builder.ins().jump(end_block, &[value]);
+    self.builder.seal_block(end_block);
+    self.builder.switch_to_block(end_block);
+
+    let value = self.builder.block_params(end_block)[0];
+
+    if let Some(data_name) = data_name {
+      self.data_tys.insert(value, data_name);
+    }
+
+    value
+  }
+
   fn translate_expr_return(
     &mut self,
     return_value: &Option<PBox<Expr>>,
@@ -382,6 +899,7 @@ impl<'a> Translator<'a> {
 
     if let Some(e) = return_value {
       value = self.translate_stmt_expr(e);
+      value = self.translate_return_value(value);
       self.builder.ins().return_(&[value]);
     } else {
       self.builder.ins().return_(&[]);
@@ -395,40 +913,1029 @@ impl<'a> Translator<'a> {
     value
   }
 
+  // the names declared inside of a block are dropped at its end
   fn translate_expr_block(&mut self, block: &Block) -> Value {
+    let vars = self.vars.to_owned();
+    let data_vars = self.data_vars.to_owned();
     let mut value = self.translate_expr_lit_int(&0);
 
     for stmt in &block.stmts {
       value = self.translate_stmt(stmt);
     }
 
+    self.vars = vars;
+    self.data_vars = data_vars;
+    value
+  }
+
+  // an array is stored as its length followed by its elements
+  fn translate_expr_array(&mut self, elements: &[PBox<Expr>]) -> Value {
+    let ptr = self.create_slot((elements.len() as u32 + 1) * 8);
+    let len = self.builder.ins().iconst(self.ty, elements.len() as i64);
+
+    self.builder.ins().store(MemFlags::trusted(), len, ptr, 0);
+
+    for (index, element) in elements.iter().enumerate() {
+      let value = self.translate_expr(element);
+      let value = self.translate_word(value);
+      let offset = (index as i32 + 1) * 8;
+
+      self
+        .builder
+        .ins()
+        .store(MemFlags::trusted(), value, ptr, offset);
+    }
+
+    ptr
+  }
+
+  // a closure is a pair of the address of its function and of its
+  // environment, to which the captured values are copied. both are allocated
+  // on the heap, so that a closure outlives the function that creates it
+  fn translate_expr_lambda(&mut self, lambda: &Lambda) -> Value {
+    let captures = lambda.captures.borrow().to_owned();
+    let func_name = format!("lambda{}", self.lambda_count);
+
+    *self.lambda_count += 1;
+
+    let signature = self.closure_signature(lambda.inputs.len());
+
+    let func_id = self
+      .module
+      .declare_function(&func_name, Linkage::Local, &signature)
+      .unwrap();
+
+    let mut ctx = self.module.make_context();
+    let mut builder_context = FunctionBuilderContext::new();
+
+    ctx.func.signature = signature;
+
+    let mut builder = FunctionBuilder::new(&mut ctx.func, &mut builder_context);
+    let entry_block = builder.create_block();
+
+    builder.append_block_params_for_function_params(entry_block);
+    builder.switch_to_block(entry_block);
+    builder.seal_block(entry_block);
+
+    let env = builder.block_params(entry_block)[0];
+    let mut vars = HashMap::new();
+    let mut data_vars = HashMap::new();
+
+    for (i, name) in captures.iter().enumerate() {
+      let value =
+        builder
+          .ins()
+          .load(types::I64, MemFlags::trusted(), env, i as i32 * 8);
+
+      let var =
+        self
+          .variable_builder
+          .create_var(&mut builder, value, types::I64);
+
+      vars.insert(name.to_string(), var);
+
+      if let Some(data_name) = self.data_vars.get(name) {
+        data_vars.insert(name.to_string(), data_name.to_string());
+      }
+    }
+
+    for (i, input) in lambda.inputs.iter().enumerate() {
+      let value = builder.block_params(entry_block)[i + 1];
+
+      let var =
+        self
+          .variable_builder
+          .create_var(&mut builder, value, types::I64);
+
+      vars.insert(input.pattern.to_string(), var);
+    }
+
+    let mut blocks = vec![];
+
+    let mut translator = Translator {
+      builder,
+      module: self.module,
+      funs: self.funs,
+      globals: self.globals,
+      vals: self.vals,
+      vars,
+      ty: self.ty,
+      datas: self.datas,
+      aliases: self.aliases,
+      data_tys: HashMap::new(),
+      data_vars,
+      sret: None,
+      self_data: self.self_data.to_owned(),
+      blocks: &mut blocks,
+      variable_builder: self.variable_builder,
+      data_ctx_builder: self.data_ctx_builder,
+      lambda_count: self.lambda_count,
+    };
+
+    let value = translator.translate(&lambda.body).unwrap();
+    let value = translator.translate_word(value);
+
+    translator.builder.ins().return_(&[value]);
+    translator.builder.finalize();
+
+    optimize(&mut ctx, self.module.isa()).unwrap();
+    self.module.define_function(func_id, &mut ctx).unwrap();
+
+    let env = if captures.is_empty() {
+      self.builder.ins().iconst(types::I64, 0)
+    } else {
+      let env = self.create_heap(captures.len() as i64 * 8);
+
+      for (i, name) in captures.iter().enumerate() {
+        let value = self.translate_expr_id(name);
+
+        // a captured data is copied along, its stack slot does not outlive
+        // the function either
+        let value = match self.data_vars.get(name) {
+          Some(data_name) => {
+            let size = self.datas[data_name].size;
+            let data = self.create_heap(size as i64);
+
+            self.copy_data(data, 0, value, size);
+            data
+          }
+          None => value,
+        };
+
+        self
+          .builder
+          .ins()
+          .store(MemFlags::trusted(), value, env, i as i32 * 8);
+      }
+
+      env
+    };
+
+    self.create_closure(func_id, env)
+  }
+
+  // the environment is passed to the function of a closure as its first input
+  // a function used as a value goes through a thunk ignoring the environment
+  fn translate_fun_closure(&mut self, name: &str) -> Value {
+    let func = &self.funs[name];
+    let func_name = format!("{name}::closure");
+
+    let func_id = match self.module.get_name(&func_name) {
+      Some(FuncOrDataId::Func(func_id)) => func_id,
+      _ => {
+        let signature = self.closure_signature(func.param_count);
+
+        let func_id = self
+          .module
+          .declare_function(&func_name, Linkage::Local, &signature)
+          .unwrap();
+
+        let mut ctx = self.module.make_context();
+        let mut builder_context = FunctionBuilderContext::new();
+
+        ctx.func.signature = signature;
+
+        let mut builder =
+          FunctionBuilder::new(&mut ctx.func, &mut builder_context);
+
+        let entry_block = builder.create_block();
+
+        builder.append_block_params_for_function_params(entry_block);
+        builder.switch_to_block(entry_block);
+        builder.seal_block(entry_block);
+
+        let arguments = builder.block_params(entry_block)[1..].to_vec();
+        let local_func =
+          self.module.declare_func_in_func(func.id, builder.func);
+        let call = builder.ins().call(local_func, &arguments);
+        let value = builder.inst_results(call)[0];
+
+        builder.ins().return_(&[value]);
+        builder.finalize();
+
+        self.module.define_function(func_id, &mut ctx).unwrap();
+
+        func_id
+      }
+    };
+
+    let env = self.builder.ins().iconst(types::I64, 0);
+
+    self.create_closure(func_id, env)
+  }
+
+  // a closure is a pair of the code address and the environment pointer
+  fn create_closure(&mut self, func_id: FuncId, env: Value) -> Value {
+    let func_ref = self.module.declare_func_in_func(func_id, self.builder.func);
+    let code = self.builder.ins().func_addr(types::I64, func_ref);
+    let closure = self.create_heap(16);
+
+    self
+      .builder
+      .ins()
+      .store(MemFlags::trusted(), code, closure, 0);
+    self
+      .builder
+      .ins()
+      .store(MemFlags::trusted(), env, closure, 8);
+
+    closure
+  }
+
+  fn closure_signature(&self, input_count: usize) -> Signature {
+    let mut signature = self.module.make_signature();
+
+    for _ in 0..=input_count {
+      signature.params.push(AbiParam::new(types::I64));
+    }
+
+    signature.returns.push(AbiParam::new(types::I64));
+    signature
+  }
+
+  fn translate_expr_call_closure(
+    &mut self,
+    callee: &Expr,
+    args: &[PBox<Expr>],
+  ) -> Value {
+    let closure = self.translate_expr(callee);
+
+    let code =
+      self
+        .builder
+        .ins()
+        .load(types::I64, MemFlags::trusted(), closure, 0);
+
+    let env =
+      self
+        .builder
+        .ins()
+        .load(types::I64, MemFlags::trusted(), closure, 8);
+
+    let mut arguments = vec![env];
+
+    for arg in args {
+      let value = self.translate_expr(arg);
+
+      arguments.push(self.translate_word(value));
+    }
+
+    let signature = self.closure_signature(args.len());
+    let sig_ref = self.builder.import_signature(signature);
+    let call = self.builder.ins().call_indirect(sig_ref, code, &arguments);
+
+    self.builder.inst_results(call)[0]
+  }
+
+  fn translate_expr_field(&mut self, lhs: &Expr, name: &Expr) -> Value {
+    let value = self.translate_expr(lhs);
+    let field = self.field_layout(value, &name.to_string());
+
+    self.load_field(value, &field)
+  }
+
+  fn translate_expr_struct(
+    &mut self,
+    name: &Expr,
+    fields: &[PBox<FieldExpr>],
+  ) -> Value {
+    let data_name = self.path_name(name);
+    let ptr = self.create_data_slot(&data_name);
+    let zero = self.translate_expr_lit_int(&0);
+
+    // the fields left out of the literal are zeroed, they are all words
+    for field in &self.datas[&data_name].fields {
+      if !fields
+        .iter()
+        .any(|expr| expr.name.to_string() == field.name)
+      {
+        self
+          .builder
+          .ins()
+          .store(MemFlags::trusted(), zero, ptr, field.offset);
+      }
+    }
+
+    for field in fields {
+      let value = match &field.value {
+        Some(value) => self.translate_expr(value),
+        None => self.translate_expr(&field.name),
+      };
+
+      let field = self.field_layout(ptr, &field.name.to_string());
+
+      self.store_field(ptr, &field, value);
+    }
+
+    ptr
+  }
+
+  // a path names a variant or an associated function, which is used as a
+  // closure without environment
+  fn translate_expr_path(&mut self, path: &Expr) -> Value {
+    if let Some(value) = self.translate_expr_variant(path, &[]) {
+      return value;
+    }
+
+    let name = self.path_name(path);
+
+    match self.funs.contains_key(&name) {
+      true => self.translate_fun_closure(&name),
+      false => unreachable!("the analyzer reports the path `{name}`"),
+    }
+  }
+
+  // the tag of a variant is stored in the first word, followed by its
+  // payload. `None` is returned if the path does not name a variant
+  fn translate_expr_variant(
+    &mut self,
+    path: &Expr,
+    args: &[PBox<Expr>],
+  ) -> Option<Value> {
+    if let ExprKind::Identifier(name) = &path.kind {
+      return self.translate_expr_prelude_variant(name, args);
+    }
+
+    let ExprKind::Path(lhs, name) = &path.kind else {
+      return None;
+    };
+    let enum_name = self.path_name(lhs);
+
+    let variant = self
+      .datas
+      .get(&enum_name)?
+      .variant(&name.to_string())?
+      .to_owned();
+
+    let values = args
+      .iter()
+      .map(|arg| self.translate_expr(arg))
+      .collect::<Vec<_>>();
+
+    Some(self.create_variant(&enum_name, &variant, &values))
+  }
+
+  // `Some`, `None`, `Ok` and `Err` are laid out after the data type of their
+  // payload, the payload of the other variant is not known and is a word.
+  // `None` is returned if a value or a function shadows them
+  fn translate_expr_prelude_variant(
+    &mut self,
+    name: &str,
+    args: &[PBox<Expr>],
+  ) -> Option<Value> {
+    if self.vars.contains_key(name)
+      || self.vals.contains_key(name)
+      || self.funs.contains_key(name)
+    {
+      return None;
+    }
+
+    let values = args
+      .iter()
+      .map(|arg| self.translate_expr(arg))
+      .collect::<Vec<_>>();
+
+    let payload = values
+      .first()
+      .and_then(|value| self.data_tys.get(value).cloned());
+
+    let (data_name, layout) = match name {
+      "Some" => (option_name(&payload), DataLayout::with_option(payload)),
+      "None" => (option_name(&None), DataLayout::with_option(None)),
+      "Ok" => (
+        result_name(&payload, &None),
+        DataLayout::with_result(payload, None),
+      ),
+      "Err" => (
+        result_name(&None, &payload),
+        DataLayout::with_result(None, payload),
+      ),
+      _ => return None,
+    };
+
+    let variant = layout.variant(name)?.to_owned();
+
+    self.datas.entry(data_name.to_owned()).or_insert(layout);
+
+    Some(self.create_variant(&data_name, &variant, &values))
+  }
+
+  // the tag of a variant is stored in the first word, followed by its payload
+  fn create_variant(
+    &mut self,
+    data_name: &str,
+    variant: &VariantLayout,
+    values: &[Value],
+  ) -> Value {
+    let ptr = self.create_data_slot(data_name);
+    let tag = self.translate_expr_lit_int(&variant.tag);
+
+    self.builder.ins().store(MemFlags::trusted(), tag, ptr, 0);
+
+    for (value, field) in values.iter().zip(&variant.payload) {
+      self.store_field(ptr, field, *value);
+    }
+
+    ptr
+  }
+
+  // `None` and `Err` are returned as they are, their layout does not depend
+  // on the payload of `Some` and `Ok`, which is the value of `x?`
+  fn translate_expr_try(&mut self, operand: &Expr) -> Value {
+    let value = self.translate_expr(operand);
+    let layout = self.datas[&self.data_tys[&value]].to_owned();
+
+    let Some(variant) = layout
+      .variants
+      .iter()
+      .find(|variant| variant.name == "Some" || variant.name == "Ok")
+    else {
+      panic!("translate try error")
+    };
+
+    let tag =
+      self
+        .builder
+        .ins()
+        .load(types::I64, MemFlags::trusted(), value, 0);
+
+    let return_block = self.builder.create_block();
+    let next_block = self.builder.create_block();
+
+    let condition = self.builder.ins().icmp_imm(IntCC::Equal, tag, variant.tag);
+
+    self.builder.ins().brnz(condition, next_block, &[]);
+    self.builder.ins().jump(return_block, &[]);
+    self.builder.seal_block(return_block);
+    self.builder.switch_to_block(return_block);
+
+    let return_value = self.translate_return_value(value);
+
+    self.builder.ins().return_(&[return_value]);
+    self.builder.seal_block(next_block);
+    self.builder.switch_to_block(next_block);
+    self.load_field(value, &variant.payload[0])
+  }
+
+  // the arms are tested one after the other, except when every arm tests
+  // the tag of an enum only, then the tag directly indexes a jump table
+  fn translate_expr_match(
+    &mut self,
+    scrutinee: &Expr,
+    arms: &[PBox<Arm>],
+  ) -> Value {
+    let value = self.translate_expr(scrutinee);
+    let value = self.translate_word(value);
+    let end_block = self.builder.create_block();
+
+    self.builder.append_block_param(end_block, types::I64);
+
+    let body_blocks = arms
+      .iter()
+      .map(|_| self.builder.create_block())
+      .collect::<Vec<_>>();
+
+    match self.jump_table_targets(value, arms, &body_blocks) {
+      Some(targets) => self.translate_match_table(value, targets),
+      None => self.translate_match_chain(value, arms, &body_blocks),
+    }
+
+    let mut data_name = None;
+
+    for (arm, body_block) in arms.iter().zip(body_blocks) {
+      self.builder.switch_to_block(body_block);
+      self.builder.seal_block(body_block);
+      self.translate_pattern_bindings(value, &arm.pattern);
+
+      let value = self.translate_expr(&arm.body);
+
+      data_name = data_name.or_else(|| self.data_tys.get(&value).cloned());
+
+      let value = self.translate_word(value);
+
+      self.builder.ins().jump(end_block, &[value]);
+    }
+
+    self.builder.seal_block(end_block);
+    self.builder.switch_to_block(end_block);
+
+    let value = self.builder.block_params(end_block)[0];
+
+    if let Some(data_name) = data_name {
+      self.data_tys.insert(value, data_name);
+    }
+
     value
   }
 
+  // the pattern of an `is` binds names only in the condition of an `if`
+  fn translate_expr_is(&mut self, lhs: &Expr, pattern: &Pattern) -> Value {
+    let value = self.translate_expr(lhs);
+    let value = self.translate_word(value);
+
+    self.translate_is_test(value, pattern)
+  }
+
+  fn translate_is_test(&mut self, value: Value, pattern: &Pattern) -> Value {
+    match self.translate_pattern_test(value, pattern) {
+      Some(condition) => condition,
+      None => self.builder.ins().bconst(types::B1, true),
+    }
+  }
+
+  // the typechecker makes sure that the match is exhaustive, so falling
+  // through every arm cannot happen
+  fn translate_match_chain(
+    &mut self,
+    value: Value,
+    arms: &[PBox<Arm>],
+    body_blocks: &[CBlock],
+  ) {
+    for (arm, body_block) in arms.iter().zip(body_blocks) {
+      match self.translate_pattern_test(value, &arm.pattern) {
+        Some(condition) => {
+          let next_block = self.builder.create_block();
+
+          self.builder.ins().brz(condition, next_block, &[]);
+          self.builder.ins().jump(*body_block, &[]);
+          self.builder.seal_block(next_block);
+          self.builder.switch_to_block(next_block);
+        }
+        None => {
+          self.builder.ins().jump(*body_block, &[]);
+          return;
+        }
+      }
+    }
+
+    self.builder.ins().trap(TrapCode::UnreachableCodeReached);
+  }
+
+  fn translate_match_table(
+    &mut self,
+    value: Value,
+    targets: Vec<Option<CBlock>>,
+  ) {
+    let trap_block = self.builder.create_block();
+    let mut jump_table_data = JumpTableData::with_capacity(targets.len());
+
+    for target in targets {
+      jump_table_data.push_entry(target.unwrap_or(trap_block));
+    }
+
+    let jump_table = self.builder.create_jump_table(jump_table_data);
+
+    let tag =
+      self
+        .builder
+        .ins()
+        .load(types::I64, MemFlags::trusted(), value, 0);
+
+    // the index of a jump table is a 32 bits integer
+    let tag = self.builder.ins().ireduce(types::I32, tag);
+
+    self.builder.ins().br_table(tag, trap_block, jump_table);
+    self.builder.seal_block(trap_block);
+    self.builder.switch_to_block(trap_block);
+    self.builder.ins().trap(TrapCode::UnreachableCodeReached);
+  }
+
+  // the block of the first arm matching each tag of the enum, `None` if an
+  // arm needs more than the tag to be tested
+  fn jump_table_targets(
+    &self,
+    value: Value,
+    arms: &[PBox<Arm>],
+    body_blocks: &[CBlock],
+  ) -> Option<Vec<Option<CBlock>>> {
+    let layout = self.datas.get(self.data_tys.get(&value)?)?;
+    let max_tag = layout.variants.iter().map(|variant| variant.tag).max()?;
+
+    if layout.variants.iter().any(|variant| variant.tag < 0)
+      || max_tag >= JUMP_TABLE_MAX_LEN
+    {
+      return None;
+    }
+
+    let mut targets = vec![None; max_tag as usize + 1];
+
+    for (arm, body_block) in arms.iter().zip(body_blocks).rev() {
+      match &arm.pattern.kind {
+        PatternKind::Variant(path, patterns)
+          if patterns.iter().all(|pattern| pattern.is_irrefutable()) =>
+        {
+          let variant = layout.variant(&variant_name(path))?;
+
+          targets[variant.tag as usize] = Some(*body_block);
+        }
+        PatternKind::Identifier(_, name) if self.is_none(value, name) => {
+          let variant = layout.variant("None")?;
+
+          targets[variant.tag as usize] = Some(*body_block);
+        }
+        PatternKind::Underscore | PatternKind::Identifier(..) => {
+          for variant in &layout.variants {
+            targets[variant.tag as usize] = Some(*body_block);
+          }
+        }
+        _ => return None,
+      }
+    }
+
+    Some(targets)
+  }
+
+  // the condition under which the value matches the pattern, `None` if the
+  // pattern matches any value
+  fn translate_pattern_test(
+    &mut self,
+    value: Value,
+    pattern: &Pattern,
+  ) -> Option<Value> {
+    match &pattern.kind {
+      PatternKind::Lit(lit) => Some(self.translate_lit_test(value, lit)),
+      PatternKind::Identifier(_, name) if self.is_none(value, name) => {
+        Some(self.translate_variant_test(value, name, &[]))
+      }
+      PatternKind::Variant(path, patterns) => {
+        Some(self.translate_variant_test(value, path, patterns))
+      }
+      _ => None,
+    }
+  }
+
+  // a string is compared by its length and its bytes, a float by its value
+  fn translate_lit_test(&mut self, value: Value, lit: &Expr) -> Value {
+    let kind = match &lit.kind {
+      ExprKind::Lit(lit) => &lit.kind,
+      _ => unreachable!(),
+    };
+
+    let lit = self.translate_expr(lit);
+    let lit = self.translate_word(lit);
+
+    match kind {
+      LitKind::Str(_) | LitKind::ByteStr(_) => {
+        let is_eq = self.translate_runtime_call(runtime::STR_EQ, &[value, lit]);
+
+        self.builder.ins().icmp_imm(IntCC::NotEqual, is_eq, 0)
+      }
+      LitKind::Float(_) => {
+        let value = match self.builder.func.dfg.value_type(value) {
+          types::F64 => value,
+          _ => self.builder.ins().bitcast(types::F64, value),
+        };
+
+        self.builder.ins().fcmp(FloatCC::Equal, value, lit)
+      }
+      _ => self.builder.ins().icmp(IntCC::Equal, value, lit),
+    }
+  }
+
+  fn translate_variant_test(
+    &mut self,
+    value: Value,
+    path: &Expr,
+    patterns: &[Pattern],
+  ) -> Value {
+    let variant = self.variant_layout(value, path);
+
+    let tag =
+      self
+        .builder
+        .ins()
+        .load(types::I64, MemFlags::trusted(), value, 0);
+
+    let mut condition =
+      self.builder.ins().icmp_imm(IntCC::Equal, tag, variant.tag);
+
+    if variant.payload.iter().any(|field| field.boxed) {
+      return self.translate_boxed_test(value, condition, &variant, patterns);
+    }
+
+    // the payload of another variant is read inside of the same stack
+    // slot, so the patterns of the payload are tested unconditionally
+    for (pattern, field) in patterns.iter().zip(&variant.payload) {
+      let field_value = self.load_field(value, field);
+
+      if let Some(field_condition) =
+        self.translate_pattern_test(field_value, pattern)
+      {
+        condition = self.builder.ins().band(condition, field_condition);
+      }
+    }
+
+    condition
+  }
+
+  // a boxed payload is only read once the tag is known to match, the word of
+  // the payload of another variant may point to nothing
+  fn translate_boxed_test(
+    &mut self,
+    value: Value,
+    condition: Value,
+    variant: &VariantLayout,
+    patterns: &[Pattern],
+  ) -> Value {
+    let payload_block = self.builder.create_block();
+    let end_block = self.builder.create_block();
+    let no = self.builder.ins().iconst(types::I64, 0);
+
+    self.builder.append_block_param(end_block, types::I64);
+    self.builder.ins().brz(condition, end_block, &[no]);
+    self.builder.ins().jump(payload_block, &[]);
+    self.builder.seal_block(payload_block);
+    self.builder.switch_to_block(payload_block);
+
+    let mut condition = self.builder.ins().iconst(types::I64, 1);
+
+    for (pattern, field) in patterns.iter().zip(&variant.payload) {
+      let field_value = self.load_field(value, field);
+
+      if let Some(field_condition) =
+        self.translate_pattern_test(field_value, pattern)
+      {
+        let field_condition = self.translate_word(field_condition);
+
+        condition = self.builder.ins().band(condition, field_condition);
+      }
+    }
+
+    self.builder.ins().jump(end_block, &[condition]);
+    self.builder.seal_block(end_block);
+    self.builder.switch_to_block(end_block);
+
+    let condition = self.builder.block_params(end_block)[0];
+
+    self.builder.ins().icmp_imm(IntCC::NotEqual, condition, 0)
+  }
+
+  fn translate_pattern_bindings(&mut self, value: Value, pattern: &Pattern) {
+    match &pattern.kind {
+      PatternKind::Identifier(_, name) if self.is_none(value, name) => {}
+      PatternKind::Identifier(_, name) => {
+        let var = self.variable_builder.create_var(
+          &mut self.builder,
+          value,
+          types::I64,
+        );
+
+        self.vars.insert(name.to_string(), var);
+
+        if let Some(data_name) = self.data_tys.get(&value) {
+          self
+            .data_vars
+            .insert(name.to_string(), data_name.to_string());
+        }
+      }
+      PatternKind::Variant(path, patterns) => {
+        let variant = self.variant_layout(value, path);
+
+        for (pattern, field) in patterns.iter().zip(&variant.payload) {
+          let field_value = self.load_field(value, field);
+
+          self.translate_pattern_bindings(field_value, pattern);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn variant_layout(&self, value: Value, path: &Expr) -> VariantLayout {
+    match self.datas[&self.data_tys[&value]].variant(&variant_name(path)) {
+      Some(variant) => variant.to_owned(),
+      None => panic!("translate variant error"),
+    }
+  }
+
+  // a pattern named `None` matches the variant of an `Option` value instead
+  // of binding it
+  fn is_none(&self, value: Value, name: &Expr) -> bool {
+    name.to_string() == "None"
+      && self
+        .data_tys
+        .get(&value)
+        .and_then(|data_name| self.datas.get(data_name))
+        .is_some_and(|layout| layout.variant("None").is_some())
+  }
+
+  fn load_field(&mut self, ptr: Value, field: &FieldLayout) -> Value {
+    match &field.data {
+      Some(data_name) if field.boxed => {
+        let value = self.builder.ins().load(
+          types::I64,
+          MemFlags::trusted(),
+          ptr,
+          field.offset,
+        );
+
+        self.data_tys.insert(value, data_name.to_string());
+        value
+      }
+      // a nested data is stored inline, its value is a pointer inside of
+      // the enclosing data
+      Some(data_name) => {
+        let value = self.builder.ins().iadd_imm(ptr, field.offset as i64);
+
+        self.data_tys.insert(value, data_name.to_string());
+        value
+      }
+      None => self.builder.ins().load(
+        types::I64,
+        MemFlags::trusted(),
+        ptr,
+        field.offset,
+      ),
+    }
+  }
+
+  fn store_field(&mut self, ptr: Value, field: &FieldLayout, value: Value) {
+    match &field.data {
+      // a boxed data is copied to the heap, so that it outlives its slot
+      Some(data_name) if field.boxed => {
+        let size = self.datas[data_name].size;
+        let size_value = self.translate_expr_lit_int(&(size as i64));
+        let data = self.translate_runtime_call(runtime::ALLOC, &[size_value]);
+
+        self.copy_data(data, 0, value, size);
+
+        self
+          .builder
+          .ins()
+          .store(MemFlags::trusted(), data, ptr, field.offset);
+      }
+      Some(data_name) => {
+        let size = self.datas[data_name].size;
+
+        self.copy_data(ptr, field.offset, value, size);
+      }
+      None => {
+        let value = self.translate_word(value);
+
+        self
+          .builder
+          .ins()
+          .store(MemFlags::trusted(), value, ptr, field.offset);
+      }
+    }
+  }
+
+  fn create_data_slot(&mut self, data_name: &str) -> Value {
+    let size = self.datas[data_name].size;
+    let ptr = self.create_slot(size);
+
+    self.data_tys.insert(ptr, data_name.to_string());
+
+    ptr
+  }
+
+  fn create_slot(&mut self, size: u32) -> Value {
+    let slot = self.builder.create_sized_stack_slot(StackSlotData::new(
+      StackSlotKind::ExplicitSlot,
+      size,
+    ));
+
+    self.builder.ins().stack_addr(types::I64, slot, 0)
+  }
+
+  fn create_heap(&mut self, size: i64) -> Value {
+    let size = self.translate_expr_lit_int(&size);
+
+    self.translate_runtime_call(runtime::ALLOC, &[size])
+  }
+
+  fn field_layout(&self, data: Value, name: &str) -> FieldLayout {
+    let data_name = &self.data_tys[&data];
+
+    match self.datas[data_name].field(name) {
+      Some(field) => field.to_owned(),
+      None => panic!("translate field error"),
+    }
+  }
+
+  // booleans are stored as words
+  fn translate_word(&mut self, value: Value) -> Value {
+    if self.builder.func.dfg.value_type(value) == types::B1 {
+      self.builder.ins().bint(types::I64, value)
+    } else {
+      value
+    }
+  }
+
+  // the name under which a path is declared, `Self::new` becomes `Foo::new`
+  // inside of `impl Foo`
+  fn path_name(&self, expr: &Expr) -> String {
+    match &expr.kind {
+      ExprKind::Identifier(identifier) if identifier == "Self" => {
+        match &self.self_data {
+          Some(data_name) => data_name.to_string(),
+          None => identifier.to_string(),
+        }
+      }
+      ExprKind::Path(path, name) => format!("{}::{name}", self.path_name(path)),
+      _ => expr.to_string(),
+    }
+  }
+
   fn translate_expr_call(
     &mut self,
     callee: &Expr,
-    args: &Vec<PBox<Expr>>,
+    args: &[PBox<Expr>],
   ) -> Value {
-    match self.funs.get(&callee.to_string()) {
+    if let Some(value) = self.translate_expr_variant(callee, args) {
+      return value;
+    }
+
+    if let ExprKind::Identifier(name) = &callee.kind {
+      if self.vars.contains_key(name) {
+        return self.translate_expr_call_closure(callee, args);
+      }
+    }
+
+    // a method receives the value on which it is called as first argument
+    let (func_name, receiver) = match &callee.kind {
+      ExprKind::Field(lhs, name) => {
+        let receiver = self.translate_expr(lhs);
+
+        (
+          format!("{}::{name}", self.data_tys[&receiver]),
+          Some(receiver),
+        )
+      }
+      _ => (self.path_name(callee), None),
+    };
+
+    match self.funs.get(&func_name) {
       Some(func) => {
-        if func.param_count != args.len() {
+        if func.param_count != args.len() + receiver.iter().count() {
           // TODO: handle error
         }
 
         let local_func =
           self.module.declare_func_in_func(func.id, self.builder.func);
 
-        let arguments = args
-          .iter()
-          .map(|arg| self.translate_expr(arg))
-          .collect::<Vec<_>>();
+        let mut arguments = receiver.into_iter().collect::<Vec<_>>();
+
+        for (i, arg) in args.iter().enumerate() {
+          let value = self.translate_expr(arg);
+          let value = self.translate_word(value);
+
+          // a C string is the address of the bytes of the string
+          let value = match func.c_strs.get(i) {
+            Some(true) => {
+              self
+                .builder
+                .ins()
+                .load(types::I64, MemFlags::trusted(), value, 0)
+            }
+            _ => value,
+          };
+
+          arguments.push(value);
+        }
+
+        if let Some(data_name) = &func.output {
+          let ptr = self.create_data_slot(data_name);
+
+          arguments.insert(0, ptr);
+        }
 
         let call = self.builder.ins().call(local_func, &arguments);
+        let value = self.builder.inst_results(call)[0];
+
+        if let Some(data_name) = &func.output {
+          self.data_tys.insert(value, data_name.to_string());
+        }
 
-        self.builder.inst_results(call)[0]
+        value
       }
       None => panic!("translate_expr_call error"),
     }
   }
+
+  fn translate_runtime_call(&mut self, name: &str, args: &[Value]) -> Value {
+    let func = &self.funs[name];
+    let local_func =
+      self.module.declare_func_in_func(func.id, self.builder.func);
+
+    let call = self.builder.ins().call(local_func, args);
+
+    self.builder.inst_results(call)[0]
+  }
+}
+
+// the name of the variant named by the path of a pattern, `Some`, `Ok` and
+// `Err` are named without a path
+fn variant_name(path: &Expr) -> String {
+  match &path.kind {
+    ExprKind::Path(_, name) => name.to_string(),
+    _ => path.to_string(),
+  }
+}
+
+fn range_cond(op: &BinOp) -> IntCC {
+  match op.node {
+    BinOpKind::RangeInclusive => IntCC::SignedLessThanOrEqual,
+    _ => IntCC::SignedLessThan,
+  }
 }
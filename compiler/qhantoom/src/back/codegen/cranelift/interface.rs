@@ -1,26 +1,314 @@
-use crate::front::parser::ast::{Ty, TyKind};
+use crate::front::parser::ast::{Data, Enum, PBox, Ty, TyKind};
 
-use codegen::ir::GlobalValue;
 use cranelift::prelude::*;
-use cranelift_module::{DataContext, FuncId, Linkage, Module};
+use cranelift_module::{DataContext, DataId, FuncId, Linkage, Module};
 use cranelift_object::ObjectModule;
 
 use core::panic;
 use std::collections::HashMap;
 
+// the type named by each alias, as it is written
+pub type Aliases = HashMap<String, PBox<Ty>>;
+
+#[derive(Clone)]
 pub struct CompiledFunction {
   pub id: FuncId,
   pub defined: bool,
   pub param_count: usize,
+  pub output: Option<String>,
+  pub c_strs: Vec<bool>,
 }
 
 impl CompiledFunction {
-  pub fn new(id: FuncId, defined: bool, param_count: usize) -> Self {
+  pub fn new(
+    id: FuncId,
+    defined: bool,
+    param_count: usize,
+    output: Option<String>,
+  ) -> Self {
     Self {
       id,
       defined,
       param_count,
+      output,
+      c_strs: vec![],
+    }
+  }
+
+  // the inputs of an external function that are passed as C strings
+  pub fn with_c_strs(mut self, c_strs: Vec<bool>) -> Self {
+    self.c_strs = c_strs;
+    self
+  }
+}
+
+// a `val` item is stored in a data object of its own and is loaded where it
+// is used. a data value is stored inline, its value is the address of the
+// data object
+#[derive(Clone)]
+pub struct CompiledVal {
+  pub id: DataId,
+  pub ty: types::Type,
+  pub data: Option<String>,
+}
+
+impl CompiledVal {
+  pub fn new(id: DataId, ty: types::Type, data: Option<String>) -> Self {
+    Self { id, ty, data }
+  }
+}
+
+// a data value lives in a stack slot and is passed around as a pointer to
+// it. every field takes a word, except a field of data type which is stored
+// inline with the layout of its own type. an enum value is laid out the
+// same way, as a tag word followed by the payload of its variant
+#[derive(Clone, Debug)]
+pub struct DataLayout {
+  pub size: u32,
+  pub fields: Vec<FieldLayout>,
+  pub variants: Vec<VariantLayout>,
+}
+
+impl DataLayout {
+  pub const WORD: u32 = 8;
+
+  pub fn new(
+    data: &Data,
+    datas: &HashMap<String, DataLayout>,
+    aliases: &Aliases,
+  ) -> Self {
+    let mut size = 0;
+    let mut fields = vec![];
+
+    for field in &data.fields {
+      let data_name = data_name(&field.ty, None, aliases);
+      let field_size = Self::size_of(&data_name, datas);
+
+      fields.push(FieldLayout::new(
+        field.name.to_string(),
+        size as i32,
+        data_name,
+      ));
+
+      size += field_size;
+    }
+
+    Self {
+      size,
+      fields,
+      variants: vec![],
+    }
+  }
+
+  // the size of an enum is the size of its largest variant
+  pub fn with_enum(
+    enumeration: &Enum,
+    datas: &HashMap<String, DataLayout>,
+    aliases: &Aliases,
+  ) -> Self {
+    let mut size = Self::WORD;
+    let mut variants = vec![];
+
+    for (variant, tag) in
+      enumeration.variants.iter().zip(enumeration.discriminants())
+    {
+      let mut offset = Self::WORD;
+      let mut payload = vec![];
+
+      for (index, ty) in variant.tys.iter().enumerate() {
+        let data_name = data_name(ty, None, aliases);
+        let ty_size = Self::size_of(&data_name, datas);
+
+        payload.push(FieldLayout::new(
+          index.to_string(),
+          offset as i32,
+          data_name,
+        ));
+
+        offset += ty_size;
+      }
+
+      size = size.max(offset);
+
+      variants.push(VariantLayout::new(variant.name.to_string(), tag, payload));
+    }
+
+    Self {
+      size,
+      fields: vec![],
+      variants,
+    }
+  }
+
+  fn size_of(
+    data_name: &Option<String>,
+    datas: &HashMap<String, DataLayout>,
+  ) -> u32 {
+    match data_name {
+      Some(name) => datas.get(name).map_or(0, |layout| layout.size),
+      None => Self::WORD,
+    }
+  }
+
+  // `Option` and `Result` take two words whatever their payload, the tag and
+  // the payload. a payload of data type is boxed, so that `None` and `Err`
+  // have the same layout in every instance of their type
+  pub fn with_option(some: Option<String>) -> Self {
+    Self::with_prelude(vec![
+      VariantLayout::new("None".into(), 0, vec![]),
+      VariantLayout::new("Some".into(), 1, vec![FieldLayout::boxed(some)]),
+    ])
+  }
+
+  pub fn with_result(ok: Option<String>, err: Option<String>) -> Self {
+    Self::with_prelude(vec![
+      VariantLayout::new("Ok".into(), 0, vec![FieldLayout::boxed(ok)]),
+      VariantLayout::new("Err".into(), 1, vec![FieldLayout::boxed(err)]),
+    ])
+  }
+
+  fn with_prelude(variants: Vec<VariantLayout>) -> Self {
+    Self {
+      size: 2 * Self::WORD,
+      fields: vec![],
+      variants,
+    }
+  }
+
+  pub fn field(&self, name: &str) -> Option<&FieldLayout> {
+    self.fields.iter().find(|field| field.name == name)
+  }
+
+  pub fn variant(&self, name: &str) -> Option<&VariantLayout> {
+    self.variants.iter().find(|variant| variant.name == name)
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct FieldLayout {
+  pub name: String,
+  pub offset: i32,
+  pub data: Option<String>,
+  // the field holds a pointer to its data instead of the data itself
+  pub boxed: bool,
+}
+
+impl FieldLayout {
+  pub fn new(name: String, offset: i32, data: Option<String>) -> Self {
+    Self {
+      name,
+      offset,
+      data,
+      boxed: false,
+    }
+  }
+
+  pub fn boxed(data: Option<String>) -> Self {
+    Self {
+      name: "0".into(),
+      offset: DataLayout::WORD as i32,
+      data,
+      boxed: true,
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct VariantLayout {
+  pub name: String,
+  pub tag: i64,
+  pub payload: Vec<FieldLayout>,
+}
+
+impl VariantLayout {
+  pub fn new(name: String, tag: i64, payload: Vec<FieldLayout>) -> Self {
+    Self { name, tag, payload }
+  }
+}
+
+// the name of the data type of `ty`, `Self` is the data type of the current
+// `impl` block and an alias is the data type that it names
+pub fn data_name(
+  ty: &Ty,
+  self_data: Option<&String>,
+  aliases: &Aliases,
+) -> Option<String> {
+  match &ty.kind {
+    TyKind::Data(name) if name == "Self" => self_data.cloned(),
+    TyKind::Data(name) if aliases.contains_key(name) => {
+      data_name(&aliases[name], self_data, aliases)
+    }
+    TyKind::Data(name) | TyKind::Enum(name) => Some(name.to_string()),
+    TyKind::Option(some_ty) => {
+      Some(option_name(&data_name(some_ty, self_data, aliases)))
+    }
+    TyKind::Result(ok_ty, err_ty) => Some(result_name(
+      &data_name(ok_ty, self_data, aliases),
+      &data_name(err_ty, self_data, aliases),
+    )),
+    _ => None,
+  }
+}
+
+// an instance of `Option` or `Result` is named after the data types of its
+// payload, the other payloads are words and are all written `_`
+pub fn option_name(some: &Option<String>) -> String {
+  format!("Option<{}>", some.as_deref().unwrap_or("_"))
+}
+
+pub fn result_name(ok: &Option<String>, err: &Option<String>) -> String {
+  let ok = ok.as_deref().unwrap_or("_");
+  let err = err.as_deref().unwrap_or("_");
+
+  format!("Result<{ok}, {err}>")
+}
+
+// `Option` and `Result` have no item of their own, the layouts of their
+// instances are declared where their types are written
+pub fn declare_prelude_layouts(
+  ty: &Ty,
+  self_data: Option<&String>,
+  aliases: &Aliases,
+  datas: &mut HashMap<String, DataLayout>,
+) {
+  let (name, layout) = match &ty.kind {
+    TyKind::Data(name) if aliases.contains_key(name) => {
+      return declare_prelude_layouts(
+        &aliases[name],
+        self_data,
+        aliases,
+        datas,
+      );
+    }
+    TyKind::Option(some_ty) => {
+      declare_prelude_layouts(some_ty, self_data, aliases, datas);
+
+      let some = data_name(some_ty, self_data, aliases);
+
+      (option_name(&some), DataLayout::with_option(some))
+    }
+    TyKind::Result(ok_ty, err_ty) => {
+      declare_prelude_layouts(ok_ty, self_data, aliases, datas);
+      declare_prelude_layouts(err_ty, self_data, aliases, datas);
+
+      let ok = data_name(ok_ty, self_data, aliases);
+      let err = data_name(err_ty, self_data, aliases);
+
+      (result_name(&ok, &err), DataLayout::with_result(ok, err))
     }
+    _ => return,
+  };
+
+  datas.entry(name).or_insert(layout);
+}
+
+// the kind of `ty` once the aliases that it goes through are followed
+pub fn ty_kind(ty: &Ty, aliases: &Aliases) -> TyKind {
+  match &ty.kind {
+    TyKind::Data(name) if aliases.contains_key(name) => {
+      ty_kind(&aliases[name], aliases)
+    }
+    kind => kind.to_owned(),
   }
 }
 
@@ -48,21 +336,40 @@ impl VariableBuilder {
   }
 }
 
+// a string is a pointer to a pair of the address of its bytes and of their
+// length. the bytes are followed by a nul byte so that they can be handed to
+// C functions as they are. the pair of a string literal is stored right
+// before its bytes, in the data section
 #[derive(Default)]
 pub struct DataContextBuilder {
   pub index: u32,
 }
 
 impl DataContextBuilder {
+  pub const STR_HEADER_SIZE: i64 = 16;
+
   pub fn create_data(
     &mut self,
     builder: &mut FunctionBuilder,
     module: &mut ObjectModule,
-    globals: &mut HashMap<String, GlobalValue>,
+    globals: &mut HashMap<String, DataId>,
     data: &String,
   ) -> Value {
-    let data_id = if let Some(data_id) = globals.get(data) {
-      *data_id
+    let id = self.declare_str(module, globals, data);
+    let data_id = module.declare_data_in_func(id, builder.func);
+
+    builder.ins().symbol_value(types::I64, data_id)
+  }
+
+  // the data object of a string literal, each literal is stored once
+  pub fn declare_str(
+    &mut self,
+    module: &mut ObjectModule,
+    globals: &mut HashMap<String, DataId>,
+    data: &String,
+  ) -> DataId {
+    if let Some(id) = globals.get(data) {
+      *id
     } else {
       match module.declare_data(
         &format!("_data{}", self.index),
@@ -72,26 +379,31 @@ impl DataContextBuilder {
       ) {
         Ok(id) => {
           let mut data_ctx = DataContext::new();
+          let mut bytes = vec![0; Self::STR_HEADER_SIZE as usize];
 
-          data_ctx.define(data.as_bytes().to_vec().into_boxed_slice());
-          module.define_data(id, &data_ctx).unwrap();
+          bytes[8..].copy_from_slice(&(data.len() as u64).to_le_bytes());
+          bytes.extend_from_slice(data.as_bytes());
+          bytes.push(0);
 
-          let data_id = module.declare_data_in_func(id, builder.func);
+          data_ctx.define(bytes.into_boxed_slice());
+
+          let self_gv = module.declare_data_in_data(id, &mut data_ctx);
+
+          data_ctx.write_data_addr(0, self_gv, Self::STR_HEADER_SIZE);
+          module.define_data(id, &data_ctx).unwrap();
 
           data_ctx.clear();
-          globals.insert(data.to_string(), data_id);
+          globals.insert(data.to_string(), id);
 
           self.index += 1;
 
-          data_id
+          id
         }
         Err(_err) => {
           panic!("_data{} already used/declared", self.index)
         }
       }
-    };
-
-    builder.ins().symbol_value(types::I64, data_id)
+    }
   }
 }
 
@@ -109,6 +421,7 @@ impl From<&Box<Ty>> for types::Type {
       TyKind::U64 => types::B64,
       TyKind::F32 => types::F32,
       TyKind::F64 => types::F64,
+      TyKind::Char => types::I32,
       _ => panic!("fom ty to types"),
     }
   }
@@ -1,3 +1,4 @@
+mod attrchecker;
 mod mainchecker;
 mod namechecker;
 mod typechecker;
@@ -7,7 +8,22 @@ use crate::front::parser::ast::Program;
 pub fn analyze(program: &Program) -> Result<(), String> {
   mainchecker::check(program);
   namechecker::check(program);
+  attrchecker::check(program);
   typechecker::check(program);
 
+  program.reporter.abort_if_has_error();
+
+  Ok(())
+}
+
+// the test executable has an entry point of its own, so the program does not
+// need a `main` function
+pub fn analyze_tests(program: &Program) -> Result<(), String> {
+  namechecker::check(program);
+  attrchecker::check(program);
+  typechecker::check(program);
+
+  program.reporter.abort_if_has_error();
+
   Ok(())
 }
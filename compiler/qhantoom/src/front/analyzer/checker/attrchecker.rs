@@ -0,0 +1,469 @@
+use crate::front::parser::ast::*;
+
+use crate::util::error::{
+  Help, HelpKind, Label, LabelKind, LabelMessage, Report, ReportCode,
+  ReportKind, ReportMessage, ReportOffset,
+};
+
+use std::fmt;
+
+// the things to which an attribute can be applied
+#[derive(Clone, Copy, PartialEq)]
+enum AttrTarget {
+  File,
+  Load,
+  Bind,
+  Ext,
+  Val,
+  Fun,
+  Data,
+  Enum,
+  Impl,
+  Behavior,
+  Type,
+  Unit,
+  Decl,
+}
+
+impl fmt::Display for AttrTarget {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::File => write!(f, "a file"),
+      Self::Load => write!(f, "a load"),
+      Self::Bind => write!(f, "a bind"),
+      Self::Ext => write!(f, "an external function"),
+      Self::Val => write!(f, "a global value"),
+      Self::Fun => write!(f, "a function"),
+      Self::Data => write!(f, "a data"),
+      Self::Enum => write!(f, "an enum"),
+      Self::Impl => write!(f, "an impl"),
+      Self::Behavior => write!(f, "a behavior"),
+      Self::Type => write!(f, "a type alias"),
+      Self::Unit => write!(f, "a unit"),
+      Self::Decl => write!(f, "a local variable"),
+    }
+  }
+}
+
+// the arguments expected after the colon of an attribute
+enum AttrArgs {
+  // one name or more
+  Names,
+  // exactly one name
+  Name,
+  // nothing or one of the given names
+  OptionalName(&'static [&'static str]),
+  // nothing or a string
+  OptionalStr,
+}
+
+struct AttrDef {
+  name: &'static str,
+  args: AttrArgs,
+  targets: &'static [AttrTarget],
+  // how the attribute is written, it is shown when the arguments are wrong
+  form: &'static str,
+}
+
+const ANYWHERE: &[AttrTarget] = &[
+  AttrTarget::File,
+  AttrTarget::Load,
+  AttrTarget::Bind,
+  AttrTarget::Ext,
+  AttrTarget::Val,
+  AttrTarget::Fun,
+  AttrTarget::Data,
+  AttrTarget::Enum,
+  AttrTarget::Impl,
+  AttrTarget::Behavior,
+  AttrTarget::Type,
+  AttrTarget::Unit,
+  AttrTarget::Decl,
+];
+
+const ITEMS: &[AttrTarget] = &[
+  AttrTarget::Load,
+  AttrTarget::Bind,
+  AttrTarget::Ext,
+  AttrTarget::Val,
+  AttrTarget::Fun,
+  AttrTarget::Data,
+  AttrTarget::Enum,
+  AttrTarget::Impl,
+  AttrTarget::Behavior,
+  AttrTarget::Type,
+  AttrTarget::Unit,
+  AttrTarget::Decl,
+];
+
+const DEFINITIONS: &[AttrTarget] = &[
+  AttrTarget::Ext,
+  AttrTarget::Val,
+  AttrTarget::Fun,
+  AttrTarget::Data,
+  AttrTarget::Enum,
+  AttrTarget::Behavior,
+  AttrTarget::Type,
+];
+
+// every attribute known by the compiler, the other ones are ignored
+const ATTRIBUTES: &[AttrDef] = &[
+  AttrDef {
+    name: "allow",
+    args: AttrArgs::Names,
+    targets: ANYWHERE,
+    form: "#> allow: lint, ...",
+  },
+  AttrDef {
+    name: "warn",
+    args: AttrArgs::Names,
+    targets: ANYWHERE,
+    form: "#> warn: lint, ...",
+  },
+  AttrDef {
+    name: "deny",
+    args: AttrArgs::Names,
+    targets: ANYWHERE,
+    form: "#> deny: lint, ...",
+  },
+  AttrDef {
+    name: "inline",
+    args: AttrArgs::OptionalName(&["always", "never"]),
+    targets: &[AttrTarget::Fun],
+    form: "#> inline: always|never.",
+  },
+  AttrDef {
+    name: "cfg",
+    args: AttrArgs::Name,
+    targets: ITEMS,
+    form: "#> cfg: name.",
+  },
+  AttrDef {
+    name: "deprecated",
+    args: AttrArgs::OptionalStr,
+    targets: DEFINITIONS,
+    form: "#> deprecated: \"note\".",
+  },
+];
+
+pub fn check(program: &Program) {
+  check_attrs(program, &program.attrs, AttrTarget::File);
+
+  for item in &program.items {
+    check_item(program, item);
+  }
+}
+
+fn check_item(program: &Program, item: &Item) {
+  match &item.kind {
+    ItemKind::Load(_) => check_attrs(program, item.attrs(), AttrTarget::Load),
+    ItemKind::Bind(_) => check_attrs(program, item.attrs(), AttrTarget::Bind),
+    ItemKind::Ext(ext) => check_item_ext(program, ext),
+    ItemKind::Val(decl) => check_item_val(program, decl),
+    ItemKind::Fun(fun) => check_fun(program, fun),
+    ItemKind::Data(_) => check_attrs(program, item.attrs(), AttrTarget::Data),
+    ItemKind::Enum(_) => check_attrs(program, item.attrs(), AttrTarget::Enum),
+    ItemKind::Impl(imp) => check_item_impl(program, item, imp),
+    ItemKind::Behavior(behavior) => {
+      check_item_behavior(program, item, behavior)
+    }
+    ItemKind::Type(_) => check_attrs(program, item.attrs(), AttrTarget::Type),
+    ItemKind::Unit(unit) => check_item_unit(program, item, unit),
+    ItemKind::Macro(_) | ItemKind::MacroCall(_) => unreachable!(),
+  }
+}
+
+fn check_item_ext(program: &Program, ext: &Ext) {
+  check_attrs(program, &ext.attrs, AttrTarget::Ext);
+
+  let Some(body) = &ext.body else {
+    return;
+  };
+
+  check_block(program, body)
+}
+
+fn check_item_val(program: &Program, decl: &Decl) {
+  check_attrs(program, &decl.attrs, AttrTarget::Val);
+  check_expr(program, &decl.value)
+}
+
+fn check_item_impl(program: &Program, item: &Item, imp: &Impl) {
+  check_attrs(program, item.attrs(), AttrTarget::Impl);
+
+  for fun in &imp.funs {
+    check_fun(program, fun);
+  }
+}
+
+fn check_item_behavior(program: &Program, item: &Item, behavior: &Behavior) {
+  check_attrs(program, item.attrs(), AttrTarget::Behavior);
+
+  for method in &behavior.methods {
+    if let Some(body) = &method.body {
+      check_block(program, body);
+    }
+  }
+}
+
+// the tests and the mocks are checked as functions
+fn check_item_unit(program: &Program, item: &Item, unit: &Unit) {
+  check_attrs(program, item.attrs(), AttrTarget::Unit);
+
+  for fun in unit.tests().chain(unit.mocks()) {
+    check_fun(program, fun);
+  }
+}
+
+fn check_fun(program: &Program, fun: &Fun) {
+  check_attrs(program, &fun.attrs, AttrTarget::Fun);
+  check_block(program, &fun.body)
+}
+
+fn check_block(program: &Program, block: &Block) {
+  for stmt in &block.stmts {
+    match &stmt.kind {
+      StmtKind::Item(item) => check_item(program, item),
+      StmtKind::Decl(decl) => check_decl(program, decl),
+      StmtKind::Expr(expr) => check_expr(program, expr),
+    }
+  }
+}
+
+fn check_decl(program: &Program, decl: &Decl) {
+  check_attrs(program, &decl.attrs, AttrTarget::Decl);
+  check_expr(program, &decl.value)
+}
+
+// only the blocks nested in the expression can hold attributes
+fn check_expr(program: &Program, expr: &Expr) {
+  match &expr.kind {
+    ExprKind::Call(callee, args) => {
+      check_expr(program, callee);
+      check_exprs(program, args);
+    }
+    ExprKind::UnOp(_, rhs) => check_expr(program, rhs),
+    ExprKind::BinOp(lhs, _, rhs)
+    | ExprKind::Assign(lhs, _, rhs)
+    | ExprKind::AssignOp(lhs, _, rhs) => {
+      check_expr(program, lhs);
+      check_expr(program, rhs);
+    }
+    ExprKind::Return(Some(value)) | ExprKind::Break(Some(value)) => {
+      check_expr(program, value)
+    }
+    ExprKind::Block(block) | ExprKind::Loop(block) => {
+      check_block(program, block)
+    }
+    ExprKind::While(condition, block) => {
+      check_expr(program, condition);
+      check_block(program, block);
+    }
+    ExprKind::When(condition, consequence, alternative) => {
+      check_expr(program, condition);
+      check_expr(program, consequence);
+      check_expr(program, alternative);
+    }
+    ExprKind::IfElse(condition, consequence, alternative) => {
+      check_expr(program, condition);
+      check_expr(program, consequence);
+
+      if let Some(alternative) = alternative {
+        check_expr(program, alternative);
+      }
+    }
+    ExprKind::Field(lhs, _) => check_expr(program, lhs),
+    ExprKind::Struct(_, fields) => {
+      for field in fields {
+        if let Some(value) = &field.value {
+          check_expr(program, value);
+        }
+      }
+    }
+    ExprKind::Match(scrutinee, arms) => {
+      check_expr(program, scrutinee);
+
+      for arm in arms {
+        check_expr(program, &arm.body);
+      }
+    }
+    ExprKind::Is(lhs, _) | ExprKind::Try(lhs) => check_expr(program, lhs),
+    ExprKind::For(iterable, start, end, block) => {
+      check_expr(program, iterable);
+      check_expr(program, start);
+
+      if let Some(end) = end {
+        check_expr(program, end);
+      }
+
+      check_block(program, block);
+    }
+    ExprKind::ForEach(iterable, block) => {
+      check_expr(program, iterable);
+      check_block(program, block);
+    }
+    ExprKind::Array(elements) => check_exprs(program, elements),
+    ExprKind::Lambda(lambda) => check_block(program, &lambda.body),
+    _ => {}
+  }
+}
+
+fn check_exprs(program: &Program, exprs: &[PBox<Expr>]) {
+  for expr in exprs {
+    check_expr(program, expr);
+  }
+}
+
+fn check_attrs(program: &Program, attrs: &[Attribute], target: AttrTarget) {
+  for attr in attrs {
+    check_attr(program, attr, target);
+  }
+}
+
+fn check_attr(program: &Program, attr: &Attribute, target: AttrTarget) {
+  let name = attr.name.to_string();
+
+  let Some(def) = ATTRIBUTES.iter().find(|def| def.name == name) else {
+    return emit_report_unknown_attribute(program, &name, attr);
+  };
+
+  if !def.targets.contains(&target) {
+    return emit_report_misplaced_attribute_error(program, &name, target, attr);
+  }
+
+  if !has_valid_args(def, &attr.args) {
+    emit_report_malformed_attribute_error(program, &name, def.form, attr);
+  }
+}
+
+fn has_valid_args(def: &AttrDef, args: &[PBox<Expr>]) -> bool {
+  let is_name = |arg: &PBox<Expr>| matches!(arg.kind, ExprKind::Identifier(_));
+
+  match def.args {
+    AttrArgs::Names => !args.is_empty() && args.iter().all(is_name),
+    AttrArgs::Name => args.len() == 1 && is_name(&args[0]),
+    AttrArgs::OptionalName(names) => match args {
+      [] => true,
+      [arg] => match &arg.kind {
+        ExprKind::Identifier(name) => names.contains(&name.as_str()),
+        _ => false,
+      },
+      _ => false,
+    },
+    AttrArgs::OptionalStr => match args {
+      [] => true,
+      [arg] => match &arg.kind {
+        ExprKind::Lit(lit) => matches!(lit.kind, LitKind::Str(_)),
+        _ => false,
+      },
+      _ => false,
+    },
+  }
+}
+
+fn similar_attr_name(name: &str) -> Option<&'static str> {
+  ATTRIBUTES
+    .iter()
+    .map(|def| (strsim::levenshtein(name, def.name), def.name))
+    .filter(|(distance, _)| *distance <= (name.len() / 3).max(1))
+    .min_by_key(|(distance, _)| *distance)
+    .map(|(_, attr_name)| attr_name)
+}
+
+fn emit_report_unknown_attribute(
+  program: &Program,
+  name: &str,
+  attr: &Attribute,
+) {
+  let source_id = program.reporter.source(attr.span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(attr.span);
+
+  let report = Report::new(
+    ReportKind::Warning,
+    path.display().to_string(),
+    ReportOffset(attr.span.lo),
+  )
+  .with_message(ReportMessage::UnknownAttribute(name.to_string()))
+  .with_label(
+    Label::new(
+      LabelKind::Warning,
+      (path.display().to_string(), attr.name.span.into()),
+    )
+    .with_message(LabelMessage::UnknownAttribute),
+  );
+
+  let report = match similar_attr_name(name) {
+    Some(suggestion) => {
+      report.with_help(Help::new(HelpKind::DidYouMean(suggestion.into())))
+    }
+    None => report,
+  };
+
+  program
+    .reporter
+    .add_report(report, path.display().to_string(), code);
+}
+
+fn emit_report_misplaced_attribute_error(
+  program: &Program,
+  name: &str,
+  target: AttrTarget,
+  attr: &Attribute,
+) {
+  let source_id = program.reporter.source(attr.span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(attr.span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(attr.span.lo),
+    )
+    .with_code(ReportCode(14)) // FIXME #2
+    .with_message(ReportMessage::MisplacedAttribute(
+      name.to_string(),
+      target.to_string(),
+    ))
+    .with_label(
+      Label::new(
+        LabelKind::Error,
+        (path.display().to_string(), attr.span.into()),
+      )
+      .with_message(LabelMessage::MisplacedAttribute),
+    ),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn emit_report_malformed_attribute_error(
+  program: &Program,
+  name: &str,
+  form: &str,
+  attr: &Attribute,
+) {
+  let source_id = program.reporter.source(attr.span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(attr.span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(attr.span.lo),
+    )
+    .with_code(ReportCode(14)) // FIXME #2
+    .with_message(ReportMessage::MalformedAttribute(name.to_string()))
+    .with_label(
+      Label::new(
+        LabelKind::Error,
+        (path.display().to_string(), attr.span.into()),
+      )
+      .with_message(LabelMessage::MalformedAttribute(form.to_string())),
+    ),
+    path.display().to_string(),
+    code,
+  );
+}
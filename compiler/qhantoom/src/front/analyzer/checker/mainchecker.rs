@@ -16,8 +16,6 @@ pub fn check(program: &Program) {
   if !context.program.items.iter().any(has_main(&context)) {
     add_report_main_not_found_error(context.program);
   }
-
-  context.program.reporter.abort_if_has_error()
 }
 
 fn has_main<'a>(
@@ -1,4 +1,5 @@
 use crate::front::analyzer::context::Context;
+use crate::front::expander::unmarked;
 use crate::front::parser::ast::*;
 
 use crate::util::error::{
@@ -47,6 +48,14 @@ fn check_item(context: &mut Context, item: &Item) {
     ItemKind::Val(val) => check_item_val(context, val),
     ItemKind::Ext(ext) => check_item_ext(context, ext),
     ItemKind::Fun(fun) => check_item_fun(context, fun),
+    ItemKind::Data(data) => check_item_data(context, data),
+    ItemKind::Enum(enumeration) => check_item_enum(context, enumeration),
+    ItemKind::Impl(imp) => check_item_impl(context, imp),
+    ItemKind::Behavior(behavior) => check_item_behavior(context, behavior),
+    ItemKind::Type(alias) => check_item_ty_alias(context, alias),
+    ItemKind::Unit(unit) => check_item_unit(context, unit),
+    // the loader already resolved the modules that they name
+    ItemKind::Load(_) | ItemKind::Bind(_) => {}
     _ => unimplemented!(),
   }
 }
@@ -92,22 +101,97 @@ fn check_item_fun(context: &mut Context, fun: &Fun) {
   check_fun(context, fun)
 }
 
+fn check_item_data(context: &mut Context, data: &Data) {
+  verify_pascal_case(data.name.to_string(), data.name.span, context.program);
+
+  for field in &data.fields {
+    verify_snake_case(field.name.to_string(), field.name.span, context.program);
+  }
+}
+
+fn check_item_enum(context: &mut Context, enumeration: &Enum) {
+  verify_pascal_case(
+    enumeration.name.to_string(),
+    enumeration.name.span,
+    context.program,
+  );
+
+  for variant in &enumeration.variants {
+    verify_pascal_case(
+      variant.name.to_string(),
+      variant.name.span,
+      context.program,
+    );
+  }
+}
+
+fn check_item_ty_alias(context: &mut Context, alias: &TyAlias) {
+  verify_pascal_case(alias.name.to_string(), alias.name.span, context.program);
+
+  let TyAliasKind::Group(fields) = &alias.kind else {
+    return;
+  };
+
+  for field in fields {
+    verify_pascal_case(
+      field.name.to_string(),
+      field.name.span,
+      context.program,
+    );
+  }
+}
+
+fn check_item_impl(context: &mut Context, imp: &Impl) {
+  for fun in &imp.funs {
+    check_fun(context, fun);
+  }
+}
+
+fn check_item_behavior(context: &mut Context, behavior: &Behavior) {
+  verify_pascal_case(
+    behavior.name.to_string(),
+    behavior.name.span,
+    context.program,
+  );
+
+  for method in &behavior.methods {
+    check_fun_prototype(context, &method.prototype);
+
+    if let Some(body) = &method.body {
+      check_block(context, body);
+    }
+  }
+}
+
+fn check_item_unit(context: &mut Context, unit: &Unit) {
+  for fun in unit.tests().chain(unit.mocks()) {
+    check_fun(context, fun);
+  }
+}
+
 fn check_fun(context: &mut Context, fun: &Fun) {
+  check_fun_prototype(context, &fun.prototype);
+  check_block(context, &fun.body);
+}
+
+fn check_fun_prototype(context: &mut Context, prototype: &Prototype) {
   verify_snake_case(
-    fun.prototype.name.to_string(),
-    fun.prototype.name.span,
+    prototype.name.to_string(),
+    prototype.name.span,
     context.program,
   );
 
-  for input in &fun.prototype.inputs {
+  for input in &prototype.inputs {
+    if let PatternKind::Receiver = input.pattern.kind {
+      continue;
+    }
+
     verify_snake_case(
       input.pattern.to_string(),
       input.pattern.span,
       context.program,
     );
   }
-
-  check_block(context, &fun.body);
 }
 
 fn check_block(context: &mut Context, block: &Block) {
@@ -146,13 +230,11 @@ fn check_stmt_expr(context: &mut Context, expr: &Expr) {
   check_expr(context, expr)
 }
 
-fn check_expr(_context: &mut Context, expr: &Expr) {
-  match &expr.kind {
-    _ => {}
-  }
-}
+fn check_expr(_context: &mut Context, _expr: &Expr) {}
 
 fn verify_pascal_case(name: String, span: Span, program: &Program) {
+  let name = unqualified(name);
+
   if !is_pascal_case(&name) {
     emit_report_wrong_naming_convention(
       name,
@@ -164,7 +246,10 @@ fn verify_pascal_case(name: String, span: Span, program: &Program) {
 }
 
 fn verify_snake_case(name: String, span: Span, program: &Program) {
-  if !is_snake_case(&name) {
+  let name = unqualified(name);
+
+  // the names declared by a macro are marked by the expander
+  if !is_snake_case(unmarked(&name)) {
     emit_report_wrong_naming_convention(
       name,
       span,
@@ -175,6 +260,8 @@ fn verify_snake_case(name: String, span: Span, program: &Program) {
 }
 
 fn verify_screaming_snake_case(name: String, span: Span, program: &Program) {
+  let name = unqualified(name);
+
   if !is_screaming_snake_case(&name) {
     emit_report_wrong_naming_convention(
       name,
@@ -185,6 +272,15 @@ fn verify_screaming_snake_case(name: String, span: Span, program: &Program) {
   }
 }
 
+// the items of a loaded module are qualified by the name of the module, the
+// convention only applies to the name of the item
+fn unqualified(name: String) -> String {
+  match name.rsplit_once("::") {
+    Some((_, name)) => name.to_string(),
+    None => name,
+  }
+}
+
 fn emit_report_wrong_naming_convention(
   name: String,
   span: Span,
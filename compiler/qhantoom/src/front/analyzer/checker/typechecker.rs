@@ -1,4 +1,7 @@
-use crate::front::analyzer::context::Context;
+use crate::front::analyzer::context::{Context, Instance};
+use crate::front::analyzer::pattern::{is_reachable, missing_pats, Ctor, Pat};
+use crate::front::analyzer::scope::{Methods, Variants};
+use crate::front::analyzer::visibility::{is_imported, module_name, ItemDef};
 use crate::front::parser::ast::*;
 use crate::util::error::{Help, HelpKind};
 use crate::util::error::{Label, LabelKind, LabelMessage};
@@ -10,6 +13,8 @@ use crate::util::error::{
 
 use crate::util::span::Span;
 
+use std::collections::HashMap;
+
 // FIXME #1
 //
 // too much error panic. The functions should return a result type.
@@ -25,8 +30,18 @@ use crate::util::span::Span;
 pub fn check(program: &Program) {
   let mut context = Context::new(program);
 
-  for item in &context.program.items {
+  declare_ty_aliases(&mut context, program.items.iter());
+
+  // the tests of a unit can call the functions defined after it
+  let (units, items): (Vec<_>, Vec<_>) = program
+    .items
+    .iter()
+    .partition(|item| matches!(item.kind, ItemKind::Unit(_)));
+
+  for item in items.into_iter().chain(units) {
     check_item(&mut context, item);
+    resolve_decls(&mut context);
+    resolve_instances(&mut context);
   }
 }
 
@@ -35,10 +50,41 @@ fn check_item(context: &mut Context, item: &Item) {
     ItemKind::Ext(ext) => check_item_ext(context, ext),
     ItemKind::Val(decl) => check_item_val(context, decl),
     ItemKind::Fun(fun) => check_item_fun(context, fun),
+    ItemKind::Data(data) => check_item_data(context, data),
+    ItemKind::Enum(enumeration) => check_item_enum(context, enumeration),
+    ItemKind::Impl(imp) => check_item_impl(context, imp),
+    ItemKind::Behavior(behavior) => check_item_behavior(context, behavior),
+    ItemKind::Type(alias) => check_item_ty_alias(context, alias),
+    ItemKind::Unit(unit) => check_item_unit(context, unit),
+    // the items of the loaded modules are checked with the ones of the program
+    ItemKind::Load(_) | ItemKind::Bind(_) => {}
     _ => todo!("{}", item),
   }
 }
 
+// the aliases of a scope are declared before anything else, so that an alias
+// can refer to an alias declared after it
+fn declare_ty_aliases<'a>(
+  context: &mut Context,
+  items: impl Iterator<Item = &'a PBox<Item>>,
+) {
+  for item in items {
+    let ItemKind::Type(alias) = &item.kind else {
+      continue;
+    };
+
+    for (name, ty) in alias.tys() {
+      if context
+        .scope_map
+        .set_ty(name.to_owned(), ty.to_owned())
+        .is_err()
+      {
+        add_report_duplicate_item_error(context.program, &name, alias.span);
+      }
+    }
+  }
+}
+
 fn check_item_ext(context: &mut Context, ext: &Ext) {
   match context.scope_map.set_fun(
     ext.prototype.name.to_string(),
@@ -54,17 +100,26 @@ fn check_item_ext(context: &mut Context, ext: &Ext) {
 
       context.scope_map.exit_scope();
     }
-    Err(_) => todo!(),
+    Err(_) => add_report_duplicate_item_error(
+      context.program,
+      &ext.prototype.name.to_string(),
+      ext.prototype.name.span,
+    ),
   }
 }
 
 fn check_item_val(context: &mut Context, decl: &Decl) {
+  let Some(ty) = &decl.ty else {
+    return check_decl_inferred(context, decl);
+  };
+  let ty = resolve_ty(context, ty);
+
   match context
     .scope_map
-    .set_decl(decl.pattern.to_string(), decl.ty.clone())
+    .set_decl(decl.pattern.to_string(), ty.to_owned())
   {
     Ok(_) => {
-      check_verify(context, &decl.value, &decl.ty);
+      check_verify(context, &decl.value, &ty);
     }
     Err(_) => add_report_variable_already_exist_error(
       decl.pattern.to_string(),
@@ -75,419 +130,2985 @@ fn check_item_val(context: &mut Context, decl: &Decl) {
 }
 
 fn check_item_fun(context: &mut Context, fun: &Fun) {
-  match context.scope_map.set_fun(
-    fun.prototype.name.to_string(),
-    (fun.prototype.as_ty(), fun.prototype.as_inputs_tys()),
-  ) {
+  let name = fun.prototype.name.to_string();
+  let ty_params = check_ty_params(context, &fun.prototype.ty_params);
+  let fun_ty = check_prototype_ty(context, &fun.prototype);
+
+  match context.scope_map.set_fun(name.to_owned(), fun_ty) {
     Ok(_) => {
-      context.scope_map.enter_scope();
-      check_prototype(context, &fun.prototype);
-      check_block(context, &fun.body);
-      context.scope_map.exit_scope();
+      context.scope_map.set_generics(name, ty_params);
+      check_fun(context, fun)
     }
-    Err(_error) => todo!(),
+    Err(_) => add_report_duplicate_item_error(
+      context.program,
+      &fun.prototype.name.to_string(),
+      fun.prototype.name.span,
+    ),
   }
 }
 
-fn check_prototype(context: &mut Context, prototype: &Prototype) {
-  // register inputs to the function scope
-  for input in &prototype.inputs {
-    if context
-      .scope_map
-      .set_decl(input.pattern.to_string(), input.ty.to_owned())
-      .is_err()
-    {
-      add_report_name_clash_if_error(context.program, input);
-    }
-  }
-
-  // preserve the return type
-  context.return_ty = prototype.as_ty();
+fn check_fun(context: &mut Context, fun: &Fun) {
+  context.scope_map.enter_scope();
+  declare_ty_params(context, &fun.prototype.ty_params);
+  check_prototype(context, &fun.prototype);
+  check_block(context, &fun.body);
+  context.scope_map.exit_scope();
 }
 
-fn check_block(context: &mut Context, block: &Block) {
-  for stmt in &block.stmts {
-    check_stmt(context, stmt);
-  }
-}
+fn check_item_data(context: &mut Context, data: &Data) {
+  let ty_params = check_ty_params(context, &data.ty_params);
+  let mut fields: Vec<(String, PBox<Ty>)> = vec![];
 
-fn check_stmt(context: &mut Context, stmt: &Stmt) {
-  match &stmt.kind {
-    StmtKind::Item(item) => check_stmt_item(context, item),
-    StmtKind::Decl(decl) => check_stmt_decl(context, decl),
-    StmtKind::Expr(expr) => check_stmt_expr(context, expr),
-  }
-}
+  context.scope_map.enter_scope();
+  declare_ty_params(context, &data.ty_params);
 
-fn check_stmt_item(context: &mut Context, item: &Item) {
-  check_item(context, item)
-}
+  for field in &data.fields {
+    let name = field.name.to_string();
 
-fn check_stmt_decl(context: &mut Context, decl: &Decl) {
-  check_decl(context, decl)
-}
+    if fields.iter().any(|(field_name, _)| *field_name == name) {
+      add_report_duplicate_field_error(context.program, &name, field.span);
+      continue;
+    }
 
-fn check_decl(context: &mut Context, decl: &Decl) {
-  let name = &decl.pattern;
-  let ty = &decl.ty;
+    // a field can only use the data types declared before, this rules out
+    // a data type that contains itself
+    fields.push((name, resolve_ty(context, &field.ty)));
+  }
 
-  let Ok(_) = context.scope_map.set_decl(name.to_string(), ty.to_owned()) else {
-    return add_report_variable_already_exist_error(
-      name.to_string(),
-      name.span,
-      context.program,
-    );
-  };
+  context.scope_map.exit_scope();
 
-  let name = if let PatternKind::Identifier(_, identifier) = &name.kind {
-    identifier
-  } else {
-    // TODO: report error?
-    todo!();
-  };
+  let name = data.name.to_string();
 
-  let t1 = check_expr(context, name);
-  let t2 = check_expr(context, &decl.value);
+  if context.scope_map.variants(&name).is_some()
+    || context.scope_map.set_data(name.to_owned(), fields).is_err()
+  {
+    add_report_duplicate_item_error(context.program, &name, data.name.span);
+  }
 
-  check_equality(context, &t1, &t2);
+  context.scope_map.set_generics(name, ty_params);
 }
 
-fn check_stmt_expr(context: &mut Context, expr: &Expr) {
-  check_expr(context, expr);
-}
+// the names of the type parameters with their bounds, a name can only be
+// used once and a bound names a behavior
+fn check_ty_params(
+  context: &mut Context,
+  ty_params: &[PBox<TyParam>],
+) -> Vec<(String, Vec<String>)> {
+  let mut names: Vec<(String, Vec<String>)> = vec![];
 
-fn check_expr(context: &mut Context, expr: &Expr) -> PBox<Ty> {
-  match &expr.kind {
-    ExprKind::Lit(lit) => check_expr_lit(context, lit),
-    ExprKind::Identifier(identifier) => {
-      check_expr_identifier(context, identifier, expr.span)
-    }
-    ExprKind::Call(callee, args) => check_expr_call(context, callee, args),
-    ExprKind::UnOp(op, rhs) => check_expr_un_op(context, op, rhs),
-    ExprKind::BinOp(lhs, op, rhs) => check_expr_bin_op(context, lhs, op, rhs),
-    ExprKind::Assign(lhs, op, rhs) => check_expr_assign(context, lhs, op, rhs),
-    ExprKind::AssignOp(lhs, op, rhs) => {
-      check_expr_assign_op(context, lhs, op, rhs)
-    }
-    ExprKind::Return(maybe_expr) => {
-      check_expr_return(context, maybe_expr, expr.span)
-    }
-    ExprKind::Block(body) => check_expr_block(context, body),
-    ExprKind::Loop(body) => check_expr_loop(context, body),
-    ExprKind::While(condition, body) => {
-      check_expr_while(context, condition, body)
-    }
-    ExprKind::Break(maybe_expr) => check_expr_break(context, maybe_expr, expr),
-    ExprKind::Continue => check_expr_continue(context, expr),
-    ExprKind::When(condition, consequence, alternative) => {
-      check_expr_when(context, condition, consequence, alternative)
-    }
-    ExprKind::IfElse(condition, consequence, maybe_alternative) => {
-      check_expr_if_else(context, condition, consequence, maybe_alternative)
+  for ty_param in ty_params {
+    let name = ty_param.name.to_string();
+
+    if names
+      .iter()
+      .any(|(ty_param_name, _)| *ty_param_name == name)
+    {
+      add_report_duplicate_item_error(context.program, &name, ty_param.span);
+      continue;
     }
+
+    let bounds = ty_param
+      .bounds
+      .iter()
+      .map(|bound| {
+        let behavior = bound.to_string();
+
+        if context.scope_map.behavior(&behavior).is_none() {
+          raise_report_undefined_name_error(
+            context.program,
+            &behavior,
+            bound.span,
+          );
+        }
+
+        verify_visibility(context, &behavior, bound.span);
+        behavior
+      })
+      .collect::<Vec<_>>();
+
+    names.push((name, bounds));
   }
+
+  names
 }
 
-fn check_expr_lit(_context: &mut Context, lit: &Lit) -> PBox<Ty> {
-  match &lit.kind {
-    LitKind::Bool(_) => check_expr_lit_bool(lit),
-    LitKind::Int(_) => check_expr_lit_int(lit),
-    LitKind::Float(_) => check_expr_lit_float(lit),
-    LitKind::Str(_) => check_expr_lit_str(lit),
+fn declare_ty_params(context: &mut Context, ty_params: &[PBox<TyParam>]) {
+  for ty_param in ty_params {
+    let bounds = ty_param.bounds.iter().map(|bound| bound.to_string());
+
+    context
+      .scope_map
+      .set_ty_param(ty_param.name.to_string(), bounds.collect());
   }
 }
 
-fn check_expr_lit_bool(lit: &Lit) -> PBox<Ty> {
-  Ty::with_bool(lit.span).into()
-}
+fn check_item_enum(context: &mut Context, enumeration: &Enum) {
+  let mut variants: Variants = vec![];
+  let mut discriminants: Vec<i64> = vec![];
 
-fn check_expr_lit_int(lit: &Lit) -> PBox<Ty> {
-  Ty::with_uint(lit.span).into()
-}
+  for (variant, discriminant) in
+    enumeration.variants.iter().zip(enumeration.discriminants())
+  {
+    let name = variant.name.to_string();
+
+    if discriminants.contains(&discriminant) {
+      add_report_duplicate_discriminant_error(
+        context.program,
+        discriminant,
+        variant.span,
+      );
+    }
 
-fn check_expr_lit_float(lit: &Lit) -> PBox<Ty> {
-  Ty::with_f64(lit.span).into()
-}
+    discriminants.push(discriminant);
 
-fn check_expr_lit_str(lit: &Lit) -> PBox<Ty> {
-  Ty::with_str(lit.span).into()
-}
+    if variants
+      .iter()
+      .any(|(variant_name, _)| *variant_name == name)
+    {
+      add_report_duplicate_variant_error(context.program, &name, variant.span);
+      continue;
+    }
 
-fn check_expr_identifier(
-  context: &mut Context,
-  identifier: &String,
-  span: Span,
-) -> PBox<Ty> {
-  if let Some(ty) = context.scope_map.decl(identifier) {
-    ty.to_owned()
-  } else if let Some(ty) = context.scope_map.fun(identifier) {
-    ty.0.to_owned()
-  } else {
-    raise_report_undefined_name_error(context.program, identifier, span)
+    let tys = variant
+      .tys
+      .iter()
+      .map(|ty| resolve_ty(context, ty))
+      .collect::<Vec<_>>();
+
+    variants.push((name, tys));
   }
-}
 
-fn check_expr_call(
-  context: &mut Context,
-  callee: &Expr,
-  inputs: &Vec<PBox<Expr>>,
-) -> PBox<Ty> {
-  let (fun_return_ty, fun_input_tys) =
-    match context.scope_map.fun(&callee.to_string()) {
-      Some(fun_ty) => fun_ty,
-      None => panic!("calling not defined function"), // FIXME #1
-    };
+  let name = enumeration.name.to_string();
 
-  if inputs.len() != fun_input_tys.len() {
-    add_report_wrong_input_count_error(
+  if context.scope_map.data(&name).is_some()
+    || context
+      .scope_map
+      .set_variants(name.to_owned(), variants)
+      .is_err()
+  {
+    add_report_duplicate_item_error(
       context.program,
-      callee,
-      inputs,
-      fun_input_tys,
+      &name,
+      enumeration.name.span,
     );
   }
+}
 
-  for (x, input) in inputs.iter().enumerate() {
-    if x < fun_input_tys.len() {
-      check_verify(&mut context.to_owned(), input, &fun_input_tys[x]);
-    }
+// an alias is resolved where it is declared, even if it is never used
+fn check_item_ty_alias(context: &mut Context, alias: &TyAlias) {
+  for (name, ty) in alias.tys() {
+    context.aliases.push(name);
+    resolve_ty(context, ty);
+    context.aliases.pop();
   }
+}
 
-  check_verify(&mut context.to_owned(), callee, fun_return_ty);
+fn check_item_impl(context: &mut Context, imp: &Impl) {
+  let ty = resolve_ty(context, &imp.ty);
 
-  fun_return_ty.clone()
-}
+  let (TyKind::Data(data_name) | TyKind::Enum(data_name)) = &ty.kind else {
+    raise_report_undefined_name_error(context.program, &ty.to_string(), ty.span)
+  };
 
-fn check_expr_un_op(context: &mut Context, op: &UnOp, rhs: &Expr) -> PBox<Ty> {
-  let t1 = check_expr(context, rhs);
+  let data_name = data_name.to_owned();
+  let self_ty = context.self_ty.replace(ty);
 
-  match &op.node {
-    UnOpKind::Neg => {
-      if !t1.is_numeric() {
-        add_report_wrong_un_op_error(context.program, op, &Ty::UINT);
-      }
+  // register every function first so that they can call each other
+  for fun in &imp.funs {
+    let fun_ty = check_prototype_ty(context, &fun.prototype);
+    let name = format!("{data_name}::{}", fun.prototype.name);
 
-      Ty::with_uint(Span::merge(&op.span, &rhs.span)).into()
+    if context.scope_map.set_fun(name.to_owned(), fun_ty).is_err() {
+      add_report_duplicate_item_error(
+        context.program,
+        &name,
+        fun.prototype.name.span,
+      );
     }
-    UnOpKind::Not => {
-      if !t1.is_boolean() {
-        add_report_wrong_un_op_error(context.program, op, &Ty::BOOL);
-      }
+  }
 
-      Ty::with_bool(Span::merge(&op.span, &rhs.span)).into()
-    }
+  if let Some(behavior) = &imp.behavior {
+    check_impl_behavior(context, imp, behavior, &data_name);
+  }
+
+  for fun in &imp.funs {
+    check_fun(context, fun);
   }
+
+  context.self_ty = self_ty;
 }
 
-fn check_expr_bin_op(
+// the functions of the impl are the methods of the behavior, a method without
+// a default body must be implemented with the type of its declaration in which
+// `Self` is the type of the impl. the default bodies that are not replaced
+// become functions of that type
+fn check_impl_behavior(
   context: &mut Context,
-  lhs: &Expr,
-  op: &BinOp,
-  rhs: &Expr,
-) -> PBox<Ty> {
-  let t1 = check_expr(context, lhs);
-  let t2 = check_expr(context, rhs);
+  imp: &Impl,
+  behavior: &Expr,
+  data_name: &str,
+) {
+  let name = behavior.to_string();
 
-  // TODO: ugly stuff, this will be improve later
-  match &op.node {
-    BinOpKind::Lt | BinOpKind::Le | BinOpKind::Gt | BinOpKind::Ge => {
-      if t1.kind != TyKind::Bool || t2.kind != TyKind::Bool {
-        raise_report_wrong_bin_op_error(context.program, op, &t1, &t2);
-      }
+  let Some(methods) = context.scope_map.behavior(&name).cloned() else {
+    raise_report_undefined_name_error(context.program, &name, behavior.span)
+  };
 
-      Ty::with_bool(Span::merge(&lhs.span, &rhs.span)).into()
+  verify_visibility(context, &name, behavior.span);
+
+  let self_params = ["Self".to_string()];
+  let self_args = context.self_ty.iter().cloned().collect::<Vec<_>>();
+  let self_substitute = |(output, inputs): &(PBox<Ty>, Vec<PBox<Ty>>)| {
+    let inputs = inputs
+      .iter()
+      .map(|ty| ty.substitute(&self_params, &self_args))
+      .collect::<Vec<_>>();
+
+    (output.substitute(&self_params, &self_args), inputs)
+  };
+
+  for fun in &imp.funs {
+    let fun_name = fun.prototype.name.to_string();
+    let span = fun.prototype.name.span;
+
+    let Some((_, method_ty, _)) = methods
+      .iter()
+      .find(|(method_name, ..)| *method_name == fun_name)
+    else {
+      add_report_not_behavior_method_error(
+        context.program,
+        &fun_name,
+        &name,
+        span,
+      );
+
+      continue;
+    };
+
+    let (output, inputs) = self_substitute(method_ty);
+    let method_ty = Ty::with_fn(inputs, output, span);
+    let (output, inputs) = check_prototype_ty(context, &fun.prototype);
+    let fun_ty = Ty::with_fn(inputs, output, span);
+
+    if method_ty.kind != fun_ty.kind {
+      add_report_method_mismatch_error(
+        context.program,
+        &fun_name,
+        &name,
+        &method_ty,
+        &fun_ty,
+      );
     }
-    BinOpKind::And | BinOpKind::Or => {
-      if t1.kind != t2.kind {
-        raise_report_wrong_bin_op_error(context.program, op, &t1, &t2);
-      }
+  }
 
-      Ty::with_bool(Span::merge(&lhs.span, &rhs.span)).into()
+  let mut missing_methods = vec![];
+
+  for (method_name, method_ty, default) in &methods {
+    if imp
+      .funs
+      .iter()
+      .any(|fun| fun.prototype.name.to_string() == *method_name)
+    {
+      continue;
     }
-    BinOpKind::Eq | BinOpKind::Ne => {
-      if t1.kind != t2.kind {
-        raise_report_wrong_bin_op_error(context.program, op, &t1, &t2);
-      }
 
-      Ty::with_bool(Span::merge(&lhs.span, &rhs.span)).into()
+    if !default {
+      missing_methods.push(method_name.to_owned());
+      continue;
     }
-    _ => {
-      if t1.kind != t2.kind {
-        raise_report_wrong_bin_op_error(context.program, op, &t1, &t2);
-      }
 
-      Ty::with_uint(Span::merge(&lhs.span, &rhs.span)).into()
+    let fun_name = format!("{data_name}::{method_name}");
+
+    if context
+      .scope_map
+      .set_fun(fun_name.to_owned(), self_substitute(method_ty))
+      .is_err()
+    {
+      add_report_duplicate_item_error(context.program, &fun_name, imp.span);
     }
   }
-}
 
-fn check_expr_assign(
-  context: &mut Context,
-  lhs: &Expr,
-  _: &BinOp,
-  rhs: &Expr,
-) -> PBox<Ty> {
-  let t1 = check_expr(context, lhs);
+  if !missing_methods.is_empty() {
+    add_report_missing_methods_error(
+      context.program,
+      &name,
+      &missing_methods,
+      behavior.span,
+    );
+  }
 
-  check_verify(context, rhs, &t1);
-  Ty::with_void(Span::merge(&lhs.span, &rhs.span)).into()
+  context.scope_map.set_impl(data_name.to_string(), name);
 }
 
-fn check_expr_assign_op(
-  context: &mut Context,
-  lhs: &Expr,
-  op: &BinOp,
-  rhs: &Expr,
-) -> PBox<Ty> {
-  let t1 = check_expr(context, lhs);
-  let t2 = check_expr(context, rhs);
+// the methods of a behavior are checked with `Self` as a type parameter that
+// implements the behavior, so that a default body can call the other methods
+fn check_item_behavior(context: &mut Context, behavior: &Behavior) {
+  let name = behavior.name.to_string();
+  let self_ty = Ty::new(TyKind::Param("Self".into()), behavior.name.span);
+  let self_ty = context.self_ty.replace(self_ty.into());
+  let mut methods: Methods = vec![];
 
-  if !op.node.is_assign_op() {
-    raise_report_wrong_assign_op_error(context.program, op, &t1, &t2)
-  }
+  context.scope_map.enter_scope();
+  context
+    .scope_map
+    .set_ty_param("Self".into(), vec![name.to_owned()]);
 
-  check_equality(context, &t1, &t2);
-  Ty::with_void(Span::merge(&lhs.span, &rhs.span)).into()
-}
+  for method in &behavior.methods {
+    let method_name = method.prototype.name.to_string();
 
-fn check_expr_return(
-  context: &mut Context,
-  maybe_expr: &Option<PBox<Expr>>,
-  return_span: Span,
-) -> PBox<Ty> {
-  if let Some(expr) = maybe_expr {
-    let t1 = check_expr(context, expr);
+    if methods
+      .iter()
+      .any(|(other_name, ..)| *other_name == method_name)
+    {
+      add_report_duplicate_item_error(
+        context.program,
+        &method_name,
+        method.prototype.name.span,
+      );
 
-    check_equality(context, &t1, &context.return_ty.clone());
+      continue;
+    }
 
-    return t1;
-  };
+    let method_ty = check_prototype_ty(context, &method.prototype);
 
-  Ty::with_void(return_span).into()
-}
+    methods.push((method_name, method_ty, method.body.is_some()));
+  }
 
-fn check_expr_block(context: &mut Context, body: &Block) -> PBox<Ty> {
-  for stmt in &body.stmts {
-    check_stmt(context, stmt);
+  context.scope_map.exit_scope();
+
+  if context
+    .scope_map
+    .set_behavior(name.to_owned(), methods)
+    .is_err()
+  {
+    add_report_duplicate_item_error(context.program, &name, behavior.name.span);
   }
 
-  Ty::with_void(body.span).into()
+  for method in &behavior.methods {
+    let Some(body) = &method.body else { continue };
+
+    context.scope_map.enter_scope();
+    context
+      .scope_map
+      .set_ty_param("Self".into(), vec![name.to_owned()]);
+    declare_ty_params(context, &method.prototype.ty_params);
+    check_prototype(context, &method.prototype);
+    check_block(context, body);
+    context.scope_map.exit_scope();
+  }
+
+  context.self_ty = self_ty;
 }
 
-fn check_expr_loop(context: &mut Context, body: &Block) -> PBox<Ty> {
-  context.loops += 1;
-  check_block(context, body);
-  context.loops -= 1;
+// the mocks are only visible to the tests of their unit, where they shadow
+// the functions that they replace
+fn check_item_unit(context: &mut Context, unit: &Unit) {
+  context.scope_map.enter_scope();
+
+  for mock in unit.mocks() {
+    let name = mock.prototype.name.to_string();
+    let (output, inputs) = check_prototype_ty(context, &mock.prototype);
+    let span = mock.prototype.name.span;
+    let mock_ty = Ty::with_fn(inputs.to_owned(), output.to_owned(), span);
+
+    if let Some((fun_output, fun_inputs)) = context.scope_map.fun(&name) {
+      let fun_ty =
+        Ty::with_fn(fun_inputs.to_owned(), fun_output.to_owned(), span);
+
+      if !context.unifier.unify(&fun_ty, &mock_ty) {
+        add_report_mock_mismatch_error(
+          context.program,
+          &name,
+          &fun_ty,
+          &mock_ty,
+        );
+      }
+    }
 
-  Ty::with_void(body.span).into()
+    if context
+      .scope_map
+      .set_fun(name.to_owned(), (output, inputs))
+      .is_err()
+    {
+      add_report_duplicate_item_error(
+        context.program,
+        &name,
+        mock.prototype.name.span,
+      );
+    }
+  }
+
+  for test in unit.tests() {
+    let name = test.prototype.name.to_string();
+
+    if !context.tests.insert(name.to_owned()) {
+      add_report_duplicate_item_error(
+        context.program,
+        &name,
+        test.prototype.name.span,
+      );
+    }
+  }
+
+  for fun in unit.mocks().chain(unit.tests()) {
+    check_fun(context, fun);
+  }
+
+  context.scope_map.exit_scope();
 }
 
-fn check_expr_while(
-  context: &mut Context,
-  condition: &Expr,
-  body: &Block,
-) -> PBox<Ty> {
-  check_verify(context, condition, &Ty::with_bool(condition.span));
-  context.loops += 1;
-  check_block(context, body);
-  context.loops -= 1;
+fn check_prototype(context: &mut Context, prototype: &Prototype) {
+  // register inputs to the function scope
+  for input in &prototype.inputs {
+    let ty = resolve_ty(context, &input.ty);
 
-  Ty::with_void(body.span).into()
+    if context
+      .scope_map
+      .set_decl(input.pattern.to_string(), ty)
+      .is_err()
+    {
+      add_report_name_clash_if_error(context.program, input);
+    }
+  }
+
+  // preserve the return type
+  context.return_ty = resolve_ty(context, &prototype.as_ty());
 }
 
-fn check_expr_break(
+fn check_prototype_ty(
   context: &mut Context,
-  maybe_expr: &Option<PBox<Expr>>,
-  origin: &Expr,
-) -> PBox<Ty> {
-  if context.loops == 0 {
-    add_report_out_of_loop(context.program, origin.to_string(), origin.span);
+  prototype: &Prototype,
+) -> (PBox<Ty>, Vec<PBox<Ty>>) {
+  context.scope_map.enter_scope();
+  declare_ty_params(context, &prototype.ty_params);
+
+  let output = resolve_ty(context, &prototype.as_ty());
+
+  let inputs = prototype
+    .inputs
+    .iter()
+    .map(|input| resolve_ty(context, &input.ty))
+    .collect::<Vec<_>>();
+
+  context.scope_map.exit_scope();
+
+  (output, inputs)
+}
+
+// replaces `Self` by the type of the current `impl` block and an alias by the
+// type it names, and makes sure that a data type is declared before being
+// used. the parser cannot tell a data from an enum, so a name declared as an
+// enum is resolved to it
+fn resolve_ty(context: &mut Context, ty: &Ty) -> PBox<Ty> {
+  if ty.is_self() {
+    return match &context.self_ty {
+      Some(self_ty) => Ty::new(self_ty.kind.to_owned(), ty.span).into(),
+      None => raise_report_undefined_name_error(
+        context.program,
+        &ty.to_string(),
+        ty.span,
+      ),
+    };
   }
 
-  if let Some(expr) = maybe_expr {
-    let t1 = check_expr(context, expr);
+  if let TyKind::Array(element_ty, len) = &ty.kind {
+    let element_ty = resolve_ty(context, element_ty);
 
-    check_equality(context, &t1, &context.return_ty.clone());
+    return Ty::new(TyKind::Array(element_ty, *len), ty.span).into();
+  }
 
-    return t1;
+  if let TyKind::Fn(input_tys, return_ty) = &ty.kind {
+    let input_tys = input_tys
+      .iter()
+      .map(|input_ty| resolve_ty(context, input_ty))
+      .collect::<Vec<_>>();
+
+    let return_ty = resolve_ty(context, return_ty);
+
+    return Ty::with_fn(input_tys, return_ty, ty.span).into();
   }
 
-  Ty::with_void(origin.span).into()
-}
+  if let TyKind::Option(some_ty) = &ty.kind {
+    let some_ty = resolve_ty(context, some_ty);
 
-fn check_expr_continue(context: &mut Context, origin: &Expr) -> PBox<Ty> {
-  if context.loops == 0 {
-    add_report_out_of_loop(context.program, origin.to_string(), origin.span);
+    return Ty::with_option(some_ty, ty.span).into();
   }
 
-  Ty::with_void(origin.span).into()
+  if let TyKind::Result(ok_ty, err_ty) = &ty.kind {
+    let ok_ty = resolve_ty(context, ok_ty);
+    let err_ty = resolve_ty(context, err_ty);
+
+    return Ty::with_result(ok_ty, err_ty, ty.span).into();
+  }
+
+  if let TyKind::App(name, args) = &ty.kind {
+    let ty_params = context.scope_map.generics(name).len();
+
+    if context.scope_map.data(name).is_none() || args.len() != ty_params {
+      let expected = match name.as_str() {
+        "Option" => 1,
+        "Result" => 2,
+        _ => ty_params,
+      };
+
+      raise_report_wrong_ty_arg_count_error(
+        context.program,
+        name,
+        expected,
+        args.len(),
+        ty.span,
+      );
+    }
+
+    verify_visibility(context, name, ty.span);
+
+    let args = args
+      .iter()
+      .map(|arg| resolve_ty(context, arg))
+      .collect::<Vec<_>>();
+
+    return Ty::new(TyKind::App(name.to_owned(), args), ty.span).into();
+  }
+
+  if let TyKind::Data(name) = &ty.kind {
+    if context.scope_map.is_ty_param(name) {
+      return Ty::new(TyKind::Param(name.to_owned()), ty.span).into();
+    }
+
+    if let Some(alias_ty) = context.scope_map.ty(name) {
+      let alias_ty = alias_ty.to_owned();
+
+      return resolve_ty_alias(context, name, &alias_ty, ty.span);
+    }
+
+    verify_visibility(context, name, ty.span);
+
+    if context.scope_map.variants(name).is_some() {
+      return Ty::new(TyKind::Enum(name.to_owned()), ty.span).into();
+    }
+
+    // an unknown type takes a fresh type, so that the checking goes on
+    // without reporting each of its uses
+    if context.scope_map.data(name).is_none() {
+      if context.unknown_tys.insert(ty.span) {
+        let suggestion = similar_ty_name(context, name);
+
+        add_report_undefined_type_error(
+          context.program,
+          name,
+          suggestion,
+          ty.span,
+        );
+      }
+
+      return context.unifier.fresh(ty.span);
+    }
+
+    let ty_params = context.scope_map.generics(name).len();
+
+    if ty_params > 0 {
+      raise_report_wrong_ty_arg_count_error(
+        context.program,
+        name,
+        ty_params,
+        0,
+        ty.span,
+      );
+    }
+  }
+
+  ty.into()
 }
 
-fn check_expr_when(
+// the closest name of a type in scope, if it is close enough to be a typo
+fn similar_ty_name(context: &Context, name: &str) -> Option<String> {
+  let ty_names = context.scope_map.ty_names();
+
+  TyKind::PRIMITIVES
+    .into_iter()
+    .chain(ty_names.iter().map(|ty_name| ty_name.as_str()))
+    .map(|ty_name| (strsim::levenshtein(name, ty_name), ty_name))
+    .filter(|(distance, _)| *distance <= (name.len() / 3).max(1))
+    .min_by_key(|(distance, _)| *distance)
+    .map(|(_, ty_name)| ty_name.to_string())
+}
+
+// the type keeps the name of the alias to be displayed in the reports
+fn resolve_ty_alias(
   context: &mut Context,
-  condition: &Expr,
-  consequence: &Expr,
-  alternative: &Expr,
+  name: &str,
+  alias_ty: &Ty,
+  span: Span,
 ) -> PBox<Ty> {
-  let t1 = check_expr(context, condition);
-  let t2 = check_expr(context, consequence);
-  let t3 = check_expr(context, alternative);
-  let boolean = Ty::with_bool(condition.span);
+  // a cyclic alias is left to a fresh type, so that the checking goes on.
+  // a cycle is reported once, whichever of its aliases is resolved first
+  if let Some(start) = context.aliases.iter().position(|alias| alias == name) {
+    if !context.cyclic_aliases.contains(name) {
+      add_report_cyclic_alias_error(context.program, name, span);
+    }
 
-  check_equality(context, &t1, &boolean);
-  unify_tys(context, &t2, &t3)
+    let cycle = context.aliases[start..].to_vec();
+
+    context.cyclic_aliases.extend(cycle);
+
+    return context.unifier.fresh(span);
+  }
+
+  context.aliases.push(name.to_string());
+
+  let ty = resolve_ty(context, alias_ty);
+
+  context.aliases.pop();
+
+  Ty::with_alias(ty.kind.to_owned(), span, name.to_string()).into()
 }
 
-fn check_expr_if_else(
+// the name under which a path is registered, `Self::new` becomes `Foo::new`
+// inside of `impl Foo`
+fn path_name(context: &Context, expr: &Expr) -> String {
+  match &expr.kind {
+    ExprKind::Identifier(identifier) if identifier == "Self" => {
+      match &context.self_ty {
+        Some(self_ty) => self_ty.kind.to_string(),
+        None => identifier.to_string(),
+      }
+    }
+    ExprKind::Path(path, name) => {
+      format!("{}::{name}", path_name(context, path))
+    }
+    _ => expr.to_string(),
+  }
+}
+
+// `Foo::Bar` names the variant `Bar` of the enum `Foo`, the type of the
+// enum and the types of the variant payload are returned. `None` is
+// returned if the path names something else, like a function of an `impl`
+fn check_variant(
   context: &mut Context,
-  condition: &Expr,
-  consequence: &Expr,
-  maybe_alternative: &Option<PBox<Expr>>,
-) -> PBox<Ty> {
-  let t1 = check_expr(context, condition);
-  let t2 = check_expr(context, consequence);
-  let Some(alternative) = maybe_alternative else { return t2; };
-  let t3 = check_expr(context, alternative);
+  path: &Expr,
+) -> Option<(PBox<Ty>, Vec<PBox<Ty>>)> {
+  if let ExprKind::Identifier(name) = &path.kind {
+    return check_prelude_variant(context, name, path.span);
+  }
 
-  match &t1.kind {
-    TyKind::Bool => {
-      check_equality(context, &t2, &t3);
-      t2
+  let ExprKind::Path(lhs, name) = &path.kind else {
+    return None;
+  };
+  let enum_name = path_name(context, lhs);
+  let variants = context.scope_map.variants(&enum_name)?;
+
+  verify_visibility(context, &enum_name, lhs.span);
+  let ty = Ty::new(TyKind::Enum(enum_name), path.span);
+
+  match variants
+    .iter()
+    .find(|(variant_name, _)| *variant_name == name.to_string())
+  {
+    Some((_, tys)) => Some((ty.into(), tys.to_owned())),
+    None if context.scope_map.fun(&path_name(context, path)).is_some() => None,
+    None => raise_report_undefined_variant_error(context.program, name, &ty),
+  }
+}
+
+// `Some`, `None`, `Ok` and `Err` are the variants of `Option` and `Result`,
+// the type arguments are inferred from the payload and from the uses. a
+// value or a function of the same name shadows them
+fn check_prelude_variant(
+  context: &mut Context,
+  name: &str,
+  span: Span,
+) -> Option<(PBox<Ty>, Vec<PBox<Ty>>)> {
+  if !is_prelude_variant(context, name) {
+    return None;
+  }
+
+  let ty = context.unifier.fresh(span);
+
+  match name {
+    "Some" => Some((Ty::with_option(ty.to_owned(), span).into(), vec![ty])),
+    "None" => Some((Ty::with_option(ty, span).into(), vec![])),
+    "Ok" => {
+      let err_ty = context.unifier.fresh(span);
+
+      Some((
+        Ty::with_result(ty.to_owned(), err_ty, span).into(),
+        vec![ty],
+      ))
     }
     _ => {
-      let boolean = Ty::with_bool(condition.span);
-      raise_report_type_mismatch_error(context.program, &t1, &boolean) // FIXME #1
+      let ok_ty = context.unifier.fresh(span);
+
+      Some((Ty::with_result(ok_ty, ty.to_owned(), span).into(), vec![ty]))
     }
   }
 }
 
-fn check_verify(context: &mut Context, expr: &Expr, t1: &Ty) -> bool {
-  let t2 = check_expr(context, expr);
+fn is_prelude_variant(context: &Context, name: &str) -> bool {
+  matches!(name, "Some" | "None" | "Ok" | "Err")
+    && context.scope_map.decl(name).is_none()
+    && context.scope_map.fun(name).is_none()
+}
 
-  check_equality(context, t1, &t2)
+fn check_block(context: &mut Context, block: &Block) {
+  declare_ty_aliases(context, block_items(block));
+
+  for stmt in &block.stmts {
+    check_stmt(context, stmt);
+  }
 }
 
-fn check_equality(context: &mut Context, t1: &Ty, t2: &Ty) -> bool {
-  if t1.kind != t2.kind {
-    add_report_type_mismatch_error(t1, t2, context.program);
-    false
+fn block_items(block: &Block) -> impl Iterator<Item = &PBox<Item>> {
+  block.stmts.iter().filter_map(|stmt| match &stmt.kind {
+    StmtKind::Item(item) => Some(item),
+    _ => None,
+  })
+}
+
+fn check_stmt(context: &mut Context, stmt: &Stmt) {
+  match &stmt.kind {
+    StmtKind::Item(item) => check_stmt_item(context, item),
+    StmtKind::Decl(decl) => check_stmt_decl(context, decl),
+    StmtKind::Expr(expr) => check_stmt_expr(context, expr),
+  }
+}
+
+// the code generator lowers no other item inside of a block
+fn check_stmt_item(context: &mut Context, item: &Item) {
+  match &item.kind {
+    ItemKind::Val(_) | ItemKind::Type(_) => check_item(context, item),
+    _ => add_report_unsupported_error(
+      context.program,
+      "an item other than `val` or `type` inside of a block",
+      "move this item out of the block",
+      item.span,
+    ),
+  }
+}
+
+fn check_stmt_decl(context: &mut Context, decl: &Decl) {
+  check_decl(context, decl)
+}
+
+fn check_decl(context: &mut Context, decl: &Decl) {
+  let Some(ty) = &decl.ty else {
+    return check_decl_inferred(context, decl);
+  };
+  let name = &decl.pattern;
+  let ty = &resolve_ty(context, ty);
+
+  let Ok(_) = context.scope_map.set_decl(name.to_string(), ty.to_owned()) else {
+    return add_report_variable_already_exist_error(
+      name.to_string(),
+      name.span,
+      context.program,
+    );
+  };
+
+  let name = if let PatternKind::Identifier(_, identifier) = &name.kind {
+    identifier
   } else {
-    true
+    // TODO: report error?
+    todo!();
+  };
+
+  let t1 = check_expr(context, name);
+
+  check_verify(context, &decl.value, &t1);
+}
+
+// the type of a declaration written with `:=` is the type of its value, the
+// value is checked before the name is declared
+// the type may still contain variables, the uses of the declaration until the
+// end of the item can bind them: `imu a := 1; imu b: u8 = a;`
+fn check_decl_inferred(context: &mut Context, decl: &Decl) {
+  let name = &decl.pattern;
+  let ty = check_expr(context, &decl.value);
+
+  *decl.inferred_ty.borrow_mut() = Some(ty.to_owned());
+
+  context
+    .decls
+    .push((name.to_owned(), decl.inferred_ty.to_owned()));
+
+  if context.scope_map.set_decl(name.to_string(), ty).is_err() {
+    add_report_variable_already_exist_error(
+      name.to_string(),
+      name.span,
+      context.program,
+    );
   }
 }
 
-fn unify_tys(context: &mut Context, t1: &Ty, t2: &Ty) -> PBox<Ty> {
-  if t1.kind != t2.kind {
-    raise_report_type_mismatch_error(context.program, t1, t2); // FIXME #1
-  }
+fn check_stmt_expr(context: &mut Context, expr: &Expr) {
+  check_expr(context, expr);
+}
+
+// the type of an expression is resolved with what is known so far
+fn check_expr(context: &mut Context, expr: &Expr) -> PBox<Ty> {
+  let ty = match &expr.kind {
+    ExprKind::Lit(lit) => check_expr_lit(context, lit),
+    ExprKind::Identifier(identifier)
+      if is_prelude_variant(context, identifier) =>
+    {
+      check_expr_path(context, expr)
+    }
+    ExprKind::Identifier(identifier) => {
+      check_expr_identifier(context, identifier, expr)
+    }
+    ExprKind::Call(callee, args) => check_expr_call(context, callee, args),
+    ExprKind::UnOp(op, rhs) => check_expr_un_op(context, op, rhs),
+    ExprKind::BinOp(lhs, op, rhs) => check_expr_bin_op(context, lhs, op, rhs),
+    ExprKind::Assign(lhs, op, rhs) => check_expr_assign(context, lhs, op, rhs),
+    ExprKind::AssignOp(lhs, op, rhs) => {
+      check_expr_assign_op(context, lhs, op, rhs)
+    }
+    ExprKind::Return(maybe_expr) => {
+      check_expr_return(context, maybe_expr, expr.span)
+    }
+    ExprKind::Block(body) => check_expr_block(context, body),
+    ExprKind::Loop(body) => check_expr_loop(context, body),
+    ExprKind::While(condition, body) => {
+      check_expr_while(context, condition, body)
+    }
+    ExprKind::Break(maybe_expr) => check_expr_break(context, maybe_expr, expr),
+    ExprKind::Continue => check_expr_continue(context, expr),
+    ExprKind::When(condition, consequence, alternative) => {
+      check_expr_when(context, condition, consequence, alternative)
+    }
+    ExprKind::IfElse(condition, consequence, maybe_alternative) => {
+      check_expr_if_else(context, condition, consequence, maybe_alternative)
+    }
+    ExprKind::Path(..) => check_expr_path(context, expr),
+    ExprKind::Receiver => check_expr_receiver(context, expr.span),
+    ExprKind::Field(lhs, name) => check_expr_field(context, lhs, name),
+    ExprKind::Struct(name, fields) => {
+      check_expr_struct(context, name, fields, expr.span)
+    }
+    ExprKind::Match(scrutinee, arms) => {
+      check_expr_match(context, scrutinee, arms, expr.span)
+    }
+    ExprKind::Is(lhs, pattern) => check_expr_is(context, lhs, pattern),
+    ExprKind::For(name, range, maybe_step, body) => {
+      check_expr_for(context, name, range, maybe_step, body)
+    }
+    ExprKind::ForEach(iterable, body) => {
+      check_expr_for_each(context, iterable, body)
+    }
+    ExprKind::Array(elements) => check_expr_array(context, elements, expr.span),
+    ExprKind::Lambda(lambda) => check_expr_lambda(context, lambda, None),
+    ExprKind::Try(operand) => check_expr_try(context, operand, expr.span),
+    // the macros are expanded before the program is analyzed
+    ExprKind::MacroCall(_) => unreachable!(),
+  };
+
+  context.unifier.resolve(&ty)
+}
+
+fn check_expr_lit(context: &mut Context, lit: &Lit) -> PBox<Ty> {
+  match &lit.kind {
+    LitKind::Bool(_) => check_expr_lit_bool(lit),
+    LitKind::Int(_) => check_expr_lit_int(context, lit),
+    LitKind::Float(_) => check_expr_lit_float(lit),
+    LitKind::Char(_) => check_expr_lit_char(lit),
+    LitKind::Str(_) | LitKind::ByteStr(_) => check_expr_lit_str(lit),
+  }
+}
+
+fn check_expr_lit_bool(lit: &Lit) -> PBox<Ty> {
+  Ty::with_bool(lit.span).into()
+}
+
+// an integer literal without suffix takes the integer type expected by the
+// context, `uint` by default
+fn check_expr_lit_int(context: &mut Context, lit: &Lit) -> PBox<Ty> {
+  match &lit.suffix {
+    Some(suffix) => Ty::new(suffix.to_owned(), lit.span).into(),
+    None => context.unifier.fresh_int(lit.span),
+  }
+}
+
+fn check_expr_lit_float(lit: &Lit) -> PBox<Ty> {
+  match &lit.suffix {
+    Some(suffix) => Ty::new(suffix.to_owned(), lit.span).into(),
+    None => Ty::with_f64(lit.span).into(),
+  }
+}
+
+fn check_expr_lit_char(lit: &Lit) -> PBox<Ty> {
+  Ty::with_char(lit.span).into()
+}
+
+fn check_expr_lit_str(lit: &Lit) -> PBox<Ty> {
+  Ty::with_str(lit.span).into()
+}
+
+fn check_expr_identifier(
+  context: &mut Context,
+  identifier: &String,
+  expr: &Expr,
+) -> PBox<Ty> {
+  let span = expr.span;
+
+  verify_visibility(context, identifier, span);
+
+  if let Some(ty) = context.scope_map.decl(identifier) {
+    let ty = ty.to_owned();
+
+    check_capture(context, identifier);
+    ty
+  } else if let Some(fun) = context.scope_map.fun(identifier) {
+    // a function used as a value is a closure without environment
+    let (return_ty, input_tys) = fun.to_owned();
+    let fun_ty = Ty::with_fn(input_tys, return_ty, span);
+    let (ty_params, ty_args) = instantiate(context, identifier, expr);
+
+    fun_ty.substitute(&ty_params, &ty_args)
+  } else {
+    raise_report_undefined_name_error(context.program, identifier, span)
+  }
+}
+
+fn check_expr_call(
+  context: &mut Context,
+  callee: &Expr,
+  inputs: &[PBox<Expr>],
+) -> PBox<Ty> {
+  if let ExprKind::Field(lhs, name) = &callee.kind {
+    return check_expr_call_method(context, lhs, name, inputs);
+  }
+
+  if let Some((ty, tys)) = check_variant(context, callee) {
+    return check_expr_call_variant(context, callee, ty, &tys, inputs);
+  }
+
+  // a local value of function type shadows the functions of the same name,
+  // a local value whose type is not known yet becomes a function
+  if let ExprKind::Identifier(name) = &callee.kind {
+    if let Some(ty) = context.scope_map.decl(name) {
+      let ty = context.unifier.resolve(&ty.to_owned());
+
+      if let TyKind::Infer(_) = ty.kind {
+        let fun_ty = Ty::with_fn(
+          inputs
+            .iter()
+            .map(|x| context.unifier.fresh(x.span))
+            .collect(),
+          context.unifier.fresh(callee.span),
+          callee.span,
+        );
+
+        context.unifier.unify(&ty, &fun_ty);
+      }
+
+      if let TyKind::Fn(..) = context.unifier.resolve(&ty).kind {
+        return check_expr_call_closure(context, callee, inputs);
+      }
+    }
+  }
+
+  let fun_name = path_name(context, callee);
+
+  verify_visibility(context, &fun_name, callee.span);
+
+  let (fun_return_ty, fun_input_tys) = match context.scope_map.fun(&fun_name) {
+    Some(fun_ty) => fun_ty.to_owned(),
+    None => {
+      raise_report_undefined_name_error(context.program, &fun_name, callee.span)
+    }
+  };
+
+  let (ty_params, ty_args) = instantiate(context, &fun_name, callee);
+  let fun_return_ty = fun_return_ty.substitute(&ty_params, &ty_args);
+
+  let fun_input_tys = fun_input_tys
+    .iter()
+    .map(|ty| ty.substitute(&ty_params, &ty_args))
+    .collect::<Vec<_>>();
+
+  if inputs.len() != fun_input_tys.len() {
+    add_report_wrong_input_count_error(
+      context.program,
+      callee,
+      inputs,
+      &fun_input_tys,
+    );
+  }
+
+  for (x, input) in inputs.iter().enumerate() {
+    if x < fun_input_tys.len() {
+      check_verify(context, input, &fun_input_tys[x]);
+    }
+  }
+
+  fun_return_ty
+}
+
+fn check_expr_call_closure(
+  context: &mut Context,
+  callee: &Expr,
+  inputs: &[PBox<Expr>],
+) -> PBox<Ty> {
+  let t1 = check_expr(context, callee);
+  let TyKind::Fn(input_tys, return_ty) = &t1.kind else {
+    unreachable!()
+  };
+
+  if inputs.len() != input_tys.len() {
+    add_report_wrong_input_count_error(
+      context.program,
+      callee,
+      inputs,
+      input_tys,
+    );
+  }
+
+  for (input, ty) in inputs.iter().zip(input_tys) {
+    check_verify(context, input, ty);
+  }
+
+  return_ty.to_owned()
+}
+
+// a method is a function of an `impl` block whose first input is the
+// receiver, it is called as `x.name(...)` and receives `x` as the receiver
+fn check_expr_call_method(
+  context: &mut Context,
+  lhs: &Expr,
+  name: &Expr,
+  inputs: &[PBox<Expr>],
+) -> PBox<Ty> {
+  let t1 = check_expr(context, lhs);
+  let fun_name = format!("{}::{name}", t1.kind);
+
+  let fun_ty = context
+    .scope_map
+    .fun(&fun_name)
+    .cloned()
+    .or_else(|| bound_method(context, &t1, &name.to_string()));
+
+  let (fun_return_ty, fun_input_tys) = match fun_ty {
+    Some((return_ty, input_tys))
+      if input_tys.first().is_some_and(|ty| ty.kind == t1.kind) =>
+    {
+      (return_ty, input_tys[1..].to_vec())
+    }
+    _ => raise_report_undefined_method_error(context.program, name, &t1),
+  };
+
+  verify_visibility(context, &fun_name, name.span);
+
+  if inputs.len() != fun_input_tys.len() {
+    add_report_wrong_input_count_error(
+      context.program,
+      name,
+      inputs,
+      &fun_input_tys,
+    );
+  }
+
+  for (input, ty) in inputs.iter().zip(&fun_input_tys) {
+    check_verify(context, input, ty);
+  }
+
+  fun_return_ty
+}
+
+// the methods of a type parameter are the ones of the behaviors that bound
+// it, in which `Self` is the type parameter
+fn bound_method(
+  context: &Context,
+  ty: &PBox<Ty>,
+  name: &str,
+) -> Option<(PBox<Ty>, Vec<PBox<Ty>>)> {
+  let TyKind::Param(ty_param) = &ty.kind else {
+    return None;
+  };
+  let self_params = ["Self".to_string()];
+  let self_args = [ty.to_owned()];
+
+  context
+    .scope_map
+    .ty_param(ty_param)?
+    .iter()
+    .filter_map(|behavior| context.scope_map.behavior(behavior))
+    .flatten()
+    .find(|(method_name, ..)| method_name == name)
+    .map(|(_, (output, inputs), _)| {
+      let inputs = inputs
+        .iter()
+        .map(|ty| ty.substitute(&self_params, &self_args))
+        .collect::<Vec<_>>();
+
+      (output.substitute(&self_params, &self_args), inputs)
+    })
+}
+
+fn check_expr_call_variant(
+  context: &mut Context,
+  callee: &Expr,
+  ty: PBox<Ty>,
+  tys: &[PBox<Ty>],
+  inputs: &[PBox<Expr>],
+) -> PBox<Ty> {
+  if inputs.len() != tys.len() {
+    add_report_wrong_input_count_error(context.program, callee, inputs, tys);
+  }
+
+  for (input, ty) in inputs.iter().zip(tys) {
+    check_verify(context, input, ty);
+  }
+
+  ty
+}
+
+fn check_expr_un_op(context: &mut Context, op: &UnOp, rhs: &Expr) -> PBox<Ty> {
+  let t1 = check_expr(context, rhs);
+
+  match &op.node {
+    UnOpKind::Neg => {
+      if !t1.is_numeric() {
+        add_report_wrong_un_op_error(context.program, op, &Ty::UINT);
+      }
+
+      Ty::with_uint(Span::merge(&op.span, &rhs.span)).into()
+    }
+    UnOpKind::Not => {
+      if !t1.is_boolean() {
+        add_report_wrong_un_op_error(context.program, op, &Ty::BOOL);
+      }
+
+      Ty::with_bool(Span::merge(&op.span, &rhs.span)).into()
+    }
+  }
+}
+
+fn check_expr_bin_op(
+  context: &mut Context,
+  lhs: &Expr,
+  op: &BinOp,
+  rhs: &Expr,
+) -> PBox<Ty> {
+  if let BinOpKind::As = op.node {
+    return check_expr_bin_op_as(context, lhs, op, rhs);
+  }
+
+  let t1 = check_expr(context, lhs);
+  let t2 = check_expr(context, rhs);
+
+  // TODO: ugly stuff, this will be improve later
+  match &op.node {
+    BinOpKind::Lt | BinOpKind::Le | BinOpKind::Gt | BinOpKind::Ge => {
+      if !check_numeric(context, &t1) || !context.unifier.unify(&t1, &t2) {
+        raise_report_wrong_bin_op_error(context.program, op, &t1, &t2);
+      }
+
+      Ty::with_bool(Span::merge(&lhs.span, &rhs.span)).into()
+    }
+    BinOpKind::And | BinOpKind::Or => {
+      if !context.unifier.unify(&t1, &t2) {
+        raise_report_wrong_bin_op_error(context.program, op, &t1, &t2);
+      }
+
+      Ty::with_bool(Span::merge(&lhs.span, &rhs.span)).into()
+    }
+    BinOpKind::Eq | BinOpKind::Ne => {
+      if !context.unifier.unify(&t1, &t2) {
+        raise_report_wrong_bin_op_error(context.program, op, &t1, &t2);
+      }
+
+      Ty::with_bool(Span::merge(&lhs.span, &rhs.span)).into()
+    }
+    BinOpKind::Concat => {
+      if !context.unifier.unify(&t1, &Ty::STR)
+        || !context.unifier.unify(&t2, &Ty::STR)
+      {
+        raise_report_wrong_bin_op_error(context.program, op, &t1, &t2);
+      }
+
+      Ty::with_str(Span::merge(&lhs.span, &rhs.span)).into()
+    }
+    _ => {
+      if !check_numeric(context, &t1) || !context.unifier.unify(&t1, &t2) {
+        raise_report_wrong_bin_op_error(context.program, op, &t1, &t2);
+      }
+
+      context.unifier.resolve(&Ty::new(
+        t1.kind.to_owned(),
+        Span::merge(&lhs.span, &rhs.span),
+      ))
+    }
+  }
+}
+
+// the rhs of `as` is a type. numbers can be cast between each other, a
+// `char` from and to an integer and a `bool` to an integer
+fn check_expr_bin_op_as(
+  context: &mut Context,
+  lhs: &Expr,
+  op: &BinOp,
+  rhs: &Expr,
+) -> PBox<Ty> {
+  let t1 = check_expr(context, lhs);
+  let t2 = resolve_ty(context, &Ty::from(rhs));
+
+  let is_castable = match (&t1.kind, &t2.kind) {
+    (_, _) if t1.is_numeric() && t2.is_numeric() => true,
+    (TyKind::Char | TyKind::Bool, _) => t2.is_integer(),
+    (_, TyKind::Char) => t1.is_integer(),
+    (lhs, rhs) => lhs == rhs,
+  };
+
+  if !is_castable {
+    raise_report_invalid_cast_error(context.program, op, &t1, &t2);
+  }
+
+  Ty::new(t2.kind.to_owned(), Span::merge(&lhs.span, &rhs.span)).into()
+}
+
+// an operand whose type is not known yet is constrained to be an integer
+fn check_numeric(context: &mut Context, ty: &Ty) -> bool {
+  if let TyKind::Infer(_) = context.unifier.resolve(ty).kind {
+    let int_ty = context.unifier.fresh_int(ty.span);
+
+    return context.unifier.unify(ty, &int_ty);
+  }
+
+  ty.is_numeric()
+}
+
+fn check_expr_assign(
+  context: &mut Context,
+  lhs: &Expr,
+  _: &BinOp,
+  rhs: &Expr,
+) -> PBox<Ty> {
+  let t1 = check_expr(context, lhs);
+
+  check_verify(context, rhs, &t1);
+  Ty::with_void(Span::merge(&lhs.span, &rhs.span)).into()
+}
+
+fn check_expr_assign_op(
+  context: &mut Context,
+  lhs: &Expr,
+  op: &BinOp,
+  rhs: &Expr,
+) -> PBox<Ty> {
+  let t1 = check_expr(context, lhs);
+  let t2 = check_expr(context, rhs);
+
+  if !op.node.is_assign_op() {
+    raise_report_wrong_assign_op_error(context.program, op, &t1, &t2)
+  }
+
+  check_equality(context, &t1, &t2);
+  Ty::with_void(Span::merge(&lhs.span, &rhs.span)).into()
+}
+
+fn check_expr_return(
+  context: &mut Context,
+  maybe_expr: &Option<PBox<Expr>>,
+  return_span: Span,
+) -> PBox<Ty> {
+  if let Some(expr) = maybe_expr {
+    let t1 = check_expr(context, expr);
+
+    check_equality(context, &t1, &context.return_ty.clone());
+
+    return t1;
+  };
+
+  Ty::with_void(return_span).into()
+}
+
+// `x?` is the payload of `Some` or `Ok`. `None` and `Err` are returned as
+// they are, so the function must return an `Option` or a `Result` with the
+// same error type
+fn check_expr_try(
+  context: &mut Context,
+  operand: &Expr,
+  span: Span,
+) -> PBox<Ty> {
+  let t1 = check_expr(context, operand);
+  let fresh_ty = context.unifier.fresh(span);
+
+  let (ty, return_ty) = match &t1.kind {
+    TyKind::Option(some_ty) => {
+      (some_ty.to_owned(), Ty::with_option(fresh_ty, span))
+    }
+    TyKind::Result(ok_ty, err_ty) => (
+      ok_ty.to_owned(),
+      Ty::with_result(fresh_ty, err_ty.to_owned(), span),
+    ),
+    _ => raise_report_invalid_try_error(context.program, &t1, operand.span),
+  };
+
+  if !context
+    .unifier
+    .unify(&context.return_ty.clone(), &return_ty)
+  {
+    add_report_misplaced_try_error(
+      context.program,
+      &context.return_ty,
+      &return_ty,
+    );
+  }
+
+  ty
+}
+
+// a block is worth its last expression, its names are only declared inside
+// of it
+fn check_expr_block(context: &mut Context, body: &Block) -> PBox<Ty> {
+  context.scope_map.enter_scope();
+
+  let ty = check_block_value(context, body);
+
+  context.scope_map.exit_scope();
+  ty
+}
+
+fn check_expr_loop(context: &mut Context, body: &Block) -> PBox<Ty> {
+  context.loops += 1;
+  check_block(context, body);
+  context.loops -= 1;
+
+  Ty::with_void(body.span).into()
+}
+
+fn check_expr_while(
+  context: &mut Context,
+  condition: &Expr,
+  body: &Block,
+) -> PBox<Ty> {
+  check_verify(context, condition, &Ty::with_bool(condition.span));
+  context.loops += 1;
+  check_block(context, body);
+  context.loops -= 1;
+
+  Ty::with_void(body.span).into()
+}
+
+// the loop variable only lives in the body, it has the type of the bounds
+fn check_expr_for(
+  context: &mut Context,
+  name: &Expr,
+  range: &Expr,
+  maybe_step: &Option<PBox<Expr>>,
+  body: &Block,
+) -> PBox<Ty> {
+  let ExprKind::BinOp(start, _, end) = &range.kind else {
+    unreachable!()
+  };
+  let t1 = check_expr(context, start);
+
+  if !t1.is_numeric() {
+    add_report_type_mismatch_error(
+      &Ty::with_uint(start.span),
+      &t1,
+      context.program,
+    );
+  }
+
+  check_verify(context, end, &t1);
+
+  if let Some(step) = maybe_step {
+    check_verify(context, step, &t1);
+
+    if is_non_positive_lit(step) {
+      add_report_non_positive_step_error(context.program, step);
+    }
+  }
+
+  context.scope_map.enter_scope();
+
+  if context
+    .scope_map
+    .set_decl(name.to_string(), t1.to_owned())
+    .is_err()
+  {
+    add_report_variable_already_exist_error(
+      name.to_string(),
+      name.span,
+      context.program,
+    );
+  }
+
+  context.loops += 1;
+  check_block(context, body);
+  context.loops -= 1;
+  context.scope_map.exit_scope();
+
+  Ty::with_void(body.span).into()
+}
+
+// a range only counts upwards, a step that is not known before running the
+// program is checked when the loop starts
+fn is_non_positive_lit(expr: &Expr) -> bool {
+  match &expr.kind {
+    ExprKind::Lit(lit) => match lit.kind {
+      LitKind::Int(num) => num <= 0,
+      LitKind::Float(num) => num <= 0.0,
+      _ => false,
+    },
+    ExprKind::UnOp(op, rhs) => {
+      matches!(op.node, UnOpKind::Neg) && matches!(rhs.kind, ExprKind::Lit(_))
+    }
+    _ => false,
+  }
+}
+
+// `it` is bound to each element in a fresh scope, the `it` of an enclosing
+// `for each` is shadowed
+fn check_expr_for_each(
+  context: &mut Context,
+  iterable: &Expr,
+  body: &Block,
+) -> PBox<Ty> {
+  let t1 = check_iterable(context, iterable);
+
+  if context.scope_map.decl("it").is_some() {
+    add_report_shadowed_it_warning(context.program, iterable.span);
+  }
+
+  context.scope_map.enter_scope();
+  context.scope_map.set_decl("it".into(), t1).ok();
+  context.loops += 1;
+  check_block(context, body);
+  context.loops -= 1;
+  context.scope_map.exit_scope();
+
+  Ty::with_void(body.span).into()
+}
+
+// the type of the elements of an array or a range
+fn check_iterable(context: &mut Context, iterable: &Expr) -> PBox<Ty> {
+  if let ExprKind::BinOp(start, op, end) = &iterable.kind {
+    if let BinOpKind::Range | BinOpKind::RangeInclusive = op.node {
+      let t1 = check_expr(context, start);
+
+      check_verify(context, end, &t1);
+
+      return t1;
+    }
+  }
+
+  let t1 = check_expr(context, iterable);
+
+  match &t1.kind {
+    TyKind::Array(element_ty, _) => element_ty.to_owned(),
+    _ => {
+      add_report_not_iterable_error(context.program, &t1, iterable.span);
+      Ty::with_void(iterable.span).into()
+    }
+  }
+}
+
+// the type of an input is either written, taken from the expected type or
+// inferred from the body. the names of the enclosing scopes used in the body
+// are captured by value
+fn check_expr_lambda(
+  context: &mut Context,
+  lambda: &Lambda,
+  expected_ty: Option<&Ty>,
+) -> PBox<Ty> {
+  let (expected_input_tys, expected_return_ty) = match expected_ty {
+    Some(Ty {
+      kind: TyKind::Fn(input_tys, return_ty),
+      ..
+    }) => (input_tys.to_owned(), Some(return_ty.to_owned())),
+    _ => (vec![], None),
+  };
+
+  let mut input_tys = vec![];
+
+  for (x, input) in lambda.inputs.iter().enumerate() {
+    let ty = match (&input.ty, expected_input_tys.get(x)) {
+      (Some(ty), _) => resolve_ty(context, ty),
+      (None, Some(ty)) => ty.to_owned(),
+      (None, None) => context.unifier.fresh(input.span),
+    };
+
+    input_tys.push(ty);
+  }
+
+  let return_ty = context.return_ty.to_owned();
+  let loops = context.loops;
+
+  context.lambdas.push((context.scope_map.depth(), vec![]));
+
+  context.scope_map.enter_scope();
+
+  for (input, ty) in lambda.inputs.iter().zip(&input_tys) {
+    if context
+      .scope_map
+      .set_decl(input.pattern.to_string(), ty.to_owned())
+      .is_err()
+    {
+      add_report_variable_already_exist_error(
+        input.pattern.to_string(),
+        input.span,
+        context.program,
+      );
+    }
+  }
+
+  context.return_ty = expected_return_ty
+    .unwrap_or_else(|| context.unifier.fresh(lambda.body.span));
+
+  context.loops = 0;
+
+  let t1 = check_block_value(context, &lambda.body);
+
+  check_equality(context, &context.return_ty.to_owned(), &t1);
+
+  context.scope_map.exit_scope();
+  context.return_ty = return_ty;
+  context.loops = loops;
+
+  if let Some((_, captures)) = context.lambdas.pop() {
+    *lambda.captures.borrow_mut() = captures;
+  }
+
+  context
+    .unifier
+    .resolve(&Ty::new(TyKind::Fn(input_tys, t1), lambda.span))
+}
+
+// the value of a lambda is the value of its last expression
+fn check_block_value(context: &mut Context, body: &Block) -> PBox<Ty> {
+  let Some((last, stmts)) = body.stmts.split_last() else {
+    return Ty::with_void(body.span).into();
+  };
+
+  declare_ty_aliases(context, block_items(body));
+
+  for stmt in stmts {
+    check_stmt(context, stmt);
+  }
+
+  match &last.kind {
+    StmtKind::Expr(expr) => check_expr(context, expr),
+    _ => {
+      check_stmt(context, last);
+      Ty::with_void(body.span).into()
+    }
+  }
+}
+
+// a name declared outside of a lambda is captured by it and by every lambda
+// in between. the names of the global scope are not captured
+fn check_capture(context: &mut Context, name: &str) {
+  let Some(depth) = context.scope_map.decl_depth(name) else {
+    return;
+  };
+
+  for (lambda_depth, captures) in context.lambdas.iter_mut() {
+    if depth > 0
+      && depth < *lambda_depth
+      && !captures.iter().any(|capture| capture == name)
+    {
+      captures.push(name.to_string());
+    }
+  }
+}
+
+// every element has the type of the first one
+fn check_expr_array(
+  context: &mut Context,
+  elements: &[PBox<Expr>],
+  span: Span,
+) -> PBox<Ty> {
+  let Some((first, rest)) = elements.split_first() else {
+    let void = Ty::with_void(span).into();
+
+    return Ty::new(TyKind::Array(void, 0), span).into();
+  };
+
+  let t1 = check_expr(context, first);
+
+  for element in rest {
+    check_verify(context, element, &t1);
+  }
+
+  Ty::new(TyKind::Array(t1, elements.len()), span).into()
+}
+
+fn check_expr_break(
+  context: &mut Context,
+  maybe_expr: &Option<PBox<Expr>>,
+  origin: &Expr,
+) -> PBox<Ty> {
+  if context.loops == 0 {
+    add_report_out_of_loop(context.program, origin.to_string(), origin.span);
+  }
+
+  if let Some(expr) = maybe_expr {
+    let t1 = check_expr(context, expr);
+
+    check_equality(context, &t1, &context.return_ty.clone());
+
+    return t1;
+  }
+
+  Ty::with_void(origin.span).into()
+}
+
+fn check_expr_continue(context: &mut Context, origin: &Expr) -> PBox<Ty> {
+  if context.loops == 0 {
+    add_report_out_of_loop(context.program, origin.to_string(), origin.span);
+  }
+
+  Ty::with_void(origin.span).into()
+}
+
+fn check_expr_when(
+  context: &mut Context,
+  condition: &Expr,
+  consequence: &Expr,
+  alternative: &Expr,
+) -> PBox<Ty> {
+  let t1 = check_expr(context, condition);
+  let t2 = check_expr(context, consequence);
+  let t3 = check_expr(context, alternative);
+  let boolean = Ty::with_bool(condition.span);
+
+  check_equality(context, &t1, &boolean);
+  unify_tys(context, &t2, &t3)
+}
+
+fn check_expr_if_else(
+  context: &mut Context,
+  condition: &Expr,
+  consequence: &Expr,
+  maybe_alternative: &Option<PBox<Expr>>,
+) -> PBox<Ty> {
+  // the names bound by an `is` condition only live in the consequence
+  context.scope_map.enter_scope();
+
+  let t1 = match &condition.kind {
+    ExprKind::Is(lhs, pattern) => check_expr_is_binding(context, lhs, pattern),
+    _ => check_expr(context, condition),
+  };
+
+  let t2 = check_expr(context, consequence);
+
+  context.scope_map.exit_scope();
+
+  let Some(alternative) = maybe_alternative else { return t2; };
+  let t3 = check_expr(context, alternative);
+
+  match &t1.kind {
+    TyKind::Bool => {
+      check_equality(context, &t2, &t3);
+      t2
+    }
+    _ => {
+      let boolean = Ty::with_bool(condition.span);
+      raise_report_type_mismatch_error(context.program, &t1, &boolean) // FIXME #1
+    }
+  }
+}
+
+fn check_expr_path(context: &mut Context, path: &Expr) -> PBox<Ty> {
+  // a variant without payload is a value on its own
+  if let Some((ty, tys)) = check_variant(context, path) {
+    if !tys.is_empty() {
+      add_report_wrong_input_count_error(context.program, path, &[], &tys);
+    }
+
+    return ty;
+  }
+
+  let name = path_name(context, path);
+
+  verify_visibility(context, &name, path.span);
+
+  // an associated function used as a value is a closure without environment
+  match context.scope_map.fun(&name) {
+    Some(fun) => {
+      let (return_ty, input_tys) = fun.to_owned();
+      let fun_ty = Ty::with_fn(input_tys, return_ty, path.span);
+      let (ty_params, ty_args) = instantiate(context, &name, path);
+
+      fun_ty.substitute(&ty_params, &ty_args)
+    }
+    None => {
+      raise_report_undefined_name_error(context.program, &name, path.span)
+    }
+  }
+}
+
+fn check_expr_receiver(context: &mut Context, span: Span) -> PBox<Ty> {
+  match context.scope_map.decl(".") {
+    Some(ty) => ty.to_owned(),
+    None => {
+      raise_report_undefined_name_error(context.program, &".".into(), span)
+    }
+  }
+}
+
+fn check_expr_field(
+  context: &mut Context,
+  lhs: &Expr,
+  name: &Expr,
+) -> PBox<Ty> {
+  let t1 = check_expr(context, lhs);
+
+  // the fields of a generic data type take the type arguments of the value
+  let (data_name, ty_args) = match &t1.kind {
+    TyKind::Data(data_name) => (data_name, vec![]),
+    TyKind::App(data_name, ty_args) => (data_name, ty_args.to_owned()),
+    _ => raise_report_undefined_field_error(context.program, name, &t1),
+  };
+
+  let ty_params = context.scope_map.generics(data_name);
+
+  let field_ty = context.scope_map.data(data_name).and_then(|fields| {
+    fields
+      .iter()
+      .find(|(field_name, _)| *field_name == name.to_string())
+      .map(|(_, ty)| ty.substitute(&ty_params, &ty_args))
+  });
+
+  match field_ty {
+    Some(ty) => ty,
+    None => raise_report_undefined_field_error(context.program, name, &t1),
+  }
+}
+
+fn check_expr_struct(
+  context: &mut Context,
+  name: &Expr,
+  fields: &[PBox<FieldExpr>],
+  span: Span,
+) -> PBox<Ty> {
+  let data_name = path_name(context, name);
+
+  let Some(field_defs) = context.scope_map.data(&data_name).cloned() else {
+    raise_report_undefined_name_error(context.program, &data_name, name.span)
+  };
+
+  verify_visibility(context, &data_name, name.span);
+
+  let (ty_params, ty_args) = instantiate(context, &data_name, name);
+
+  let field_defs = field_defs
+    .iter()
+    .map(|(field_name, ty)| {
+      (field_name.to_owned(), ty.substitute(&ty_params, &ty_args))
+    })
+    .collect::<Vec<_>>();
+
+  let ty = match ty_args.is_empty() {
+    true => Ty::with_data(data_name, span),
+    false => Ty::new(TyKind::App(data_name, ty_args), span),
+  };
+
+  let mut initialized: Vec<String> = vec![];
+
+  for field in fields {
+    let field_name = field.name.to_string();
+
+    let Some((_, field_ty)) = field_defs
+      .iter()
+      .find(|(field_def_name, _)| *field_def_name == field_name)
+    else {
+      raise_report_undefined_field_error(context.program, &field.name, &ty)
+    };
+
+    if initialized.contains(&field_name) {
+      add_report_duplicate_field_error(
+        context.program,
+        &field_name,
+        field.span,
+      );
+    }
+
+    // `Foo { name }` is a shorthand for `Foo { name: name }`
+    match &field.value {
+      Some(value) => check_verify(context, value, field_ty),
+      None => check_verify(context, &field.name, field_ty),
+    };
+
+    initialized.push(field_name);
+  }
+
+  // `Self { name }` leaves the numbers, booleans and characters at zero
+  let missing_fields = field_defs
+    .iter()
+    .filter(|(field_def_name, _)| !initialized.contains(field_def_name))
+    .filter(|(_, field_ty)| !field_ty.is_zeroable())
+    .map(|(field_def_name, _)| field_def_name.to_owned())
+    .collect::<Vec<_>>();
+
+  if !missing_fields.is_empty() {
+    add_report_missing_fields_error(context.program, &missing_fields, span);
+  }
+
+  ty.into()
+}
+
+// every arm has the type of the first one. an arm that cannot be reached
+// is reported as a warning, a value matched by none of the arms as an error
+fn check_expr_match(
+  context: &mut Context,
+  scrutinee: &Expr,
+  arms: &[PBox<Arm>],
+  span: Span,
+) -> PBox<Ty> {
+  let t1 = check_expr(context, scrutinee);
+  let mut ty: Option<PBox<Ty>> = None;
+  let mut pats: Vec<Pat> = vec![];
+
+  for arm in arms {
+    context.scope_map.enter_scope();
+
+    let pat = check_pattern(context, &arm.pattern, &t1);
+    let t2 = check_expr(context, &arm.body);
+
+    context.scope_map.exit_scope();
+
+    if !is_reachable(&context.scope_map, &pats, &pat, &t1) {
+      add_report_unreachable_pattern_warning(context.program, arm.pattern.span);
+    }
+
+    pats.push(pat);
+
+    match &ty {
+      Some(ty) => {
+        check_equality(context, ty, &t2);
+      }
+      None => ty = Some(t2),
+    }
+  }
+
+  let missing_pats = missing_pats(&context.scope_map, &pats, &t1);
+
+  if !missing_pats.is_empty() {
+    add_report_non_exhaustive_match_error(
+      context.program,
+      &missing_pats,
+      scrutinee.span,
+    );
+  }
+
+  ty.unwrap_or_else(|| Ty::with_void(span).into())
+}
+
+// the payload of the variant is bound to the current scope, which is the
+// consequence of the enclosing `if`
+// the names bound by an `is` would outlive the test of their variant, they
+// are only bound in the condition of an `if`
+fn check_expr_is(
+  context: &mut Context,
+  lhs: &Expr,
+  pattern: &Pattern,
+) -> PBox<Ty> {
+  if let Some(name) = pattern_binding(context, pattern) {
+    add_report_misplaced_binding_error(context.program, name);
+  }
+
+  check_expr_is_binding(context, lhs, pattern)
+}
+
+fn check_expr_is_binding(
+  context: &mut Context,
+  lhs: &Expr,
+  pattern: &Pattern,
+) -> PBox<Ty> {
+  let t1 = check_expr(context, lhs);
+
+  check_pattern(context, pattern, &t1);
+  Ty::with_bool(Span::merge(&lhs.span, &pattern.span)).into()
+}
+
+// the first name bound by the pattern, `None` is a variant and binds nothing
+fn pattern_binding<'p>(
+  context: &Context,
+  pattern: &'p Pattern,
+) -> Option<&'p Expr> {
+  match &pattern.kind {
+    PatternKind::Identifier(_, name)
+      if name.to_string() == "None" && is_prelude_variant(context, "None") =>
+    {
+      None
+    }
+    PatternKind::Identifier(_, name) => Some(name),
+    PatternKind::Variant(_, patterns) => patterns
+      .iter()
+      .find_map(|pattern| pattern_binding(context, pattern)),
+    _ => None,
+  }
+}
+
+// registers the names bound by the pattern to the current scope
+fn check_pattern(context: &mut Context, pattern: &Pattern, ty: &Ty) -> Pat {
+  match &pattern.kind {
+    PatternKind::Underscore => Pat::Wild,
+    // `None` is the only variant that can be written as a name
+    PatternKind::Identifier(_, name)
+      if name.to_string() == "None" && is_prelude_variant(context, "None") =>
+    {
+      check_pattern_variant(context, name, &[], ty)
+    }
+    PatternKind::Identifier(_, name) => {
+      if context
+        .scope_map
+        .set_decl(name.to_string(), ty.into())
+        .is_err()
+      {
+        add_report_variable_already_exist_error(
+          name.to_string(),
+          name.span,
+          context.program,
+        );
+      }
+
+      Pat::Wild
+    }
+    PatternKind::Lit(lit) => {
+      check_verify(context, lit, ty);
+
+      match &lit.kind {
+        ExprKind::Lit(lit) => match &lit.kind {
+          LitKind::Bool(boolean) => Pat::Ctor(Ctor::Bool(*boolean), vec![]),
+          _ => Pat::Ctor(Ctor::Lit(lit.to_string()), vec![]),
+        },
+        _ => Pat::Wild,
+      }
+    }
+    PatternKind::Variant(path, patterns) => {
+      check_pattern_variant(context, path, patterns, ty)
+    }
+    PatternKind::Receiver => unreachable!(),
+  }
+}
+
+fn check_pattern_variant(
+  context: &mut Context,
+  path: &Expr,
+  patterns: &[Pattern],
+  ty: &Ty,
+) -> Pat {
+  let Some((t1, tys)) = check_variant(context, path) else {
+    raise_report_undefined_name_error(
+      context.program,
+      &path_name(context, path),
+      path.span,
+    )
+  };
+
+  check_equality(context, ty, &t1);
+
+  if patterns.len() != tys.len() {
+    add_report_wrong_pattern_count_error(
+      context.program,
+      path,
+      tys.len(),
+      patterns.len(),
+    );
+  }
+
+  // a missing payload pattern matches anything, the error is reported
+  // above and the exhaustiveness checking can go on
+  let pats = tys
+    .iter()
+    .enumerate()
+    .map(|(x, ty)| match patterns.get(x) {
+      Some(pattern) => check_pattern(context, pattern, ty),
+      None => Pat::Wild,
+    })
+    .collect::<Vec<_>>();
+
+  match &path.kind {
+    ExprKind::Path(_, name) => {
+      Pat::Ctor(Ctor::Variant(t1.kind.to_string(), name.to_string()), pats)
+    }
+    _ => Pat::Ctor(Ctor::Prelude(path.to_string()), pats),
+  }
+}
+
+// the type parameters of a generic item are replaced by fresh variables at
+// each use. the variables are the type arguments of the expression that names
+// the item, they are inferred from the rest of the enclosing item
+fn instantiate(
+  context: &mut Context,
+  name: &str,
+  expr: &Expr,
+) -> (Vec<String>, Vec<PBox<Ty>>) {
+  let ty_params = context.scope_map.generics(name);
+
+  if ty_params.is_empty() {
+    return (ty_params, vec![]);
+  }
+
+  let bounds = context
+    .scope_map
+    .bounded_generics(name)
+    .iter()
+    .map(|(_, bounds)| bounds.to_owned())
+    .collect::<Vec<_>>();
+
+  let ty_args = ty_params
+    .iter()
+    .map(|_| context.unifier.fresh(expr.span))
+    .collect::<Vec<_>>();
+
+  *expr.ty_args.borrow_mut() = ty_args.to_owned();
+
+  context.instances.push(Instance {
+    name: name.to_string(),
+    ty_params: ty_params.to_owned(),
+    ty_args: expr.ty_args.to_owned(),
+    bounds,
+    scope_bounds: context.scope_map.ty_params(),
+    span: expr.span,
+  });
+
+  (ty_params, ty_args)
+}
+
+// the type of every declaration must be known once the item that declares it
+// is checked, an integer that nothing constrains is a `uint`
+fn resolve_decls(context: &mut Context) {
+  for (name, inferred_ty) in std::mem::take(&mut context.decls) {
+    let Some(ty) = inferred_ty.borrow().to_owned() else {
+      continue;
+    };
+
+    let Some(ty) = context.unifier.finalize(&ty) else {
+      raise_report_cannot_infer_error(context.program, &name);
+    };
+
+    *inferred_ty.borrow_mut() = Some(ty);
+  }
+}
+
+// every type argument must be known once the item that uses it is checked,
+// and implement the behaviors that bound its type parameter
+fn resolve_instances(context: &mut Context) {
+  for instance in std::mem::take(&mut context.instances) {
+    let ty_args = instance
+      .ty_args
+      .borrow()
+      .iter()
+      .zip(&instance.ty_params)
+      .map(|(ty, ty_param)| match context.unifier.finalize(ty) {
+        Some(ty) => ty,
+        None => raise_report_cannot_infer_ty_arg_error(
+          context.program,
+          ty_param,
+          &instance.name,
+          instance.span,
+        ),
+      })
+      .collect::<Vec<_>>();
+
+    for ((ty, ty_param), bounds) in ty_args
+      .iter()
+      .zip(&instance.ty_params)
+      .zip(&instance.bounds)
+    {
+      for behavior in bounds {
+        if !implements(context, ty, behavior, &instance.scope_bounds) {
+          add_report_unsatisfied_bound_error(
+            context.program,
+            ty,
+            ty_param,
+            behavior,
+            instance.span,
+          );
+        }
+      }
+    }
+
+    *instance.ty_args.borrow_mut() = ty_args;
+  }
+}
+
+// a type parameter implements the behaviors that bound it
+fn implements(
+  context: &Context,
+  ty: &Ty,
+  behavior: &str,
+  scope_bounds: &HashMap<String, Vec<String>>,
+) -> bool {
+  match &ty.kind {
+    TyKind::Param(name) => scope_bounds
+      .get(name)
+      .is_some_and(|bounds| bounds.iter().any(|bound| bound == behavior)),
+    TyKind::Data(name) | TyKind::Enum(name) => {
+      context.scope_map.implements(name, behavior)
+    }
+    _ => false,
+  }
+}
+
+// an item of another module can be used if that module is imported by the
+// module of the use and if the item is public
+fn verify_visibility(context: &Context, name: &str, span: Span) {
+  if !context.scope_map.is_global(name) {
+    return;
+  }
+
+  let Some(def) = context.items.get(name) else {
+    return;
+  };
+  let program = context.program;
+  let user = program.reporter.source(span);
+  let module = program.reporter.source(def.span);
+
+  if user == module {
+    return;
+  }
+
+  // the items of a module are named after it, see `loader::qualify`
+  let module_name = module_name(program, module);
+  let name = name
+    .strip_prefix(&format!("{module_name}::"))
+    .unwrap_or(name);
+
+  if !is_imported(program, user, module, name) {
+    add_report_not_imported_error(program, name, &module_name, span, def);
+  } else if !def.public {
+    add_report_private_item_error(program, name, &module_name, span, def);
+  }
+}
+
+fn check_verify(context: &mut Context, expr: &Expr, t1: &Ty) -> bool {
+  // the inputs of a lambda take the types expected by the context
+  let t2 = match &expr.kind {
+    ExprKind::Lambda(lambda) => check_expr_lambda(context, lambda, Some(t1)),
+    _ => check_expr(context, expr),
+  };
+
+  check_equality(context, t1, &t2)
+}
+
+fn check_equality(context: &mut Context, t1: &Ty, t2: &Ty) -> bool {
+  if !context.unifier.unify(t1, t2) {
+    let t1 = context.unifier.resolve(t1);
+    let t2 = context.unifier.resolve(t2);
+
+    add_report_type_mismatch_error(&t1, &t2, context.program);
+    false
+  } else {
+    true
+  }
+}
+
+fn unify_tys(context: &mut Context, t1: &Ty, t2: &Ty) -> PBox<Ty> {
+  if !context.unifier.unify(t1, t2) {
+    let t1 = context.unifier.resolve(t1);
+    let t2 = context.unifier.resolve(t2);
+
+    raise_report_type_mismatch_error(context.program, &t1, &t2); // FIXME #1
+  }
+
+  context.unifier.resolve(t1)
+}
+
+fn add_report_variable_already_exist_error(
+  name: String,
+  span: Span,
+  program: &Program,
+) {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(3)) // FIXME #2
+    .with_message(ReportMessage::DuplicateDeclaration(name))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::DuplicateDeclaration),
+    ),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn add_report_misplaced_binding_error(program: &Program, name: &Expr) {
+  let source_id = program.reporter.source(name.span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(name.span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(name.span.lo),
+    )
+    .with_code(ReportCode(23)) // FIXME #2
+    .with_message(ReportMessage::MisplacedBinding(name.to_string()))
+    .with_label(
+      Label::new(
+        LabelKind::Error,
+        (path.display().to_string(), name.span.into()),
+      )
+      .with_message(LabelMessage::MisplacedBinding),
+    ),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn add_report_non_positive_step_error(program: &Program, step: &Expr) {
+  let source_id = program.reporter.source(step.span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(step.span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(step.span.lo),
+    )
+    .with_code(ReportCode(24)) // FIXME #2
+    .with_message(ReportMessage::NonPositiveStep)
+    .with_label(
+      Label::new(
+        LabelKind::Error,
+        (path.display().to_string(), step.span.into()),
+      )
+      .with_message(LabelMessage::NonPositiveStep(step.to_string())),
+    ),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn add_report_type_mismatch_error(t1: &Ty, t2: &Ty, program: &Program) {
+  let source_id = program.reporter.source(t1.span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(t1.span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(t2.span.lo),
+    )
+    .with_message(ReportMessage::TypeMismatch)
+    .with_code(ReportCode(5)) // FIXME #2
+    .with_label(
+      Label::new(
+        LabelKind::Error,
+        (path.display().to_string(), t2.span.into()),
+      )
+      .with_message(LabelMessage::TypeMismatch(t1.to_string(), t2.to_string())),
+    )
+    .with_label(
+      Label::new(
+        LabelKind::Hint,
+        (
+          path.display().to_string(),
+          t1.span.lo as usize..t1.span.hi as usize,
+        ),
+      )
+      .with_message(LabelMessage::TypeMismatchDefinedAs(t1.to_string())),
+    ),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn add_report_cyclic_alias_error(program: &Program, name: &str, span: Span) {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(26)) // FIXME #2
+    .with_message(ReportMessage::CyclicAlias(name.to_string()))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::CyclicAlias(name.to_string())),
+    ),
+    path.display().to_string(),
+    code,
+  )
+}
+
+fn add_report_undefined_type_error(
+  program: &Program,
+  name: &str,
+  suggestion: Option<String>,
+  span: Span,
+) {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  let report = Report::new(
+    ReportKind::Error,
+    path.display().to_string(),
+    ReportOffset(span.lo),
+  )
+  .with_code(ReportCode(3)) // FIXME #2
+  .with_message(ReportMessage::UndefinedType(name.to_string()))
+  .with_label(
+    Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+      .with_message(LabelMessage::UndefinedType),
+  );
+
+  let report = match suggestion {
+    Some(suggestion) => {
+      report.with_help(Help::new(HelpKind::DidYouMean(suggestion)))
+    }
+    None => report,
+  };
+
+  program
+    .reporter
+    .add_report(report, path.display().to_string(), code)
+}
+
+fn raise_report_wrong_ty_arg_count_error(
+  program: &Program,
+  name: &str,
+  expected: usize,
+  actual: usize,
+  span: Span,
+) -> ! {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  program.reporter.raise(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(19)) // FIXME #2
+    .with_message(ReportMessage::WrongTyArgCount(name.to_string()))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::WrongTyArgCount(expected, actual)),
+    ),
+    path.display().to_string(),
+    code,
+  )
+}
+
+fn raise_report_undefined_name_error(
+  program: &Program,
+  identifier: &String,
+  span: Span,
+) -> ! {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  program.reporter.raise(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(3)) // FIXME #2
+    .with_message(ReportMessage::UndefinedName(identifier.into()))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::UndefinedName),
+    ),
+    path.display().to_string(),
+    code,
+  )
+}
+
+fn add_report_wrong_input_count_error(
+  program: &Program,
+  callee: &Expr,
+  actual_inputs: &[PBox<Expr>],
+  expected_inputs: &[PBox<Ty>],
+) {
+  let source_id = program.reporter.source(callee.span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(callee.span);
+
+  let expected_inputs_fmt = expected_inputs
+    .iter()
+    .map(|input| format!("`{}`", input))
+    .collect::<Vec<_>>()
+    .join(", ");
+
+  let actual_callee = format!("{}({})", callee, expected_inputs_fmt);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(callee.span.lo),
+    )
+    .with_code(ReportCode(3)) // FIXME #2
+    .with_message(ReportMessage::MissingInputs)
+    .with_label(
+      Label::new(
+        LabelKind::Error,
+        (path.display().to_string(), callee.span.into()),
+      )
+      .with_message(LabelMessage::MissingInputs(expected_inputs_fmt)),
+    )
+    .with_note(Note::new(NoteKind::MissingInputs(
+      expected_inputs.len(),
+      actual_inputs.len(),
+    )))
+    .with_help(Help::new(HelpKind::MissingInputs(actual_callee))),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn add_report_wrong_un_op_error(program: &Program, op: &UnOp, ty: &Ty) {
+  let source_id = program.reporter.source(op.span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(op.span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(op.span.lo),
+    )
+    .with_code(ReportCode(3)) // FIXME #2
+    .with_message(ReportMessage::WrongUnOp(op.node.to_string()))
+    .with_label(
+      Label::new(
+        LabelKind::Error,
+        (path.display().to_string(), op.span.into()),
+      )
+      .with_message(LabelMessage::WrongUnOp(ty.to_string())),
+    ),
+    path.display().to_string(),
+    code,
+  )
+}
+
+fn add_report_name_clash_if_error(program: &Program, input: &Arg) {
+  let span = input.span;
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(7)) // FIXME #2
+    .with_message(ReportMessage::NameClash)
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::NameClash),
+    )
+    .with_note(Note::new(NoteKind::NameClash)),
+    path.display().to_string(),
+    code,
+  )
+}
+
+fn raise_report_wrong_bin_op_error(
+  program: &Program,
+  op: &BinOp,
+  t1: &Ty,
+  t2: &Ty,
+) -> ! {
+  let source_id = program.reporter.source(op.span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(op.span);
+
+  program.reporter.raise(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(op.span.lo),
+    )
+    .with_code(ReportCode(7)) // FIXME #2
+    .with_message(ReportMessage::WrongBinOp)
+    .with_label(
+      Label::new(
+        LabelKind::Error,
+        (path.display().to_string(), op.span.into()),
+      )
+      .with_message(LabelMessage::WrongBinOp(t1.to_string(), t2.to_string())),
+    ),
+    path.display().to_string(),
+    code,
+  )
+}
+
+fn raise_report_invalid_cast_error(
+  program: &Program,
+  op: &BinOp,
+  t1: &Ty,
+  t2: &Ty,
+) -> ! {
+  let source_id = program.reporter.source(op.span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(op.span);
+
+  program.reporter.raise(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(op.span.lo),
+    )
+    .with_code(ReportCode(5)) // FIXME #2
+    .with_message(ReportMessage::InvalidCast(t1.to_string(), t2.to_string()))
+    .with_label(
+      Label::new(
+        LabelKind::Error,
+        (path.display().to_string(), op.span.into()),
+      )
+      .with_message(LabelMessage::InvalidCast(t2.to_string())),
+    ),
+    path.display().to_string(),
+    code,
+  )
+}
+
+fn raise_report_wrong_assign_op_error(
+  program: &Program,
+  op: &BinOp,
+  t1: &Ty,
+  t2: &Ty,
+) -> ! {
+  let source_id = program.reporter.source(op.span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(op.span);
+
+  program.reporter.raise(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(op.span.lo),
+    )
+    .with_code(ReportCode(7)) // FIXME #2
+    .with_message(ReportMessage::WrongAssignOp)
+    .with_label(
+      Label::new(
+        LabelKind::Error,
+        (path.display().to_string(), op.span.into()),
+      )
+      .with_message(LabelMessage::WrongAssignOp(
+        t1.to_string(),
+        t2.to_string(),
+      )),
+    ),
+    path.display().to_string(),
+    code,
+  )
+}
+
+fn add_report_out_of_loop(program: &Program, name: String, span: Span) {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(4))
+    .with_message(ReportMessage::OutOfLoop(name.to_string()))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::OutOfLoop(name)),
+    ),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn add_report_unsupported_error(
+  program: &Program,
+  what: &str,
+  help: &str,
+  span: Span,
+) {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(22)) // FIXME #2
+    .with_message(ReportMessage::Unsupported(what.to_string()))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::Unsupported(help.to_string())),
+    ),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn raise_report_type_mismatch_error(program: &Program, t1: &Ty, t2: &Ty) -> ! {
+  let source_id = program.reporter.source(t1.span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(t1.span);
+
+  program.reporter.raise(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(t2.span.lo),
+    )
+    .with_message(ReportMessage::TypeMismatch)
+    .with_code(ReportCode(5)) // FIXME #2
+    .with_label(
+      Label::new(
+        LabelKind::Error,
+        (path.display().to_string(), t2.span.into()),
+      )
+      .with_message(LabelMessage::TypeMismatch(t1.to_string(), t2.to_string())),
+    )
+    .with_label(
+      Label::new(
+        LabelKind::Hint,
+        (
+          path.display().to_string(),
+          t1.span.lo as usize..t1.span.hi as usize,
+        ),
+      )
+      .with_message(LabelMessage::TypeMismatchDefinedAs(t1.to_string())),
+    ),
+    path.display().to_string(),
+    code,
+  )
+}
+
+fn add_report_duplicate_field_error(program: &Program, name: &str, span: Span) {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(3)) // FIXME #2
+    .with_message(ReportMessage::DuplicateField(name.to_string()))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::DuplicateField),
+    ),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn add_report_duplicate_item_error(program: &Program, name: &str, span: Span) {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(3)) // FIXME #2
+    .with_message(ReportMessage::DuplicateItem(name.to_string()))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::DuplicateDeclaration),
+    ),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn add_report_private_item_error(
+  program: &Program,
+  name: &str,
+  module: &str,
+  span: Span,
+  def: &ItemDef,
+) {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+  let def_path = program.reporter.path(def.span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(17)) // FIXME #2
+    .with_message(ReportMessage::PrivateItem(name.to_string()))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::PrivateItem(module.to_string())),
+    )
+    .with_label(
+      Label::new(
+        LabelKind::Hint,
+        (def_path.display().to_string(), def.span.into()),
+      )
+      .with_message(LabelMessage::DefinedWithoutPub),
+    )
+    .with_help(Help::new(HelpKind::MakePublic(name.to_string()))),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn add_report_not_imported_error(
+  program: &Program,
+  name: &str,
+  module: &str,
+  span: Span,
+  def: &ItemDef,
+) {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+  let def_path = program.reporter.path(def.span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(18)) // FIXME #2
+    .with_message(ReportMessage::NotImported(name.to_string()))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::NotImported(module.to_string())),
+    )
+    .with_label(
+      Label::new(
+        LabelKind::Hint,
+        (def_path.display().to_string(), def.span.into()),
+      )
+      .with_message(LabelMessage::DefinedHere),
+    )
+    .with_help(Help::new(HelpKind::LoadModule(module.to_string()))),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn add_report_mock_mismatch_error(
+  program: &Program,
+  name: &str,
+  fun_ty: &Ty,
+  mock_ty: &Ty,
+) {
+  let span = mock_ty.span;
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(5)) // FIXME #2
+    .with_message(ReportMessage::MockMismatch(name.to_string()))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::MockMismatch(
+          fun_ty.to_string(),
+          mock_ty.to_string(),
+        )),
+    ),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn add_report_method_mismatch_error(
+  program: &Program,
+  name: &str,
+  behavior: &str,
+  method_ty: &Ty,
+  fun_ty: &Ty,
+) {
+  let span = fun_ty.span;
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(5)) // FIXME #2
+    .with_message(ReportMessage::MethodMismatch(
+      name.to_string(),
+      behavior.to_string(),
+    ))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::MethodMismatch(
+          method_ty.to_string(),
+          fun_ty.to_string(),
+        )),
+    ),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn add_report_not_behavior_method_error(
+  program: &Program,
+  name: &str,
+  behavior: &str,
+  span: Span,
+) {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(3)) // FIXME #2
+    .with_message(ReportMessage::NotBehaviorMethod(
+      name.to_string(),
+      behavior.to_string(),
+    ))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::NotBehaviorMethod(behavior.to_string())),
+    ),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn add_report_missing_methods_error(
+  program: &Program,
+  behavior: &str,
+  methods: &[String],
+  span: Span,
+) {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  let methods_fmt = methods
+    .iter()
+    .map(|method| format!("`{method}`"))
+    .collect::<Vec<_>>()
+    .join(", ");
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(3)) // FIXME #2
+    .with_message(ReportMessage::MissingMethods(behavior.to_string()))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::MissingMethods(methods_fmt)),
+    ),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn add_report_unsatisfied_bound_error(
+  program: &Program,
+  ty: &Ty,
+  ty_param: &str,
+  behavior: &str,
+  span: Span,
+) {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(21)) // FIXME #2
+    .with_message(ReportMessage::UnsatisfiedBound(
+      ty.to_string(),
+      behavior.to_string(),
+    ))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::UnsatisfiedBound(format!(
+          "{ty_param}: {behavior}"
+        ))),
+    ),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn add_report_duplicate_variant_error(
+  program: &Program,
+  name: &str,
+  span: Span,
+) {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
 
-  t1.into()
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(3)) // FIXME #2
+    .with_message(ReportMessage::DuplicateVariant(name.to_string()))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::DuplicateDeclaration),
+    ),
+    path.display().to_string(),
+    code,
+  );
 }
 
-fn add_report_variable_already_exist_error(
-  name: String,
-  span: Span,
+fn add_report_duplicate_discriminant_error(
   program: &Program,
+  discriminant: i64,
+  span: Span,
 ) {
   let source_id = program.reporter.source(span);
   let code = program.reporter.code(source_id);
@@ -500,147 +3121,242 @@ fn add_report_variable_already_exist_error(
       ReportOffset(span.lo),
     )
     .with_code(ReportCode(3)) // FIXME #2
-    .with_message(ReportMessage::DuplicateDeclaration(name))
+    .with_message(ReportMessage::DuplicateDiscriminant(discriminant))
     .with_label(
       Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
-        .with_message(LabelMessage::DuplicateDeclaration),
+        .with_message(LabelMessage::DuplicateDiscriminant),
     ),
     path.display().to_string(),
     code,
   );
 }
 
-fn add_report_type_mismatch_error(t1: &Ty, t2: &Ty, program: &Program) {
-  let source_id = program.reporter.source(t1.span);
+fn add_report_missing_fields_error(
+  program: &Program,
+  fields: &[String],
+  span: Span,
+) {
+  let source_id = program.reporter.source(span);
   let code = program.reporter.code(source_id);
-  let path = program.reporter.path(t1.span);
+  let path = program.reporter.path(span);
+
+  let fields_fmt = fields
+    .iter()
+    .map(|field| format!("`{field}`"))
+    .collect::<Vec<_>>()
+    .join(", ");
 
   program.reporter.add_report(
     Report::new(
       ReportKind::Error,
       path.display().to_string(),
-      ReportOffset(t2.span.lo),
+      ReportOffset(span.lo),
     )
-    .with_message(ReportMessage::TypeMismatch)
-    .with_code(ReportCode(5)) // FIXME #2
+    .with_code(ReportCode(3)) // FIXME #2
+    .with_message(ReportMessage::MissingFields)
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::MissingFields(fields_fmt)),
+    ),
+    path.display().to_string(),
+    code,
+  );
+}
+
+fn raise_report_undefined_field_error(
+  program: &Program,
+  name: &Expr,
+  ty: &Ty,
+) -> ! {
+  let source_id = program.reporter.source(name.span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(name.span);
+
+  program.reporter.raise(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(name.span.lo),
+    )
+    .with_code(ReportCode(3)) // FIXME #2
+    .with_message(ReportMessage::UndefinedField(
+      name.to_string(),
+      ty.to_string(),
+    ))
     .with_label(
       Label::new(
         LabelKind::Error,
-        (path.display().to_string(), t2.span.into()),
+        (path.display().to_string(), name.span.into()),
       )
-      .with_message(LabelMessage::TypeMismatch(t1.to_string(), t2.to_string())),
+      .with_message(LabelMessage::UndefinedField(ty.to_string())),
+    ),
+    path.display().to_string(),
+    code,
+  )
+}
+
+fn raise_report_undefined_method_error(
+  program: &Program,
+  name: &Expr,
+  ty: &Ty,
+) -> ! {
+  let source_id = program.reporter.source(name.span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(name.span);
+
+  program.reporter.raise(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(name.span.lo),
     )
+    .with_code(ReportCode(3)) // FIXME #2
+    .with_message(ReportMessage::UndefinedMethod(
+      name.to_string(),
+      ty.to_string(),
+    ))
     .with_label(
       Label::new(
-        LabelKind::Hint,
-        (
-          path.display().to_string(),
-          t1.span.lo as usize..t1.span.hi as usize,
-        ),
+        LabelKind::Error,
+        (path.display().to_string(), name.span.into()),
       )
-      .with_message(LabelMessage::TypeMismatchDefinedAs(t1.to_string())),
+      .with_message(LabelMessage::UndefinedMethod(ty.to_string())),
     ),
     path.display().to_string(),
     code,
-  );
+  )
 }
 
-fn raise_report_undefined_name_error(
+fn raise_report_undefined_variant_error(
   program: &Program,
-  identifier: &String,
-  span: Span,
+  name: &Expr,
+  ty: &Ty,
 ) -> ! {
-  let source_id = program.reporter.source(span);
+  let source_id = program.reporter.source(name.span);
   let code = program.reporter.code(source_id);
-  let path = program.reporter.path(span);
+  let path = program.reporter.path(name.span);
 
   program.reporter.raise(
     Report::new(
       ReportKind::Error,
       path.display().to_string(),
-      ReportOffset(span.lo),
+      ReportOffset(name.span.lo),
     )
     .with_code(ReportCode(3)) // FIXME #2
-    .with_message(ReportMessage::UndefinedName(identifier.into()))
+    .with_message(ReportMessage::UndefinedVariant(
+      name.to_string(),
+      ty.to_string(),
+    ))
     .with_label(
-      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
-        .with_message(LabelMessage::UndefinedName),
+      Label::new(
+        LabelKind::Error,
+        (path.display().to_string(), name.span.into()),
+      )
+      .with_message(LabelMessage::UndefinedVariant(ty.to_string())),
     ),
     path.display().to_string(),
     code,
   )
 }
 
-fn add_report_wrong_input_count_error(
+fn add_report_wrong_pattern_count_error(
   program: &Program,
-  callee: &Expr,
-  actual_inputs: &Vec<PBox<Expr>>,
-  expected_inputs: &Vec<PBox<Ty>>,
+  path: &Expr,
+  expected: usize,
+  actual: usize,
 ) {
-  let source_id = program.reporter.source(callee.span);
+  let source_id = program.reporter.source(path.span);
   let code = program.reporter.code(source_id);
-  let path = program.reporter.path(callee.span);
+  let path_name = path.to_string();
+  let path_span = path.span;
+  let path = program.reporter.path(path_span);
 
-  let expected_inputs_fmt = expected_inputs
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(path_span.lo),
+    )
+    .with_code(ReportCode(3)) // FIXME #2
+    .with_message(ReportMessage::WrongPatternCount(path_name))
+    .with_label(
+      Label::new(
+        LabelKind::Error,
+        (path.display().to_string(), path_span.into()),
+      )
+      .with_message(LabelMessage::WrongPatternCount(expected, actual)),
+    ),
+    path.display().to_string(),
+    code,
+  )
+}
+
+fn add_report_non_exhaustive_match_error(
+  program: &Program,
+  missing_pats: &[Pat],
+  span: Span,
+) {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  // only the first missing patterns are shown, there may be a lot of them
+  let mut missing_pats_fmt = missing_pats
     .iter()
-    .map(|input| format!("`{}`", input))
+    .take(3)
+    .map(|pat| format!("`{pat}`"))
     .collect::<Vec<_>>()
     .join(", ");
 
-  let actual_callee = format!("{}({})", callee, expected_inputs_fmt);
+  if missing_pats.len() > 3 {
+    missing_pats_fmt += &format!(" and {} more", missing_pats.len() - 3);
+  }
 
   program.reporter.add_report(
     Report::new(
       ReportKind::Error,
       path.display().to_string(),
-      ReportOffset(callee.span.lo),
+      ReportOffset(span.lo),
     )
-    .with_code(ReportCode(3)) // FIXME #2
-    .with_message(ReportMessage::MissingInputs)
+    .with_code(ReportCode(13))
+    .with_message(ReportMessage::NonExhaustiveMatch)
     .with_label(
-      Label::new(
-        LabelKind::Error,
-        (path.display().to_string(), callee.span.into()),
-      )
-      .with_message(LabelMessage::MissingInputs(expected_inputs_fmt)),
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::NonExhaustiveMatch(missing_pats_fmt)),
     )
-    .with_note(Note::new(NoteKind::MissingInputs(
-      expected_inputs.len(),
-      actual_inputs.len(),
-    )))
-    .with_help(Help::new(HelpKind::MissingInputs(actual_callee))),
+    .with_help(Help::new(HelpKind::NonExhaustiveMatch(
+      missing_pats[0].to_string(),
+    ))),
     path.display().to_string(),
     code,
-  );
+  )
 }
 
-fn add_report_wrong_un_op_error(program: &Program, op: &UnOp, ty: &Ty) {
-  let source_id = program.reporter.source(op.span);
+fn add_report_unreachable_pattern_warning(program: &Program, span: Span) {
+  let source_id = program.reporter.source(span);
   let code = program.reporter.code(source_id);
-  let path = program.reporter.path(op.span);
+  let path = program.reporter.path(span);
 
   program.reporter.add_report(
     Report::new(
-      ReportKind::Error,
+      ReportKind::Warning,
       path.display().to_string(),
-      ReportOffset(op.span.lo),
+      ReportOffset(span.lo),
     )
-    .with_code(ReportCode(3)) // FIXME #2
-    .with_message(ReportMessage::WrongUnOp(op.node.to_string()))
+    .with_message(ReportMessage::UnreachablePattern)
     .with_label(
       Label::new(
-        LabelKind::Error,
-        (path.display().to_string(), op.span.into()),
+        LabelKind::Warning,
+        (path.display().to_string(), span.into()),
       )
-      .with_message(LabelMessage::WrongUnOp(ty.to_string())),
+      .with_message(LabelMessage::UnreachablePattern),
     ),
     path.display().to_string(),
     code,
   )
 }
 
-fn add_report_name_clash_if_error(program: &Program, input: &Arg) {
-  let span = input.span;
+fn add_report_not_iterable_error(program: &Program, ty: &Ty, span: Span) {
   let source_id = program.reporter.source(span);
   let code = program.reporter.code(source_id);
   let path = program.reporter.path(span);
@@ -651,134 +3367,150 @@ fn add_report_name_clash_if_error(program: &Program, input: &Arg) {
       path.display().to_string(),
       ReportOffset(span.lo),
     )
-    .with_code(ReportCode(7)) // FIXME #2
-    .with_message(ReportMessage::NameClash)
+    .with_code(ReportCode(5)) // FIXME #2
+    .with_message(ReportMessage::NotIterable(ty.to_string()))
     .with_label(
       Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
-        .with_message(LabelMessage::NameClash),
+        .with_message(LabelMessage::NotIterable),
+    ),
+    path.display().to_string(),
+    code,
+  )
+}
+
+fn add_report_shadowed_it_warning(program: &Program, span: Span) {
+  let source_id = program.reporter.source(span);
+  let code = program.reporter.code(source_id);
+  let path = program.reporter.path(span);
+
+  program.reporter.add_report(
+    Report::new(
+      ReportKind::Warning,
+      path.display().to_string(),
+      ReportOffset(span.lo),
     )
-    .with_note(Note::new(NoteKind::NameClash)),
+    .with_message(ReportMessage::ShadowedIt)
+    .with_label(
+      Label::new(
+        LabelKind::Warning,
+        (path.display().to_string(), span.into()),
+      )
+      .with_message(LabelMessage::ShadowedIt),
+    ),
     path.display().to_string(),
     code,
   )
 }
 
-fn raise_report_wrong_bin_op_error(
-  program: &Program,
-  op: &BinOp,
-  t1: &Ty,
-  t2: &Ty,
-) -> ! {
-  let source_id = program.reporter.source(op.span);
+fn raise_report_cannot_infer_error(program: &Program, name: &Pattern) -> ! {
+  let source_id = program.reporter.source(name.span);
   let code = program.reporter.code(source_id);
-  let path = program.reporter.path(op.span);
+  let path = program.reporter.path(name.span);
 
   program.reporter.raise(
     Report::new(
       ReportKind::Error,
       path.display().to_string(),
-      ReportOffset(op.span.lo),
+      ReportOffset(name.span.lo),
     )
-    .with_code(ReportCode(7)) // FIXME #2
-    .with_message(ReportMessage::WrongBinOp)
+    .with_code(ReportCode(5)) // FIXME #2
+    .with_message(ReportMessage::CannotInfer(name.to_string()))
     .with_label(
       Label::new(
         LabelKind::Error,
-        (path.display().to_string(), op.span.into()),
+        (path.display().to_string(), name.span.into()),
       )
-      .with_message(LabelMessage::WrongBinOp(t1.to_string(), t2.to_string())),
+      .with_message(LabelMessage::CannotInfer(name.to_string())),
     ),
     path.display().to_string(),
     code,
   )
 }
 
-fn raise_report_wrong_assign_op_error(
+fn raise_report_cannot_infer_ty_arg_error(
   program: &Program,
-  op: &BinOp,
-  t1: &Ty,
-  t2: &Ty,
+  ty_param: &str,
+  name: &str,
+  span: Span,
 ) -> ! {
-  let source_id = program.reporter.source(op.span);
+  let source_id = program.reporter.source(span);
   let code = program.reporter.code(source_id);
-  let path = program.reporter.path(op.span);
+  let path = program.reporter.path(span);
 
   program.reporter.raise(
     Report::new(
       ReportKind::Error,
       path.display().to_string(),
-      ReportOffset(op.span.lo),
+      ReportOffset(span.lo),
     )
-    .with_code(ReportCode(7)) // FIXME #2
-    .with_message(ReportMessage::WrongAssignOp)
+    .with_code(ReportCode(5)) // FIXME #2
+    .with_message(ReportMessage::CannotInferTyArg(
+      ty_param.to_string(),
+      name.to_string(),
+    ))
     .with_label(
-      Label::new(
-        LabelKind::Error,
-        (path.display().to_string(), op.span.into()),
-      )
-      .with_message(LabelMessage::WrongAssignOp(
-        t1.to_string(),
-        t2.to_string(),
-      )),
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::CannotInferTyArg(ty_param.to_string())),
     ),
     path.display().to_string(),
     code,
   )
 }
 
-fn add_report_out_of_loop(program: &Program, name: String, span: Span) {
+fn raise_report_invalid_try_error(program: &Program, ty: &Ty, span: Span) -> ! {
   let source_id = program.reporter.source(span);
   let code = program.reporter.code(source_id);
   let path = program.reporter.path(span);
 
-  program.reporter.add_report(
+  program.reporter.raise(
     Report::new(
       ReportKind::Error,
       path.display().to_string(),
       ReportOffset(span.lo),
     )
-    .with_code(ReportCode(4))
-    .with_message(ReportMessage::OutOfLoop(name.to_string()))
+    .with_code(ReportCode(5)) // FIXME #2
+    .with_message(ReportMessage::InvalidTry(ty.to_string()))
     .with_label(
       Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
-        .with_message(LabelMessage::OutOfLoop(name)),
+        .with_message(LabelMessage::InvalidTry),
     ),
     path.display().to_string(),
     code,
-  );
+  )
 }
 
-fn raise_report_type_mismatch_error(program: &Program, t1: &Ty, t2: &Ty) -> ! {
-  let source_id = program.reporter.source(t1.span);
+fn add_report_misplaced_try_error(
+  program: &Program,
+  return_ty: &Ty,
+  try_ty: &Ty,
+) {
+  let source_id = program.reporter.source(try_ty.span);
   let code = program.reporter.code(source_id);
-  let path = program.reporter.path(t1.span);
+  let path = program.reporter.path(try_ty.span);
 
-  program.reporter.raise(
+  program.reporter.add_report(
     Report::new(
       ReportKind::Error,
       path.display().to_string(),
-      ReportOffset(t2.span.lo),
+      ReportOffset(try_ty.span.lo),
     )
-    .with_message(ReportMessage::TypeMismatch)
     .with_code(ReportCode(5)) // FIXME #2
+    .with_message(ReportMessage::MisplacedTry(return_ty.to_string()))
     .with_label(
       Label::new(
         LabelKind::Error,
-        (path.display().to_string(), t2.span.into()),
+        (path.display().to_string(), try_ty.span.into()),
       )
-      .with_message(LabelMessage::TypeMismatch(t1.to_string(), t2.to_string())),
+      .with_message(LabelMessage::MisplacedTry(try_ty.to_string())),
     )
     .with_label(
       Label::new(
         LabelKind::Hint,
-        (
-          path.display().to_string(),
-          t1.span.lo as usize..t1.span.hi as usize,
-        ),
+        (path.display().to_string(), return_ty.span.into()),
       )
-      .with_message(LabelMessage::TypeMismatchDefinedAs(t1.to_string())),
+      .with_message(LabelMessage::TypeMismatchDefinedAs(return_ty.to_string())),
     ),
     path.display().to_string(),
     code,
-  )
+  );
 }
@@ -1,6 +1,13 @@
+use super::infer::Unifier;
 use super::scope::ScopeMap;
+use super::visibility::{item_defs, ItemDef};
 
-use crate::front::parser::ast::{pbox, PBox, Program, Ty};
+use crate::front::parser::ast::{
+  pbox, InferredTy, PBox, Pattern, Program, Ty, TyArgs,
+};
+use crate::util::span::Span;
+
+use std::collections::{HashMap, HashSet};
 
 #[derive(Clone, Debug)]
 pub struct Context<'a> {
@@ -8,6 +15,42 @@ pub struct Context<'a> {
   pub loops: u32,
   pub program: &'a Program,
   pub return_ty: PBox<Ty>,
+  pub self_ty: Option<PBox<Ty>>,
+  // the depth of the scope of each enclosing lambda with the names that it
+  // captures
+  pub lambdas: Vec<(usize, Vec<String>)>,
+  pub unifier: Unifier,
+  // the aliases being resolved, an alias that refers back to one of them is
+  // cyclic
+  pub aliases: Vec<String>,
+  // the aliases of the cycles already reported
+  pub cyclic_aliases: HashSet<String>,
+  // the unknown types already reported, a prototype is resolved both when it
+  // is declared and when its function is checked
+  pub unknown_tys: HashSet<Span>,
+  // the top-level items, to check that the other modules may use them
+  pub items: HashMap<String, ItemDef>,
+  // the uses of the generic items, their type arguments are inferred once the
+  // enclosing item is checked
+  pub instances: Vec<Instance>,
+  // the declarations written with `:=`, their type is finalized once the
+  // enclosing item is checked
+  pub decls: Vec<(Pattern, InferredTy)>,
+  // the names of the tests of every unit, the test binary runs a test by its
+  // name
+  pub tests: HashSet<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Instance {
+  pub name: String,
+  pub ty_params: Vec<String>,
+  pub ty_args: TyArgs,
+  // the behaviors that each type argument must implement, and the ones that
+  // bound the type parameters in scope where the item is used
+  pub bounds: Vec<Vec<String>>,
+  pub scope_bounds: HashMap<String, Vec<String>>,
+  pub span: Span,
 }
 
 impl<'a> Context<'a> {
@@ -24,6 +67,16 @@ impl<'a> Context<'a> {
       loops: 0,
       program,
       return_ty: pbox(Ty::VOID),
+      self_ty: None,
+      lambdas: vec![],
+      unifier: Unifier::default(),
+      aliases: vec![],
+      cyclic_aliases: HashSet::new(),
+      unknown_tys: HashSet::new(),
+      items: item_defs(program),
+      instances: vec![],
+      decls: vec![],
+      tests: HashSet::new(),
     }
   }
 }
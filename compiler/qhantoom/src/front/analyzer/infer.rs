@@ -0,0 +1,272 @@
+use crate::front::parser::ast::{PBox, Ty, TyKind};
+use crate::util::span::Span;
+
+// a type variable is unbound until it is unified with another type. the
+// variable of an integer literal can only be bound to an integer type and
+// becomes `uint` if nothing else is known about it
+#[derive(Clone, Debug)]
+enum Var {
+  Unbound,
+  Bound(PBox<Ty>),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Unifier {
+  vars: Vec<Var>,
+}
+
+impl Unifier {
+  pub fn fresh(&mut self, span: Span) -> PBox<Ty> {
+    self.vars.push(Var::Unbound);
+
+    Ty::new(TyKind::Infer(self.vars.len() - 1), span).into()
+  }
+
+  pub fn fresh_int(&mut self, span: Span) -> PBox<Ty> {
+    self.vars.push(Var::Unbound);
+
+    Ty::new(TyKind::InferInt(self.vars.len() - 1), span).into()
+  }
+
+  // replaces every bound variable by its type, the span and the alias of the
+  // given type are kept so that a report points to where the type is used
+  pub fn resolve(&self, ty: &Ty) -> PBox<Ty> {
+    let kind = match &ty.kind {
+      TyKind::Infer(id) | TyKind::InferInt(id) => match &self.vars[*id] {
+        Var::Bound(bound_ty) => self.resolve(bound_ty).kind.to_owned(),
+        Var::Unbound => ty.kind.to_owned(),
+      },
+      TyKind::Fn(input_tys, return_ty) => TyKind::Fn(
+        input_tys.iter().map(|ty| self.resolve(ty)).collect(),
+        self.resolve(return_ty),
+      ),
+      TyKind::Array(element_ty, len) => {
+        TyKind::Array(self.resolve(element_ty), *len)
+      }
+      TyKind::Option(some_ty) => TyKind::Option(self.resolve(some_ty)),
+      TyKind::Result(ok_ty, err_ty) => {
+        TyKind::Result(self.resolve(ok_ty), self.resolve(err_ty))
+      }
+      TyKind::App(name, tys) => TyKind::App(
+        name.to_owned(),
+        tys.iter().map(|ty| self.resolve(ty)).collect(),
+      ),
+      kind => kind.to_owned(),
+    };
+
+    match &ty.alias {
+      Some(alias) => Ty::with_alias(kind, ty.span, alias.to_owned()).into(),
+      None => Ty::new(kind, ty.span).into(),
+    }
+  }
+
+  pub fn unify(&mut self, t1: &Ty, t2: &Ty) -> bool {
+    let t1 = self.resolve(t1);
+    let t2 = self.resolve(t2);
+
+    match (&t1.kind, &t2.kind) {
+      (TyKind::Infer(lhs), TyKind::Infer(rhs))
+      | (TyKind::InferInt(lhs), TyKind::InferInt(rhs))
+        if lhs == rhs =>
+      {
+        true
+      }
+      (TyKind::Infer(id), _) => self.bind(*id, &t2),
+      (_, TyKind::Infer(id)) => self.bind(*id, &t1),
+      (TyKind::InferInt(id), _) if t2.is_integer() => self.bind(*id, &t2),
+      (_, TyKind::InferInt(id)) if t1.is_integer() => self.bind(*id, &t1),
+      (
+        TyKind::Fn(lhs_tys, lhs_return_ty),
+        TyKind::Fn(rhs_tys, rhs_return_ty),
+      ) => {
+        lhs_tys.len() == rhs_tys.len()
+          && lhs_tys
+            .iter()
+            .zip(rhs_tys)
+            .all(|(lhs, rhs)| self.unify(lhs, rhs))
+          && self.unify(lhs_return_ty, rhs_return_ty)
+      }
+      (TyKind::Array(lhs_ty, lhs_len), TyKind::Array(rhs_ty, rhs_len)) => {
+        lhs_len == rhs_len && self.unify(lhs_ty, rhs_ty)
+      }
+      (TyKind::Option(lhs_ty), TyKind::Option(rhs_ty)) => {
+        self.unify(lhs_ty, rhs_ty)
+      }
+      (TyKind::Result(lhs_ok, lhs_err), TyKind::Result(rhs_ok, rhs_err)) => {
+        self.unify(lhs_ok, rhs_ok) && self.unify(lhs_err, rhs_err)
+      }
+      (TyKind::App(lhs_name, lhs_tys), TyKind::App(rhs_name, rhs_tys)) => {
+        lhs_name == rhs_name
+          && lhs_tys.len() == rhs_tys.len()
+          && lhs_tys
+            .iter()
+            .zip(rhs_tys)
+            .all(|(lhs, rhs)| self.unify(lhs, rhs))
+      }
+      (lhs, rhs) => lhs == rhs,
+    }
+  }
+
+  // binds the integer variables left to `uint`. `None` is returned if the
+  // type still contains a variable that nothing constrains
+  pub fn finalize(&mut self, ty: &Ty) -> Option<PBox<Ty>> {
+    let ty = self.resolve(ty);
+
+    match &ty.kind {
+      TyKind::Infer(_) => None,
+      TyKind::InferInt(id) => {
+        self.vars[*id] = Var::Bound(Ty::with_uint(ty.span).into());
+        Some(Ty::with_uint(ty.span).into())
+      }
+      TyKind::Fn(input_tys, return_ty) => {
+        let input_tys = input_tys
+          .iter()
+          .map(|ty| self.finalize(ty))
+          .collect::<Option<Vec<_>>>()?;
+
+        let return_ty = self.finalize(return_ty)?;
+
+        Some(Ty::with_fn(input_tys, return_ty, ty.span).into())
+      }
+      TyKind::Array(element_ty, len) => {
+        let element_ty = self.finalize(element_ty)?;
+
+        Some(Ty::new(TyKind::Array(element_ty, *len), ty.span).into())
+      }
+      TyKind::Option(some_ty) => {
+        let some_ty = self.finalize(some_ty)?;
+
+        Some(Ty::with_option(some_ty, ty.span).into())
+      }
+      TyKind::Result(ok_ty, err_ty) => {
+        let ok_ty = self.finalize(ok_ty)?;
+        let err_ty = self.finalize(err_ty)?;
+
+        Some(Ty::with_result(ok_ty, err_ty, ty.span).into())
+      }
+      TyKind::App(name, tys) => {
+        let tys = tys
+          .iter()
+          .map(|ty| self.finalize(ty))
+          .collect::<Option<Vec<_>>>()?;
+
+        Some(Ty::new(TyKind::App(name.to_owned(), tys), ty.span).into())
+      }
+      _ => Some(ty),
+    }
+  }
+
+  fn bind(&mut self, id: usize, ty: &Ty) -> bool {
+    if self.occurs(id, ty) {
+      return false;
+    }
+
+    self.vars[id] = Var::Bound(ty.into());
+    true
+  }
+
+  fn occurs(&self, id: usize, ty: &Ty) -> bool {
+    match &ty.kind {
+      TyKind::Infer(var_id) | TyKind::InferInt(var_id) => {
+        match &self.vars[*var_id] {
+          Var::Bound(bound_ty) => self.occurs(id, bound_ty),
+          Var::Unbound => *var_id == id,
+        }
+      }
+      TyKind::Fn(input_tys, return_ty) => {
+        input_tys.iter().any(|ty| self.occurs(id, ty))
+          || self.occurs(id, return_ty)
+      }
+      TyKind::Array(element_ty, _) | TyKind::Option(element_ty) => {
+        self.occurs(id, element_ty)
+      }
+      TyKind::Result(ok_ty, err_ty) => {
+        self.occurs(id, ok_ty) || self.occurs(id, err_ty)
+      }
+      TyKind::App(_, tys) => tys.iter().any(|ty| self.occurs(id, ty)),
+      _ => false,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn span() -> Span {
+    Span::new(0, 0)
+  }
+
+  fn ty(kind: TyKind) -> PBox<Ty> {
+    Ty::new(kind, span()).into()
+  }
+
+  #[test]
+  fn unbound_integer_defaults_to_uint() {
+    let mut unifier = Unifier::default();
+    let int = unifier.fresh_int(span());
+
+    assert_eq!(unifier.finalize(&int).unwrap().kind, TyKind::UInt);
+  }
+
+  #[test]
+  fn integer_takes_the_type_it_is_unified_with() {
+    let mut unifier = Unifier::default();
+    let int = unifier.fresh_int(span());
+    let other = unifier.fresh_int(span());
+
+    assert!(unifier.unify(&int, &other));
+    assert!(unifier.unify(&other, &ty(TyKind::U8)));
+    assert_eq!(unifier.finalize(&int).unwrap().kind, TyKind::U8);
+  }
+
+  #[test]
+  fn integer_is_not_unified_with_other_types() {
+    let mut unifier = Unifier::default();
+    let int = unifier.fresh_int(span());
+
+    assert!(!unifier.unify(&int, &ty(TyKind::Str)));
+    assert!(!unifier.unify(&int, &ty(TyKind::F64)));
+    assert!(unifier.unify(&int, &ty(TyKind::S64)));
+  }
+
+  #[test]
+  fn defaulting_reaches_nested_variables() {
+    let mut unifier = Unifier::default();
+    let int = unifier.fresh_int(span());
+    let option = ty(TyKind::Option(int));
+
+    assert_eq!(
+      unifier.finalize(&option).unwrap().kind,
+      TyKind::Option(ty(TyKind::UInt))
+    );
+  }
+
+  #[test]
+  fn unconstrained_variable_is_not_finalized() {
+    let mut unifier = Unifier::default();
+    let var = unifier.fresh(span());
+
+    assert!(unifier.finalize(&var).is_none());
+  }
+
+  #[test]
+  fn occurs_check_rejects_infinite_types() {
+    let mut unifier = Unifier::default();
+    let var = unifier.fresh(span());
+    let option = ty(TyKind::Option(var.to_owned()));
+
+    assert!(!unifier.unify(&var, &option));
+  }
+
+  #[test]
+  fn occurs_check_follows_bound_variables() {
+    let mut unifier = Unifier::default();
+    let var = unifier.fresh(span());
+    let other = unifier.fresh(span());
+    let array = ty(TyKind::Array(var.to_owned(), 2));
+
+    assert!(unifier.unify(&other, &array));
+    assert!(!unifier.unify(&var, &ty(TyKind::Option(other))));
+  }
+}
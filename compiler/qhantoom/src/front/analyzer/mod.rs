@@ -1,5 +1,8 @@
 mod checker;
 mod context;
+mod infer;
+mod pattern;
 mod scope;
+mod visibility;
 
-pub use checker::analyze;
+pub use checker::{analyze, analyze_tests};
@@ -0,0 +1,356 @@
+use crate::front::analyzer::scope::ScopeMap;
+use crate::front::parser::ast::{PBox, Sep, Ty, TyKind};
+
+use std::fmt::{Display, Formatter, Result};
+
+// a pattern reduced to what matters for the exhaustiveness checking, it
+// either matches any value or tests a constructor and its payload
+#[derive(Clone, Debug)]
+pub enum Pat {
+  Wild,
+  Ctor(Ctor, Vec<Pat>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Ctor {
+  Bool(bool),
+  Lit(String),
+  Variant(String, String),
+  // a variant of `Option` or `Result`, which is named without a path
+  Prelude(String),
+}
+
+impl Display for Pat {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    match self {
+      Self::Wild => write!(f, "_"),
+      Self::Ctor(ctor, pats) if pats.is_empty() => write!(f, "{ctor}"),
+      Self::Ctor(ctor, pats) => write!(f, "{ctor}({})", Sep(pats, ", ")),
+    }
+  }
+}
+
+impl Display for Ctor {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    match self {
+      Self::Bool(boolean) => write!(f, "{boolean}"),
+      Self::Lit(lit) => write!(f, "{lit}"),
+      Self::Variant(enum_name, name) => write!(f, "{enum_name}::{name}"),
+      Self::Prelude(name) => write!(f, "{name}"),
+    }
+  }
+}
+
+// an arm is reachable if it matches a value that none of the previous arms
+// match
+pub fn is_reachable(
+  scope_map: &ScopeMap,
+  pats: &[Pat],
+  pat: &Pat,
+  ty: &PBox<Ty>,
+) -> bool {
+  let rows = pats.iter().map(|pat| vec![pat.clone()]).collect::<Vec<_>>();
+
+  let (pats, tys) = (std::slice::from_ref(pat), std::slice::from_ref(ty));
+
+  !witnesses(scope_map, &rows, pats, tys).is_empty()
+}
+
+// the values of type `ty` that none of the patterns match, a match is
+// exhaustive when there is none
+pub fn missing_pats(
+  scope_map: &ScopeMap,
+  pats: &[Pat],
+  ty: &PBox<Ty>,
+) -> Vec<Pat> {
+  let rows = pats.iter().map(|pat| vec![pat.clone()]).collect::<Vec<_>>();
+
+  witnesses(scope_map, &rows, &[Pat::Wild], std::slice::from_ref(ty))
+    .into_iter()
+    .filter_map(|witness| witness.into_iter().next())
+    .collect::<Vec<_>>()
+}
+
+// the usefulness algorithm from "warnings for pattern matching" by luc
+// maranget. the witnesses are the values matched by `row` and by none of
+// the `rows`, `row` is useless when there is none
+fn witnesses(
+  scope_map: &ScopeMap,
+  rows: &[Vec<Pat>],
+  row: &[Pat],
+  tys: &[PBox<Ty>],
+) -> Vec<Vec<Pat>> {
+  let Some((head, tail)) = row.split_first() else {
+    return if rows.is_empty() {
+      vec![vec![]]
+    } else {
+      vec![]
+    };
+  };
+
+  if let Pat::Ctor(ctor, pats) = head {
+    let row = [pats.as_slice(), tail].concat();
+
+    return specialized_witnesses(scope_map, rows, ctor, &row, tys);
+  }
+
+  // a type with a few constructors is split into each of them, so that
+  // every missing value is found
+  if let Some(all_ctors) = ctors(scope_map, &tys[0]) {
+    return all_ctors
+      .iter()
+      .flat_map(|ctor| {
+        let arity = ctor_tys(scope_map, ctor, &tys[0]).len();
+        let row = [vec![Pat::Wild; arity].as_slice(), tail].concat();
+
+        specialized_witnesses(scope_map, rows, ctor, &row, tys)
+      })
+      .collect::<Vec<_>>();
+  }
+
+  // otherwise only the rows starting with a wildcard can match the values
+  // that are not listed by the other rows
+  let default_rows = rows
+    .iter()
+    .filter(|row| matches!(row[0], Pat::Wild))
+    .map(|row| row[1..].to_vec())
+    .collect::<Vec<_>>();
+
+  witnesses(scope_map, &default_rows, tail, &tys[1..])
+    .into_iter()
+    .map(|witness| [vec![Pat::Wild], witness].concat())
+    .collect::<Vec<_>>()
+}
+
+fn specialized_witnesses(
+  scope_map: &ScopeMap,
+  rows: &[Vec<Pat>],
+  ctor: &Ctor,
+  row: &[Pat],
+  tys: &[PBox<Ty>],
+) -> Vec<Vec<Pat>> {
+  let sub_tys = ctor_tys(scope_map, ctor, &tys[0]);
+  let arity = sub_tys.len();
+
+  let rows = rows
+    .iter()
+    .filter_map(|row| specialize(row, ctor, arity))
+    .collect::<Vec<_>>();
+
+  let tys = [sub_tys.as_slice(), &tys[1..]].concat();
+
+  witnesses(scope_map, &rows, row, &tys)
+    .into_iter()
+    .map(|witness| {
+      let (pats, rest) = witness.split_at(arity);
+
+      [
+        vec![Pat::Ctor(ctor.to_owned(), pats.to_vec())],
+        rest.to_vec(),
+      ]
+      .concat()
+    })
+    .collect::<Vec<_>>()
+}
+
+// keeps the rows that can match `ctor`, its payload takes the place of the
+// first column
+fn specialize(row: &[Pat], ctor: &Ctor, arity: usize) -> Option<Vec<Pat>> {
+  match &row[0] {
+    Pat::Ctor(row_ctor, pats) if row_ctor == ctor => {
+      Some([pats.as_slice(), &row[1..]].concat())
+    }
+    Pat::Ctor(..) => None,
+    Pat::Wild => Some([vec![Pat::Wild; arity].as_slice(), &row[1..]].concat()),
+  }
+}
+
+// the constructors of a type, `None` if there are too many to be listed
+fn ctors(scope_map: &ScopeMap, ty: &Ty) -> Option<Vec<Ctor>> {
+  match &ty.kind {
+    TyKind::Bool => Some(vec![Ctor::Bool(true), Ctor::Bool(false)]),
+    TyKind::Enum(enum_name) => scope_map.variants(enum_name).map(|variants| {
+      variants
+        .iter()
+        .map(|(name, _)| Ctor::Variant(enum_name.into(), name.into()))
+        .collect::<Vec<_>>()
+    }),
+    TyKind::Option(_) => Some(vec![
+      Ctor::Prelude("None".into()),
+      Ctor::Prelude("Some".into()),
+    ]),
+    TyKind::Result(..) => Some(vec![
+      Ctor::Prelude("Ok".into()),
+      Ctor::Prelude("Err".into()),
+    ]),
+    _ => None,
+  }
+}
+
+// the types of the payload of a constructor of the type `ty`
+fn ctor_tys(scope_map: &ScopeMap, ctor: &Ctor, ty: &Ty) -> Vec<PBox<Ty>> {
+  match ctor {
+    Ctor::Variant(enum_name, name) => scope_map
+      .variants(enum_name)
+      .and_then(|variants| {
+        variants
+          .iter()
+          .find(|(variant_name, _)| variant_name == name)
+          .map(|(_, tys)| tys.to_owned())
+      })
+      .unwrap_or_default(),
+    Ctor::Prelude(name) => match (name.as_str(), &ty.kind) {
+      ("Some", TyKind::Option(some_ty)) => vec![some_ty.to_owned()],
+      ("Ok", TyKind::Result(ok_ty, _)) => vec![ok_ty.to_owned()],
+      ("Err", TyKind::Result(_, err_ty)) => vec![err_ty.to_owned()],
+      _ => vec![],
+    },
+    _ => vec![],
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::util::span::Span;
+
+  fn ty(kind: TyKind) -> PBox<Ty> {
+    Ty::new(kind, Span::new(0, 0)).into()
+  }
+
+  fn variant(enum_name: &str, name: &str, pats: Vec<Pat>) -> Pat {
+    Pat::Ctor(Ctor::Variant(enum_name.into(), name.into()), pats)
+  }
+
+  // enum Shape { Empty, Dot(uint), Pair(bool, uint) }
+  // enum Wrap { Some(Shape), None }
+  fn scope_map() -> ScopeMap {
+    let mut scope_map = ScopeMap::default();
+
+    scope_map
+      .set_variants(
+        "Shape".into(),
+        vec![
+          ("Empty".into(), vec![]),
+          ("Dot".into(), vec![ty(TyKind::UInt)]),
+          ("Pair".into(), vec![ty(TyKind::Bool), ty(TyKind::UInt)]),
+        ],
+      )
+      .unwrap();
+
+    scope_map
+      .set_variants(
+        "Wrap".into(),
+        vec![
+          ("Some".into(), vec![ty(TyKind::Enum("Shape".into()))]),
+          ("None".into(), vec![]),
+        ],
+      )
+      .unwrap();
+
+    scope_map
+  }
+
+  fn missing(scope_map: &ScopeMap, pats: &[Pat], ty: &PBox<Ty>) -> String {
+    missing_pats(scope_map, pats, ty)
+      .iter()
+      .map(|pat| pat.to_string())
+      .collect::<Vec<_>>()
+      .join(" ")
+  }
+
+  #[test]
+  fn every_variant_is_exhaustive() {
+    let scope_map = scope_map();
+    let shape = ty(TyKind::Enum("Shape".into()));
+
+    let pats = vec![
+      variant("Shape", "Empty", vec![]),
+      variant("Shape", "Dot", vec![Pat::Wild]),
+      variant("Shape", "Pair", vec![Pat::Wild, Pat::Wild]),
+    ];
+
+    assert_eq!(missing(&scope_map, &pats, &shape), "");
+  }
+
+  #[test]
+  fn missing_variant_is_reported() {
+    let scope_map = scope_map();
+    let shape = ty(TyKind::Enum("Shape".into()));
+    let pats = vec![variant("Shape", "Dot", vec![Pat::Wild])];
+
+    assert_eq!(
+      missing(&scope_map, &pats, &shape),
+      "Shape::Empty Shape::Pair(true, _) Shape::Pair(false, _)"
+    );
+  }
+
+  #[test]
+  fn wildcard_is_exhaustive() {
+    let scope_map = scope_map();
+    let shape = ty(TyKind::Enum("Shape".into()));
+    let pats = vec![variant("Shape", "Empty", vec![]), Pat::Wild];
+
+    assert_eq!(missing(&scope_map, &pats, &shape), "");
+    assert_eq!(missing(&scope_map, &[], &ty(TyKind::UInt)), "_");
+  }
+
+  #[test]
+  fn nested_variants_are_checked() {
+    let scope_map = scope_map();
+    let wrap = ty(TyKind::Enum("Wrap".into()));
+
+    let pair = variant(
+      "Shape",
+      "Pair",
+      vec![Pat::Ctor(Ctor::Bool(true), vec![]), Pat::Wild],
+    );
+
+    let pats = vec![
+      variant("Wrap", "Some", vec![pair]),
+      variant("Wrap", "Some", vec![variant("Shape", "Empty", vec![])]),
+      variant("Wrap", "None", vec![]),
+    ];
+
+    assert_eq!(
+      missing(&scope_map, &pats, &wrap),
+      "Wrap::Some(Shape::Dot(_)) Wrap::Some(Shape::Pair(false, _))"
+    );
+  }
+
+  #[test]
+  fn literals_need_a_wildcard() {
+    let scope_map = ScopeMap::default();
+    let uint = ty(TyKind::UInt);
+    let pats = vec![Pat::Ctor(Ctor::Lit("1".into()), vec![])];
+
+    assert_eq!(missing(&scope_map, &pats, &uint), "_");
+  }
+
+  #[test]
+  fn arm_after_a_wildcard_is_unreachable() {
+    let scope_map = scope_map();
+    let shape = ty(TyKind::Enum("Shape".into()));
+    let empty = variant("Shape", "Empty", vec![]);
+
+    assert!(is_reachable(&scope_map, &[], &empty, &shape));
+    assert!(!is_reachable(&scope_map, &[Pat::Wild], &empty, &shape));
+
+    let pats = std::slice::from_ref(&empty);
+
+    assert!(!is_reachable(&scope_map, pats, &empty, &shape));
+  }
+
+  #[test]
+  fn option_needs_both_variants() {
+    let scope_map = ScopeMap::default();
+    let option = ty(TyKind::Option(ty(TyKind::Bool)));
+
+    let pats = vec![Pat::Ctor(
+      Ctor::Prelude("Some".into()),
+      vec![Pat::Ctor(Ctor::Bool(true), vec![])],
+    )];
+
+    assert_eq!(missing(&scope_map, &pats, &option), "None Some(false)");
+  }
+}
@@ -2,10 +2,27 @@ use crate::front::parser::ast::{PBox, Ty};
 
 use std::collections::HashMap;
 
+// the variants of an enum with the types of their payload
+pub type Variants = Vec<(String, Vec<PBox<Ty>>)>;
+
+// the methods of a behavior with their type and whether they have a default
+// body, `Self` is a type parameter in their type
+pub type Methods = Vec<(String, (PBox<Ty>, Vec<PBox<Ty>>), bool)>;
+
 #[derive(Clone, Debug, Default)]
 struct Scope {
   decls: HashMap<String, PBox<Ty>>,
   funs: HashMap<String, (PBox<Ty>, Vec<PBox<Ty>>)>,
+  datas: HashMap<String, Vec<(String, PBox<Ty>)>>,
+  enums: HashMap<String, Variants>,
+  tys: HashMap<String, PBox<Ty>>,
+  // the type parameters of the generic functions and data types with the
+  // behaviors that bound them
+  generics: HashMap<String, Vec<(String, Vec<String>)>>,
+  ty_params: HashMap<String, Vec<String>>,
+  behaviors: HashMap<String, Methods>,
+  // the behaviors implemented by each type
+  impls: HashMap<String, Vec<String>>,
 }
 
 impl Scope {
@@ -17,6 +34,44 @@ impl Scope {
     self.funs.get(name)
   }
 
+  fn data(&self, name: &str) -> Option<&Vec<(String, PBox<Ty>)>> {
+    self.datas.get(name)
+  }
+
+  fn variants(&self, name: &str) -> Option<&Variants> {
+    self.enums.get(name)
+  }
+
+  fn ty(&self, name: &str) -> Option<&PBox<Ty>> {
+    self.tys.get(name)
+  }
+
+  fn generics(&self, name: &str) -> Option<&Vec<(String, Vec<String>)>> {
+    self.generics.get(name)
+  }
+
+  fn ty_param(&self, name: &str) -> Option<&Vec<String>> {
+    self.ty_params.get(name)
+  }
+
+  fn behavior(&self, name: &str) -> Option<&Methods> {
+    self.behaviors.get(name)
+  }
+
+  fn implements(&self, ty_name: &str, behavior: &str) -> bool {
+    self
+      .impls
+      .get(ty_name)
+      .is_some_and(|behaviors| behaviors.iter().any(|name| name == behavior))
+  }
+
+  fn declares(&self, name: &str) -> bool {
+    self.decls.contains_key(name)
+      || self.funs.contains_key(name)
+      || self.datas.contains_key(name)
+      || self.enums.contains_key(name)
+  }
+
   fn set_decl(&mut self, name: String, ty: PBox<Ty>) -> Result<(), String> {
     match self.decls.get(&name) {
       Some(_) => Err(format!("variable `{name}` already exists")),
@@ -40,6 +95,58 @@ impl Scope {
       }
     }
   }
+
+  fn set_data(
+    &mut self,
+    name: String,
+    fields: Vec<(String, PBox<Ty>)>,
+  ) -> Result<(), String> {
+    match self.datas.get(&name) {
+      Some(_) => Err(format!("data `{name}` already exists")),
+      None => {
+        self.datas.insert(name, fields);
+        Ok(())
+      }
+    }
+  }
+
+  fn set_variants(
+    &mut self,
+    name: String,
+    variants: Variants,
+  ) -> Result<(), String> {
+    match self.enums.get(&name) {
+      Some(_) => Err(format!("enum `{name}` already exists")),
+      None => {
+        self.enums.insert(name, variants);
+        Ok(())
+      }
+    }
+  }
+
+  fn set_ty(&mut self, name: String, ty: PBox<Ty>) -> Result<(), String> {
+    match self.tys.get(&name) {
+      Some(_) => Err(format!("type `{name}` already exists")),
+      None => {
+        self.tys.insert(name, ty);
+        Ok(())
+      }
+    }
+  }
+
+  fn set_behavior(
+    &mut self,
+    name: String,
+    methods: Methods,
+  ) -> Result<(), String> {
+    match self.behaviors.get(&name) {
+      Some(_) => Err(format!("behavior `{name}` already exists")),
+      None => {
+        self.behaviors.insert(name, methods);
+        Ok(())
+      }
+    }
+  }
 }
 
 #[derive(Clone, Debug)]
@@ -58,6 +165,20 @@ impl ScopeMap {
     }
   }
 
+  pub fn depth(&self) -> usize {
+    self.maps.len()
+  }
+
+  // the index of the innermost scope in which the name is declared
+  pub fn decl_depth(&self, name: &str) -> Option<usize> {
+    self.maps.iter().rposition(|map| map.decl(name).is_some())
+  }
+
+  // the name is not shadowed by a declaration or an item of a block
+  pub fn is_global(&self, name: &str) -> bool {
+    self.maps.iter().rposition(|map| map.declares(name)) == Some(0)
+  }
+
   pub fn decl(&self, name: &str) -> Option<&PBox<Ty>> {
     for map in self.maps.iter().rev() {
       if let Some(decl) = map.decl(name) {
@@ -78,6 +199,114 @@ impl ScopeMap {
     None
   }
 
+  pub fn data(&self, name: &str) -> Option<&Vec<(String, PBox<Ty>)>> {
+    for map in self.maps.iter().rev() {
+      if let Some(data) = map.data(name) {
+        return Some(data);
+      }
+    }
+
+    None
+  }
+
+  pub fn variants(&self, name: &str) -> Option<&Variants> {
+    for map in self.maps.iter().rev() {
+      if let Some(variants) = map.variants(name) {
+        return Some(variants);
+      }
+    }
+
+    None
+  }
+
+  // the names of the data types, the enums and the aliases in scope
+  pub fn ty_names(&self) -> Vec<&String> {
+    self
+      .maps
+      .iter()
+      .flat_map(|map| {
+        map
+          .datas
+          .keys()
+          .chain(map.enums.keys())
+          .chain(map.tys.keys())
+      })
+      .collect()
+  }
+
+  // the type named by an alias, as it is written
+  pub fn ty(&self, name: &str) -> Option<&PBox<Ty>> {
+    for map in self.maps.iter().rev() {
+      if let Some(ty) = map.ty(name) {
+        return Some(ty);
+      }
+    }
+
+    None
+  }
+
+  // the type parameters of a generic function or data type, a name which is
+  // not generic has none
+  pub fn generics(&self, name: &str) -> Vec<String> {
+    self
+      .bounded_generics(name)
+      .iter()
+      .map(|(ty_param, _)| ty_param.to_owned())
+      .collect()
+  }
+
+  // the type parameters of a generic item with the behaviors that bound them
+  pub fn bounded_generics(&self, name: &str) -> &[(String, Vec<String>)] {
+    for map in self.maps.iter().rev() {
+      if let Some(generics) = map.generics(name) {
+        return generics;
+      }
+    }
+
+    &[]
+  }
+
+  pub fn is_ty_param(&self, name: &str) -> bool {
+    self.ty_param(name).is_some()
+  }
+
+  // the behaviors that bound a type parameter in scope
+  pub fn ty_param(&self, name: &str) -> Option<&Vec<String>> {
+    for map in self.maps.iter().rev() {
+      if let Some(bounds) = map.ty_param(name) {
+        return Some(bounds);
+      }
+    }
+
+    None
+  }
+
+  // every type parameter in scope with its bounds
+  pub fn ty_params(&self) -> HashMap<String, Vec<String>> {
+    self
+      .maps
+      .iter()
+      .flat_map(|map| map.ty_params.to_owned())
+      .collect()
+  }
+
+  pub fn behavior(&self, name: &str) -> Option<&Methods> {
+    for map in self.maps.iter().rev() {
+      if let Some(methods) = map.behavior(name) {
+        return Some(methods);
+      }
+    }
+
+    None
+  }
+
+  pub fn implements(&self, ty_name: &str, behavior: &str) -> bool {
+    self
+      .maps
+      .iter()
+      .any(|map| map.implements(ty_name, behavior))
+  }
+
   pub fn set_decl(&mut self, name: String, ty: PBox<Ty>) -> Result<(), String> {
     match self.maps.last_mut() {
       Some(map) => map.set_decl(name, ty),
@@ -95,6 +324,68 @@ impl ScopeMap {
       None => Err(format!("function {name} value do not exist")),
     }
   }
+
+  pub fn set_data(
+    &mut self,
+    name: String,
+    fields: Vec<(String, PBox<Ty>)>,
+  ) -> Result<(), String> {
+    match self.maps.last_mut() {
+      Some(map) => map.set_data(name, fields),
+      None => Err(format!("data {name} value do not exist")),
+    }
+  }
+
+  pub fn set_variants(
+    &mut self,
+    name: String,
+    variants: Variants,
+  ) -> Result<(), String> {
+    match self.maps.last_mut() {
+      Some(map) => map.set_variants(name, variants),
+      None => Err(format!("enum {name} value do not exist")),
+    }
+  }
+
+  pub fn set_ty(&mut self, name: String, ty: PBox<Ty>) -> Result<(), String> {
+    match self.maps.last_mut() {
+      Some(map) => map.set_ty(name, ty),
+      None => Err(format!("type {name} value do not exist")),
+    }
+  }
+
+  pub fn set_behavior(
+    &mut self,
+    name: String,
+    methods: Methods,
+  ) -> Result<(), String> {
+    match self.maps.last_mut() {
+      Some(map) => map.set_behavior(name, methods),
+      None => Err(format!("behavior {name} value do not exist")),
+    }
+  }
+
+  pub fn set_generics(
+    &mut self,
+    name: String,
+    ty_params: Vec<(String, Vec<String>)>,
+  ) {
+    if let Some(map) = self.maps.last_mut() {
+      map.generics.insert(name, ty_params);
+    }
+  }
+
+  pub fn set_ty_param(&mut self, name: String, bounds: Vec<String>) {
+    if let Some(map) = self.maps.last_mut() {
+      map.ty_params.insert(name, bounds);
+    }
+  }
+
+  pub fn set_impl(&mut self, ty_name: String, behavior: String) {
+    if let Some(map) = self.maps.last_mut() {
+      map.impls.entry(ty_name).or_default().push(behavior);
+    }
+  }
 }
 
 impl Default for ScopeMap {
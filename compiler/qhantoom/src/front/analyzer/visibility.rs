@@ -0,0 +1,142 @@
+use crate::front::parser::ast::Public;
+use crate::front::parser::ast::{Import, Item, ItemKind, Module, Program};
+use crate::util::span::Span;
+
+use std::collections::{HashMap, HashSet};
+
+// where a top-level item is defined and whether the other modules can use it
+#[derive(Clone, Copy, Debug)]
+pub struct ItemDef {
+  pub public: bool,
+  pub span: Span,
+}
+
+// the top-level items of the program and of the loaded modules by name, the
+// functions of an `impl Foo` are named `Foo::name`
+pub fn item_defs(program: &Program) -> HashMap<String, ItemDef> {
+  let mut defs = HashMap::new();
+
+  for item in &program.items {
+    for (name, public, span) in item_names(item) {
+      defs.entry(name).or_insert(ItemDef {
+        public: matches!(public, Public::Yes(_)),
+        span,
+      });
+    }
+  }
+
+  defs
+}
+
+fn item_names(item: &Item) -> Vec<(String, &Public, Span)> {
+  match &item.kind {
+    ItemKind::Ext(ext) => {
+      let name = &ext.prototype.name;
+
+      vec![(name.to_string(), &ext.public, name.span)]
+    }
+    ItemKind::Val(decl) => {
+      vec![(decl.pattern.to_string(), &decl.public, decl.pattern.span)]
+    }
+    ItemKind::Fun(fun) => {
+      let name = &fun.prototype.name;
+
+      vec![(name.to_string(), &fun.public, name.span)]
+    }
+    ItemKind::Data(data) => {
+      vec![(data.name.to_string(), &data.public, data.name.span)]
+    }
+    ItemKind::Enum(enumeration) => {
+      let name = &enumeration.name;
+
+      vec![(name.to_string(), &enumeration.public, name.span)]
+    }
+    ItemKind::Behavior(behavior) => {
+      let name = &behavior.name;
+
+      vec![(name.to_string(), &behavior.public, name.span)]
+    }
+    ItemKind::Impl(imp) => imp
+      .funs
+      .iter()
+      .map(|fun| {
+        let name = &fun.prototype.name;
+
+        (format!("{}::{name}", imp.ty), &fun.public, name.span)
+      })
+      .collect(),
+    _ => vec![],
+  }
+}
+
+// the item `name` of the source `module` is imported by the source `user` if
+// the user loads the module or loads a module that binds it with `pub bind`,
+// directly or through other `pub bind`
+pub fn is_imported(
+  program: &Program,
+  user: u32,
+  module: u32,
+  name: &str,
+) -> bool {
+  let mut visited = HashSet::from([user]);
+
+  imports(program, user)
+    .any(|import| exports(program, import, module, name, &mut visited))
+}
+
+fn exports(
+  program: &Program,
+  import: &Import,
+  module: u32,
+  name: &str,
+  visited: &mut HashSet<u32>,
+) -> bool {
+  if import.item.as_ref().is_some_and(|item| !names(item, name)) {
+    return false;
+  }
+
+  if import.source_id == module {
+    return true;
+  }
+
+  if !visited.insert(import.source_id) {
+    return false;
+  }
+
+  imports(program, import.source_id)
+    .filter(|bound| bound.public)
+    .any(|bound| exports(program, bound, module, name, visited))
+}
+
+fn imports(program: &Program, source_id: u32) -> impl Iterator<Item = &Import> {
+  program.imports.get(&source_id).into_iter().flatten()
+}
+
+// `load a::Foo` also imports the functions of `impl Foo`
+fn names(item: &str, name: &str) -> bool {
+  name == item
+    || name
+      .strip_prefix(item)
+      .is_some_and(|rest| rest.starts_with("::"))
+}
+
+// the name under which a module is loaded, the path of its file otherwise
+pub fn module_name(program: &Program, source_id: u32) -> String {
+  fn find(modules: &[Module], source_id: u32) -> Option<&Module> {
+    modules
+      .iter()
+      .find_map(|module| match module.source_id == source_id {
+        true => Some(module),
+        false => find(&module.modules, source_id),
+      })
+  }
+
+  match find(&program.modules, source_id) {
+    Some(module) => module.name.to_owned(),
+    None => {
+      let source = &program.reporter.source_map.sources[source_id as usize];
+
+      source.path.display().to_string()
+    }
+  }
+}
@@ -0,0 +1,587 @@
+use super::matcher::{match_trees, metavar_depths, parse_matchers, Matcher};
+
+use super::transcriber::{
+  declared_names, parse_transcribers, MismatchedRepetition, Transcriber,
+  Transcription,
+};
+
+use crate::front::lexer::Token;
+use crate::front::parser::ast::*;
+use crate::front::parser::parse_tokens;
+
+use crate::util::error::{
+  Help, HelpKind, Label, LabelKind, LabelMessage, Report, ReportCode,
+  ReportKind, ReportMessage, ReportOffset, Reporter,
+};
+
+use crate::util::span::Span;
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+// the number of calls that can be nested in the expansion of a call
+const RECURSION_LIMIT: usize = 64;
+
+struct MacroDef {
+  rules: Vec<Rule>,
+  // the name of the macro where it is defined
+  span: Span,
+}
+
+struct Rule {
+  matchers: Vec<Matcher>,
+  transcribers: Vec<Transcriber>,
+  declared_names: HashSet<String>,
+}
+
+// a macro is known by its name and the delimiter with which it is called
+type MacroKey = (String, Delimiter);
+
+struct Expander<'a> {
+  reporter: &'a Reporter,
+  // the macros defined by each enclosing item list or block
+  scopes: Vec<HashMap<MacroKey, Rc<MacroDef>>>,
+  // the number of expansions so far, it marks the names that they declare
+  expansions: usize,
+  depth: usize,
+}
+
+// replaces every macro call by its expansion and removes the definitions of
+// the macros, so that the analysis never sees them
+pub fn expand(program: &mut Program) {
+  let mut expander = Expander {
+    reporter: &program.reporter,
+    scopes: vec![],
+    expansions: 0,
+    depth: 0,
+  };
+
+  // the syntax errors are already reported, the analysis still runs on the
+  // items that were parsed unless the expansion itself went wrong
+  let errors = program.reporter.error_count();
+
+  expander.expand_items(&mut program.items);
+
+  if program.reporter.error_count() > errors {
+    program.reporter.abort();
+  }
+}
+
+impl<'a> Expander<'a> {
+  fn expand_items(&mut self, items: &mut Vec<PBox<Item>>) {
+    self.scopes.push(HashMap::new());
+
+    // a macro can be called before the place where it is defined
+    for item in items.iter() {
+      if let ItemKind::Macro(mac) = &item.kind {
+        self.define_macro(mac);
+      }
+    }
+
+    for mut item in std::mem::take(items) {
+      match &item.kind {
+        ItemKind::Macro(_) => {}
+        ItemKind::MacroCall(call) => items.extend(self.expand_call_items(call)),
+        _ => {
+          self.expand_item(&mut item);
+          items.push(item);
+        }
+      }
+    }
+
+    self.scopes.pop();
+  }
+
+  fn expand_item(&mut self, item: &mut Item) {
+    match &mut item.kind {
+      ItemKind::Ext(ext) => {
+        if let Some(body) = &mut ext.body {
+          self.expand_block(body);
+        }
+      }
+      ItemKind::Val(decl) => self.expand_expr(&mut decl.value),
+      ItemKind::Fun(fun) => self.expand_block(&mut fun.body),
+      ItemKind::Impl(imp) => {
+        for fun in &mut imp.funs {
+          self.expand_block(&mut fun.body);
+        }
+      }
+      ItemKind::Behavior(behavior) => {
+        for method in &mut behavior.methods {
+          if let Some(body) = &mut method.body {
+            self.expand_block(body);
+          }
+        }
+      }
+      ItemKind::Unit(unit) => {
+        for item in &mut unit.items {
+          let (UnitItem::Test(fun) | UnitItem::Mock(fun)) = item;
+
+          self.expand_block(&mut fun.body);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn expand_block(&mut self, block: &mut Block) {
+    self.scopes.push(HashMap::new());
+
+    for stmt in &block.stmts {
+      if let StmtKind::Item(item) = &stmt.kind {
+        if let ItemKind::Macro(mac) = &item.kind {
+          self.define_macro(mac);
+        }
+      }
+    }
+
+    for mut stmt in std::mem::take(&mut block.stmts) {
+      match &mut stmt.kind {
+        StmtKind::Item(item) if matches!(item.kind, ItemKind::Macro(_)) => {}
+        StmtKind::Item(item) => {
+          self.expand_item(item);
+          block.stmts.push(stmt);
+        }
+        StmtKind::Decl(decl) => {
+          self.expand_expr(&mut decl.value);
+          block.stmts.push(stmt);
+        }
+        StmtKind::Expr(expr) => match &expr.kind {
+          ExprKind::MacroCall(call) => {
+            block.stmts.extend(self.expand_call_stmts(call))
+          }
+          _ => {
+            self.expand_expr(expr);
+            block.stmts.push(stmt);
+          }
+        },
+      }
+    }
+
+    self.scopes.pop();
+  }
+
+  fn expand_expr(&mut self, expr: &mut Expr) {
+    match &mut expr.kind {
+      ExprKind::MacroCall(call) => *expr = self.expand_call_expr(call),
+      ExprKind::Call(callee, args) => {
+        self.expand_expr(callee);
+        self.expand_exprs(args);
+      }
+      ExprKind::UnOp(_, rhs) => self.expand_expr(rhs),
+      ExprKind::BinOp(lhs, _, rhs)
+      | ExprKind::Assign(lhs, _, rhs)
+      | ExprKind::AssignOp(lhs, _, rhs) => {
+        self.expand_expr(lhs);
+        self.expand_expr(rhs);
+      }
+      ExprKind::Return(Some(value)) | ExprKind::Break(Some(value)) => {
+        self.expand_expr(value)
+      }
+      ExprKind::Block(block) | ExprKind::Loop(block) => {
+        self.expand_block(block)
+      }
+      ExprKind::While(condition, block) => {
+        self.expand_expr(condition);
+        self.expand_block(block);
+      }
+      ExprKind::When(condition, consequence, alternative) => {
+        self.expand_expr(condition);
+        self.expand_expr(consequence);
+        self.expand_expr(alternative);
+      }
+      ExprKind::IfElse(condition, consequence, alternative) => {
+        self.expand_expr(condition);
+        self.expand_expr(consequence);
+
+        if let Some(alternative) = alternative {
+          self.expand_expr(alternative);
+        }
+      }
+      ExprKind::Field(lhs, _) | ExprKind::Is(lhs, _) | ExprKind::Try(lhs) => {
+        self.expand_expr(lhs)
+      }
+      ExprKind::Struct(_, fields) => {
+        for field in fields {
+          if let Some(value) = &mut field.value {
+            self.expand_expr(value);
+          }
+        }
+      }
+      ExprKind::Match(scrutinee, arms) => {
+        self.expand_expr(scrutinee);
+
+        for arm in arms {
+          self.expand_expr(&mut arm.body);
+        }
+      }
+      ExprKind::For(_, range, step, block) => {
+        self.expand_expr(range);
+
+        if let Some(step) = step {
+          self.expand_expr(step);
+        }
+
+        self.expand_block(block);
+      }
+      ExprKind::ForEach(iterable, block) => {
+        self.expand_expr(iterable);
+        self.expand_block(block);
+      }
+      ExprKind::Array(elements) => self.expand_exprs(elements),
+      ExprKind::Lambda(lambda) => self.expand_block(&mut lambda.body),
+      _ => {}
+    }
+  }
+
+  fn expand_exprs(&mut self, exprs: &mut [PBox<Expr>]) {
+    for expr in exprs {
+      self.expand_expr(expr);
+    }
+  }
+
+  // the expansion is parsed as a list of items
+  fn expand_call_items(&mut self, call: &MacroCall) -> Vec<PBox<Item>> {
+    let Some(tokens) = self.transcribe_call(call) else {
+      return vec![];
+    };
+    let Some((_, mut items)) = parse_tokens(self.reporter, tokens) else {
+      return vec![];
+    };
+
+    self.depth += 1;
+    self.expand_items(&mut items);
+    self.depth -= 1;
+
+    items
+  }
+
+  // the expansion is parsed as the body of a function
+  fn expand_call_stmts(&mut self, call: &MacroCall) -> Vec<PBox<Stmt>> {
+    let Some(mut tokens) = self.transcribe_call(call) else {
+      return vec![];
+    };
+    let (lo, hi) = (call.span.lo as usize, call.span.hi as usize);
+
+    tokens.splice(
+      0..0,
+      [
+        Token::Fun,
+        Token::Identifier("expansion".into()),
+        Token::OpenParen,
+        Token::CloseParen,
+        Token::OpenBrace,
+      ]
+      .map(|token| (lo, token, hi)),
+    );
+
+    tokens.push((lo, Token::CloseBrace, hi));
+
+    let Some((_, items)) = parse_tokens(self.reporter, tokens) else {
+      return vec![];
+    };
+
+    let Some(item) = items.into_iter().next() else {
+      return vec![];
+    };
+    let ItemKind::Fun(mut fun) = item.0.kind else {
+      unreachable!()
+    };
+
+    self.depth += 1;
+    self.expand_block(&mut fun.body);
+    self.depth -= 1;
+
+    std::mem::take(&mut fun.body.stmts)
+  }
+
+  // an expansion made of a single expression replaces the call, otherwise
+  // the statements are put in a block
+  fn expand_call_expr(&mut self, call: &MacroCall) -> Expr {
+    let mut stmts = self.expand_call_stmts(call);
+
+    if let [stmt] = stmts.as_slice() {
+      if matches!(stmt.kind, StmtKind::Expr(_)) {
+        let StmtKind::Expr(expr) = stmts.remove(0).0.kind else {
+          unreachable!()
+        };
+
+        return *expr.0;
+      }
+    }
+
+    let block = Block::new(stmts, call.span);
+
+    Expr::new(ExprKind::Block(pbox(block)), call.span)
+  }
+
+  // the tokens produced by the first rule that matches the call
+  fn transcribe_call(
+    &mut self,
+    call: &MacroCall,
+  ) -> Option<Vec<(usize, Token, usize)>> {
+    let name = call.name.to_string();
+
+    let Some(mac) = self.find_macro(&name, call.delimiter) else {
+      self.add_report_undefined_macro_error(&name, call);
+      return None;
+    };
+
+    if self.depth >= RECURSION_LIMIT {
+      self.raise_report_macro_recursion_error(&name, call.span);
+    }
+
+    let Some((rule, bindings)) = mac.rules.iter().find_map(|rule| {
+      match_trees(&rule.matchers, &call.tokens).map(|bindings| (rule, bindings))
+    }) else {
+      self.add_report_no_macro_rule_error(&name, call.span);
+      return None;
+    };
+
+    self.expansions += 1;
+
+    let transcription = Transcription {
+      span: call.span,
+      mark: self.expansions,
+      declared_names: &rule.declared_names,
+    };
+
+    let mut tokens = vec![];
+
+    match transcription.transcribe(&rule.transcribers, &bindings, &mut tokens) {
+      Ok(()) => {
+        self.reporter.add_expansion(call.span);
+        Some(tokens)
+      }
+      Err(MismatchedRepetition) => {
+        self.add_report_mismatched_repetition_error(call.span);
+        None
+      }
+    }
+  }
+
+  fn find_macro(
+    &self,
+    name: &str,
+    delimiter: Delimiter,
+  ) -> Option<Rc<MacroDef>> {
+    let key = (name.to_string(), delimiter);
+
+    self
+      .scopes
+      .iter()
+      .rev()
+      .find_map(|scope| scope.get(&key))
+      .cloned()
+  }
+
+  // a rule that cannot be read is reported and left out of the macro. a
+  // macro defined twice in the same scope is reported, the first one stays
+  fn define_macro(&mut self, mac: &Macro) {
+    let name = mac.name.to_string();
+    let key = (name.to_owned(), mac.delimiter);
+
+    if let Some(def) = self.scopes.last().unwrap().get(&key) {
+      let span = def.span;
+
+      return self.add_report_duplicate_macro_error(&name, mac.name.span, span);
+    }
+    let mut rules = vec![];
+
+    for rule in &mac.rules {
+      match parse_rule(rule) {
+        Ok(rule) => rules.push(rule),
+        Err((reason, span)) => {
+          self.add_report_invalid_macro_rule_error(&name, reason, span)
+        }
+      }
+    }
+
+    let scope = self.scopes.last_mut().unwrap();
+    let span = mac.name.span;
+
+    scope.insert(key, Rc::new(MacroDef { rules, span }));
+  }
+
+  fn add_report_duplicate_macro_error(
+    &self,
+    name: &str,
+    span: Span,
+    def_span: Span,
+  ) {
+    let source_id = self.reporter.source(span);
+    let code = self.reporter.code(source_id);
+    let path = self.reporter.path(span);
+
+    self.reporter.add_report(
+      Report::new(
+        ReportKind::Error,
+        path.display().to_string(),
+        ReportOffset(span.lo),
+      )
+      .with_code(ReportCode(3)) // FIXME #2
+      .with_message(ReportMessage::DuplicateItem(name.to_string()))
+      .with_label(
+        Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+          .with_message(LabelMessage::DuplicateDeclaration),
+      )
+      .with_label(
+        Label::new(
+          LabelKind::Note,
+          (path.display().to_string(), def_span.into()),
+        )
+        .with_message(LabelMessage::DefinedHere),
+      ),
+      path.display().to_string(),
+      code,
+    );
+  }
+
+  fn add_report_undefined_macro_error(&self, name: &str, call: &MacroCall) {
+    let source_id = self.reporter.source(call.span);
+    let code = self.reporter.code(source_id);
+    let path = self.reporter.path(call.span);
+
+    let report = Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(call.span.lo),
+    )
+    .with_code(ReportCode(6)) // FIXME #2
+    .with_message(ReportMessage::UndefinedMacro(name.to_string()))
+    .with_label(
+      Label::new(
+        LabelKind::Error,
+        (path.display().to_string(), call.name.span.into()),
+      )
+      .with_message(LabelMessage::UndefinedMacro),
+    );
+
+    // the macro may exist with another delimiter
+    let delimiter = [Delimiter::Paren, Delimiter::Brace, Delimiter::Bracket]
+      .into_iter()
+      .find(|delimiter| self.find_macro(name, *delimiter).is_some());
+
+    let report = match delimiter {
+      Some(delimiter) => report.with_help(Help::new(HelpKind::MacroDelimiter(
+        format!("#{name}{}...{}", delimiter.open(), delimiter.close()),
+      ))),
+      None => report,
+    };
+
+    self
+      .reporter
+      .add_report(report, path.display().to_string(), code);
+  }
+
+  fn add_report_no_macro_rule_error(&self, name: &str, span: Span) {
+    let source_id = self.reporter.source(span);
+    let code = self.reporter.code(source_id);
+    let path = self.reporter.path(span);
+
+    self.reporter.add_report(
+      Report::new(
+        ReportKind::Error,
+        path.display().to_string(),
+        ReportOffset(span.lo),
+      )
+      .with_code(ReportCode(6)) // FIXME #2
+      .with_message(ReportMessage::NoMacroRule(name.to_string()))
+      .with_label(
+        Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+          .with_message(LabelMessage::NoMacroRule),
+      ),
+      path.display().to_string(),
+      code,
+    );
+  }
+
+  fn add_report_mismatched_repetition_error(&self, span: Span) {
+    let source_id = self.reporter.source(span);
+    let code = self.reporter.code(source_id);
+    let path = self.reporter.path(span);
+
+    self.reporter.add_report(
+      Report::new(
+        ReportKind::Error,
+        path.display().to_string(),
+        ReportOffset(span.lo),
+      )
+      .with_code(ReportCode(6)) // FIXME #2
+      .with_message(ReportMessage::MismatchedRepetition)
+      .with_label(
+        Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+          .with_message(LabelMessage::MismatchedRepetition),
+      ),
+      path.display().to_string(),
+      code,
+    );
+  }
+
+  fn add_report_invalid_macro_rule_error(
+    &self,
+    name: &str,
+    reason: String,
+    span: Span,
+  ) {
+    let source_id = self.reporter.source(span);
+    let code = self.reporter.code(source_id);
+    let path = self.reporter.path(span);
+
+    self.reporter.add_report(
+      Report::new(
+        ReportKind::Error,
+        path.display().to_string(),
+        ReportOffset(span.lo),
+      )
+      .with_code(ReportCode(6)) // FIXME #2
+      .with_message(ReportMessage::InvalidMacroRule(name.to_string()))
+      .with_label(
+        Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+          .with_message(LabelMessage::InvalidMacroRule(reason)),
+      ),
+      path.display().to_string(),
+      code,
+    );
+  }
+
+  fn raise_report_macro_recursion_error(&self, name: &str, span: Span) -> ! {
+    let source_id = self.reporter.source(span);
+    let code = self.reporter.code(source_id);
+    let path = self.reporter.path(span);
+
+    self.reporter.raise(
+      Report::new(
+        ReportKind::Error,
+        path.display().to_string(),
+        ReportOffset(span.lo),
+      )
+      .with_code(ReportCode(6)) // FIXME #2
+      .with_message(ReportMessage::MacroRecursion(name.to_string()))
+      .with_label(
+        Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+          .with_message(LabelMessage::MacroRecursion),
+      ),
+      path.display().to_string(),
+      code,
+    )
+  }
+}
+
+fn parse_rule(rule: &MacroRule) -> Result<Rule, (String, Span)> {
+  let matchers = parse_matchers(&rule.matcher)?;
+  let mut depths = HashMap::new();
+
+  metavar_depths(&matchers, 0, &mut depths);
+
+  let transcribers = parse_transcribers(&rule.transcriber, &depths, 0)?;
+  let mut names = HashSet::new();
+
+  declared_names(&rule.transcriber, &mut names);
+
+  Ok(Rule {
+    matchers,
+    transcribers,
+    declared_names: names,
+  })
+}
@@ -0,0 +1,491 @@
+use crate::front::lexer::Token;
+use crate::front::parser::ast::{Delimiter, TokenTree};
+use crate::util::span::Span;
+
+use std::collections::HashMap;
+
+// the kind of tokens that a metavariable `$name:kind` matches
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fragment {
+  Block,
+  Expr,
+  Ident,
+  Lit,
+  Tt,
+  Ty,
+}
+
+impl Fragment {
+  pub const NAMES: [&'static str; 6] =
+    ["block", "expr", "ident", "lit", "tt", "ty"];
+
+  fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "block" => Some(Self::Block),
+      "expr" => Some(Self::Expr),
+      "ident" => Some(Self::Ident),
+      "lit" => Some(Self::Lit),
+      "tt" => Some(Self::Tt),
+      "ty" => Some(Self::Ty),
+      _ => None,
+    }
+  }
+
+  // the number of trees that the fragment could take from the front of
+  // `trees`, from the longest to the shortest
+  fn lens(&self, trees: &[TokenTree]) -> Vec<usize> {
+    match self {
+      Self::Block => match trees.first() {
+        Some(TokenTree::Delimited(Delimiter::Brace, ..)) => vec![1],
+        _ => vec![],
+      },
+      Self::Expr => (1..=end_of(trees, &[])).rev().collect(),
+      Self::Ident => match trees.first() {
+        Some(TokenTree::Token(Token::Identifier(_), _)) => vec![1],
+        _ => vec![],
+      },
+      Self::Lit => match trees {
+        [TokenTree::Token(Token::Sub, _), TokenTree::Token(Token::Int(_) | Token::Float(_), _), ..] =>
+        {
+          vec![2]
+        }
+        [TokenTree::Token(token, _), ..] if is_lit(token) => vec![1],
+        _ => vec![],
+      },
+      Self::Tt => match trees.is_empty() {
+        true => vec![],
+        false => vec![1],
+      },
+      Self::Ty => (1..=end_of(trees, &[Token::Eq])).rev().collect(),
+    }
+  }
+}
+
+// an expression or a type never spans over a comma, a semicolon or an arrow
+// that is not nested in a delimiter
+fn end_of(trees: &[TokenTree], stops: &[Token]) -> usize {
+  trees
+    .iter()
+    .position(|tree| match tree {
+      TokenTree::Token(token, _) => {
+        matches!(token, Token::Comma | Token::Semicolon | Token::FatArrow)
+          || stops.contains(token)
+      }
+      TokenTree::Delimited(..) => false,
+    })
+    .unwrap_or(trees.len())
+}
+
+fn is_lit(token: &Token) -> bool {
+  matches!(
+    token,
+    Token::Int(_)
+      | Token::Float(_)
+      | Token::Char(_)
+      | Token::Str(_)
+      | Token::RawStr(_)
+      | Token::ByteStr(_)
+      | Token::True
+      | Token::False
+  )
+}
+
+// how many times the content of `$( ... )` is repeated
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RepetitionOp {
+  // `*`
+  ZeroOrMore,
+  // `+`
+  OneOrMore,
+  // `?`
+  ZeroOrOne,
+}
+
+impl RepetitionOp {
+  fn from_token(token: &Token) -> Option<Self> {
+    match token {
+      Token::Mul => Some(Self::ZeroOrMore),
+      Token::Add => Some(Self::OneOrMore),
+      Token::Question => Some(Self::ZeroOrOne),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug)]
+pub enum Matcher {
+  Token(Token),
+  Delimited(Delimiter, Vec<Matcher>),
+  Metavar(String, Fragment),
+  Repetition(Vec<Matcher>, Option<Token>, RepetitionOp),
+}
+
+// the trees bound to a metavariable, it is bound once per iteration of each
+// repetition in which it is written
+#[derive(Clone, Debug)]
+pub enum Binding {
+  One(Fragment, Vec<TokenTree>),
+  Many(Vec<Binding>),
+}
+
+pub type Bindings = HashMap<String, Binding>;
+
+// the reason why a rule is rejected and where
+pub type RuleError = (String, Span);
+
+pub fn parse_matchers(trees: &[TokenTree]) -> Result<Vec<Matcher>, RuleError> {
+  let mut matchers = vec![];
+  let mut index = 0;
+
+  while index < trees.len() {
+    let (matcher, len) = match &trees[index] {
+      TokenTree::Token(Token::Dollar, span) => {
+        parse_matcher_dollar(&trees[index + 1..], *span)?
+      }
+      TokenTree::Token(token, _) => (Matcher::Token(token.clone()), 1),
+      TokenTree::Delimited(delimiter, trees, _) => {
+        (Matcher::Delimited(*delimiter, parse_matchers(trees)?), 1)
+      }
+    };
+
+    matchers.push(matcher);
+    index += len;
+  }
+
+  Ok(matchers)
+}
+
+// `$name:kind` or `$( ... ) sep op`, the returned length counts the `$`
+fn parse_matcher_dollar(
+  trees: &[TokenTree],
+  span: Span,
+) -> Result<(Matcher, usize), RuleError> {
+  match trees {
+    [TokenTree::Token(Token::Identifier(name), _), TokenTree::Token(Token::Colon, _), TokenTree::Token(Token::Identifier(kind), kind_span), ..] =>
+    {
+      let Some(fragment) = Fragment::from_name(kind) else {
+        return Err((
+          format!(
+            "`{kind}` is not a fragment, expected one of {}",
+            Fragment::NAMES.join(", ")
+          ),
+          *kind_span,
+        ));
+      };
+
+      Ok((Matcher::Metavar(name.to_string(), fragment), 4))
+    }
+    [TokenTree::Token(Token::Identifier(name), _), ..] => Err((
+      format!("the fragment of `${name}` is missing: `${name}:expr`"),
+      span,
+    )),
+    [TokenTree::Delimited(Delimiter::Paren, inner, _), rest @ ..] => {
+      let matchers = parse_matchers(inner)?;
+      let (separator, op, len) = parse_repetition(rest, span)?;
+
+      Ok((Matcher::Repetition(matchers, separator, op), len + 2))
+    }
+    _ => Err(("`$` starts a metavariable or a repetition".into(), span)),
+  }
+}
+
+// the separator and the operator that follow `$( ... )`
+pub fn parse_repetition(
+  trees: &[TokenTree],
+  span: Span,
+) -> Result<(Option<Token>, RepetitionOp, usize), RuleError> {
+  let op = |tree: &TokenTree| match tree {
+    TokenTree::Token(token, _) => RepetitionOp::from_token(token),
+    TokenTree::Delimited(..) => None,
+  };
+
+  match trees {
+    [first, ..] if op(first).is_some() => Ok((None, op(first).unwrap(), 1)),
+    [TokenTree::Token(separator, _), second, ..] if op(second).is_some() => {
+      Ok((Some(separator.clone()), op(second).unwrap(), 2))
+    }
+    _ => Err(("a repetition ends with `*`, `+` or `?`".into(), span)),
+  }
+}
+
+// the depth of each metavariable, that is the number of repetitions in which
+// it is written
+pub fn metavar_depths(
+  matchers: &[Matcher],
+  depth: usize,
+  depths: &mut HashMap<String, usize>,
+) {
+  for matcher in matchers {
+    match matcher {
+      Matcher::Token(_) => {}
+      Matcher::Delimited(_, matchers) => {
+        metavar_depths(matchers, depth, depths)
+      }
+      Matcher::Metavar(name, _) => {
+        depths.insert(name.to_string(), depth);
+      }
+      Matcher::Repetition(matchers, ..) => {
+        metavar_depths(matchers, depth + 1, depths)
+      }
+    }
+  }
+}
+
+// every tree has to be matched, the fragments and the repetitions take as
+// many trees as they can as long as the rest still matches
+pub fn match_trees(
+  matchers: &[Matcher],
+  trees: &[TokenTree],
+) -> Option<Bindings> {
+  let Some((matcher, matchers)) = matchers.split_first() else {
+    return trees.is_empty().then(Bindings::new);
+  };
+
+  match matcher {
+    Matcher::Token(token) => match trees.split_first() {
+      Some((TokenTree::Token(tree, _), trees)) if tree == token => {
+        match_trees(matchers, trees)
+      }
+      _ => None,
+    },
+    Matcher::Delimited(delimiter, inner) => match trees.split_first() {
+      Some((TokenTree::Delimited(tree, inner_trees, _), trees))
+        if tree == delimiter =>
+      {
+        let mut bindings = match_trees(inner, inner_trees)?;
+
+        bindings.extend(match_trees(matchers, trees)?);
+        Some(bindings)
+      }
+      _ => None,
+    },
+    Matcher::Metavar(name, fragment) => {
+      fragment.lens(trees).into_iter().find_map(|len| {
+        let mut bindings = match_trees(matchers, &trees[len..])?;
+
+        bindings.insert(
+          name.to_string(),
+          Binding::One(*fragment, trees[..len].to_vec()),
+        );
+
+        Some(bindings)
+      })
+    }
+    Matcher::Repetition(inner, separator, op) => {
+      let repetition = Repetition {
+        matchers: inner,
+        separator,
+        op: *op,
+      };
+
+      repetition.match_trees(matchers, trees, vec![])
+    }
+  }
+}
+
+struct Repetition<'a> {
+  matchers: &'a [Matcher],
+  separator: &'a Option<Token>,
+  op: RepetitionOp,
+}
+
+impl<'a> Repetition<'a> {
+  // tries one more iteration before matching what follows the repetition
+  fn match_trees(
+    &self,
+    rest: &[Matcher],
+    trees: &[TokenTree],
+    iterations: Vec<Bindings>,
+  ) -> Option<Bindings> {
+    let count = iterations.len();
+
+    let start = match (count, self.separator) {
+      (0, _) | (_, None) => Some(0),
+      (_, Some(separator)) => match trees.first() {
+        Some(TokenTree::Token(token, _)) if token == separator => Some(1),
+        _ => None,
+      },
+    };
+
+    let can_repeat = !(self.op == RepetitionOp::ZeroOrOne && count == 1);
+
+    if let Some(start) = start.filter(|_| can_repeat) {
+      for end in (start + 1..=trees.len()).rev() {
+        let Some(bindings) = match_trees(self.matchers, &trees[start..end])
+        else {
+          continue;
+        };
+
+        let mut iterations = iterations.clone();
+
+        iterations.push(bindings);
+
+        if let Some(bindings) =
+          self.match_trees(rest, &trees[end..], iterations)
+        {
+          return Some(bindings);
+        }
+      }
+    }
+
+    if self.op == RepetitionOp::OneOrMore && count == 0 {
+      return None;
+    }
+
+    let mut bindings = match_trees(rest, trees)?;
+    let mut depths = HashMap::new();
+
+    metavar_depths(self.matchers, 0, &mut depths);
+
+    for name in depths.into_keys() {
+      let binding = iterations
+        .iter()
+        .map(|iteration| iteration[&name].clone())
+        .collect();
+
+      bindings.insert(name, Binding::Many(binding));
+    }
+
+    Some(bindings)
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+
+  use crate::front::lexer::Lexer;
+
+  // the token trees of `source`, nested as the parser nests them
+  pub fn trees(source: &str) -> Vec<TokenTree> {
+    let mut stack = vec![(None, vec![], 0)];
+
+    for spanned in Lexer::new(source) {
+      let (lo, token, hi) = spanned.unwrap();
+      let delimiter = match token {
+        Token::OpenParen | Token::CloseParen => Some(Delimiter::Paren),
+        Token::OpenBrace | Token::CloseBrace => Some(Delimiter::Brace),
+        Token::OpenBracket | Token::CloseBracket => Some(Delimiter::Bracket),
+        _ => None,
+      };
+
+      match delimiter {
+        Some(delimiter) if token == delimiter.open() => {
+          stack.push((Some(delimiter), vec![], lo))
+        }
+        Some(_) => {
+          let (delimiter, trees, lo) = stack.pop().unwrap();
+          let tree =
+            TokenTree::Delimited(delimiter.unwrap(), trees, Span::new(lo, hi));
+
+          stack.last_mut().unwrap().1.push(tree);
+        }
+        None => {
+          let tree = TokenTree::Token(token, Span::new(lo, hi));
+
+          stack.last_mut().unwrap().1.push(tree);
+        }
+      }
+    }
+
+    stack.pop().unwrap().1
+  }
+
+  fn bind(matcher: &str, call: &str) -> Option<Bindings> {
+    let matchers = parse_matchers(&trees(matcher)).unwrap();
+
+    match_trees(&matchers, &trees(call))
+  }
+
+  fn source(binding: &Binding) -> String {
+    match binding {
+      Binding::One(_, trees) => trees
+        .iter()
+        .map(|tree| match tree {
+          TokenTree::Token(token, _) => token.to_string(),
+          TokenTree::Delimited(delimiter, trees, _) => format!(
+            "{}{}{}",
+            delimiter.open(),
+            source(&Binding::One(Fragment::Tt, trees.to_vec())),
+            delimiter.close()
+          ),
+        })
+        .collect::<Vec<_>>()
+        .join(" "),
+      Binding::Many(bindings) => {
+        let sources = bindings.iter().map(source).collect::<Vec<_>>();
+
+        format!("[{}]", sources.join(", "))
+      }
+    }
+  }
+
+  #[test]
+  fn repetitions_take_their_separators() {
+    let bindings = bind("$($x:expr),*", "1, 2 + 3, f(4)").unwrap();
+
+    assert_eq!(source(&bindings["x"]), "[1, 2 + 3, f (4)]");
+    assert_eq!(source(&bind("$($x:expr),*", "").unwrap()["x"]), "[]");
+    assert!(bind("$($x:expr),*", "1, 2,").is_none());
+  }
+
+  #[test]
+  fn repetition_operators_bound_the_iterations() {
+    assert!(bind("$($x:ident)+", "").is_none());
+    assert_eq!(source(&bind("$($x:ident)+", "a b").unwrap()["x"]), "[a, b]");
+    assert_eq!(source(&bind("$($x:ident)?", "").unwrap()["x"]), "[]");
+    assert_eq!(source(&bind("$($x:ident)?", "a").unwrap()["x"]), "[a]");
+    assert!(bind("$($x:ident)?", "a b").is_none());
+  }
+
+  #[test]
+  fn nested_repetitions_are_bound_per_iteration() {
+    let bindings = bind("$([$($x:lit);*])*", "[1; 2] [] [3]").unwrap();
+
+    assert_eq!(source(&bindings["x"]), "[[1, 2], [], [3]]");
+  }
+
+  #[test]
+  fn fragments_match_their_kind() {
+    let bindings = bind(
+      "$a:ident $b:lit $c:block $d:tt $e:ty = $f:expr",
+      "foo -1 { bar } (baz) Fn(s32) -> s32 = 1 + 2",
+    )
+    .unwrap();
+
+    assert_eq!(source(&bindings["a"]), "foo");
+    assert_eq!(source(&bindings["b"]), "- 1");
+    assert_eq!(source(&bindings["c"]), "{bar}");
+    assert_eq!(source(&bindings["d"]), "(baz)");
+    assert_eq!(source(&bindings["e"]), "Fn (s32) -> s32");
+    assert_eq!(source(&bindings["f"]), "1 + 2");
+
+    assert!(bind("$a:ident", "1").is_none());
+    assert!(bind("$a:lit", "foo").is_none());
+    assert!(bind("$a:block", "(foo)").is_none());
+    assert!(bind("$a:tt", "foo bar").is_none());
+  }
+
+  #[test]
+  fn an_expression_stops_at_a_comma() {
+    let bindings = bind("$a:expr, $b:expr", "f(1, 2), 3").unwrap();
+
+    assert_eq!(source(&bindings["a"]), "f (1 , 2)");
+    assert_eq!(source(&bindings["b"]), "3");
+  }
+
+  #[test]
+  fn malformed_matchers_are_rejected() {
+    let error = |matcher| parse_matchers(&trees(matcher)).unwrap_err().0;
+
+    assert_eq!(
+      error("$x:foo"),
+      "`foo` is not a fragment, expected one of block, expr, ident, lit, tt, ty"
+    );
+    assert_eq!(error("$x"), "the fragment of `$x` is missing: `$x:expr`");
+    assert_eq!(
+      error("$($x:expr)"),
+      "a repetition ends with `*`, `+` or `?`"
+    );
+  }
+}
@@ -0,0 +1,6 @@
+mod expansion;
+mod matcher;
+mod transcriber;
+
+pub use expansion::expand;
+pub use transcriber::unmarked;
@@ -0,0 +1,443 @@
+use super::matcher::{
+  parse_repetition, Binding, Bindings, Fragment, RuleError,
+};
+
+use crate::front::lexer::Token;
+use crate::front::parser::ast::{Delimiter, TokenTree};
+use crate::util::span::Span;
+
+use std::collections::{HashMap, HashSet};
+
+// the names declared by a macro are renamed with this mark and the number of
+// the expansion, so they do not clash with the names of the call site. the
+// renamed names are still identifiers, the expanded program can be read again
+const HYGIENE_MARK: &str = "__";
+
+// the name as it was written in the macro
+pub fn unmarked(name: &str) -> &str {
+  match name.rsplit_once(HYGIENE_MARK) {
+    Some((unmarked, mark))
+      if !mark.is_empty() && mark.bytes().all(|byte| byte.is_ascii_digit()) =>
+    {
+      unmarked
+    }
+    _ => name,
+  }
+}
+
+#[derive(Debug)]
+pub enum Transcriber {
+  Token(Token),
+  Delimited(Delimiter, Vec<Transcriber>),
+  Metavar(String),
+  // the operator only matters to the matcher
+  Repetition(Vec<Transcriber>, Option<Token>),
+}
+
+// a metavariable is written in at least as many repetitions as in the
+// matcher, and every repetition repeats at least one metavariable
+pub fn parse_transcribers(
+  trees: &[TokenTree],
+  depths: &HashMap<String, usize>,
+  depth: usize,
+) -> Result<Vec<Transcriber>, RuleError> {
+  let mut transcribers = vec![];
+  let mut index = 0;
+
+  while index < trees.len() {
+    let (transcriber, len) = match &trees[index..] {
+      [TokenTree::Token(Token::Dollar, span), TokenTree::Token(Token::Identifier(name), name_span), ..] =>
+      {
+        let span = Span::merge(span, name_span);
+
+        match depths.get(name) {
+          Some(metavar_depth) if *metavar_depth <= depth => {
+            (Transcriber::Metavar(name.to_string()), 2)
+          }
+          Some(_) => {
+            return Err((format!("`${name}` is still repeating here"), span))
+          }
+          None => {
+            return Err((
+              format!("`${name}` is not bound by the matcher of this rule"),
+              span,
+            ))
+          }
+        }
+      }
+      [TokenTree::Token(Token::Dollar, span), TokenTree::Delimited(Delimiter::Paren, inner, _), rest @ ..] =>
+      {
+        if !repeats(inner, depths, depth + 1) {
+          return Err((
+            "this repetition does not repeat any metavariable".into(),
+            *span,
+          ));
+        }
+
+        let transcribers = parse_transcribers(inner, depths, depth + 1)?;
+        let (separator, _, len) = parse_repetition(rest, *span)?;
+
+        (Transcriber::Repetition(transcribers, separator), len + 2)
+      }
+      [TokenTree::Token(token, _), ..] => {
+        (Transcriber::Token(token.clone()), 1)
+      }
+      [TokenTree::Delimited(delimiter, trees, _), ..] => (
+        Transcriber::Delimited(
+          *delimiter,
+          parse_transcribers(trees, depths, depth)?,
+        ),
+        1,
+      ),
+      [] => unreachable!(),
+    };
+
+    transcribers.push(transcriber);
+    index += len;
+  }
+
+  Ok(transcribers)
+}
+
+fn repeats(
+  trees: &[TokenTree],
+  depths: &HashMap<String, usize>,
+  depth: usize,
+) -> bool {
+  trees.windows(2).any(|pair| match pair {
+    [TokenTree::Token(Token::Dollar, _), TokenTree::Token(Token::Identifier(name), _)] => {
+      depths.get(name).is_some_and(|metavar_depth| *metavar_depth >= depth)
+    }
+    _ => false,
+  }) || trees.iter().any(|tree| match tree {
+    TokenTree::Delimited(_, trees, _) => repeats(trees, depths, depth),
+    TokenTree::Token(..) => false,
+  })
+}
+
+// the names that the macro itself declares with `imu`, `mut`, `for` or as
+// the inputs of a lambda
+pub fn declared_names(trees: &[TokenTree], names: &mut HashSet<String>) {
+  for (index, tree) in trees.iter().enumerate() {
+    let next = trees.get(index + 1);
+
+    match (tree, next) {
+      (
+        TokenTree::Token(Token::Imu | Token::Mut | Token::For, _),
+        Some(TokenTree::Token(Token::Identifier(name), _)),
+      ) => {
+        names.insert(name.to_string());
+      }
+      (
+        TokenTree::Token(Token::Fn, _),
+        Some(TokenTree::Delimited(Delimiter::Paren, inputs, _)),
+      ) => {
+        let inputs = inputs
+          .split(|tree| matches!(tree, TokenTree::Token(Token::Comma, _)));
+
+        for input in inputs {
+          if let [TokenTree::Token(Token::Identifier(name), _), ..] = input {
+            names.insert(name.to_string());
+          }
+        }
+      }
+      (TokenTree::Delimited(_, trees, _), _) => declared_names(trees, names),
+      _ => {}
+    }
+  }
+}
+
+pub struct Transcription<'a> {
+  // the tokens written in the macro point at the call site
+  pub span: Span,
+  pub mark: usize,
+  pub declared_names: &'a HashSet<String>,
+}
+
+// the repetitions of the call do not line up with the ones of the rule
+pub struct MismatchedRepetition;
+
+impl<'a> Transcription<'a> {
+  pub fn transcribe(
+    &self,
+    transcribers: &[Transcriber],
+    bindings: &Bindings,
+    tokens: &mut Vec<(usize, Token, usize)>,
+  ) -> Result<(), MismatchedRepetition> {
+    for transcriber in transcribers {
+      match transcriber {
+        Transcriber::Token(token) => self.push(token.clone(), tokens),
+        Transcriber::Delimited(delimiter, transcribers) => {
+          self.push(delimiter.open(), tokens);
+          self.transcribe(transcribers, bindings, tokens)?;
+          self.push(delimiter.close(), tokens);
+        }
+        Transcriber::Metavar(name) => match &bindings[name] {
+          Binding::One(fragment, trees) => {
+            transcribe_fragment(*fragment, trees, tokens)
+          }
+          Binding::Many(_) => unreachable!(),
+        },
+        Transcriber::Repetition(transcribers, separator) => {
+          let iterations = iterations(transcribers, bindings)?;
+
+          for (index, bindings) in iterations.iter().enumerate() {
+            if let (Some(separator), true) = (separator, index > 0) {
+              self.push(separator.clone(), tokens);
+            }
+
+            self.transcribe(transcribers, bindings, tokens)?;
+          }
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  fn push(&self, token: Token, tokens: &mut Vec<(usize, Token, usize)>) {
+    let token = match token {
+      Token::Identifier(name) if self.declared_names.contains(&name) => {
+        Token::Identifier(format!("{name}{HYGIENE_MARK}{}", self.mark))
+      }
+      token => token,
+    };
+
+    tokens.push((self.span.lo as usize, token, self.span.hi as usize));
+  }
+}
+
+// the bindings of each iteration, the metavariables repeated by the
+// repetition have to be bound the same number of times
+fn iterations(
+  transcribers: &[Transcriber],
+  bindings: &Bindings,
+) -> Result<Vec<Bindings>, MismatchedRepetition> {
+  let mut names = vec![];
+
+  metavars(transcribers, &mut names);
+
+  let repeated = names
+    .iter()
+    .filter_map(|name| match &bindings[name] {
+      Binding::Many(iterations) => Some((name, iterations)),
+      Binding::One(..) => None,
+    })
+    .collect::<Vec<_>>();
+
+  let count = repeated
+    .first()
+    .map_or(0, |(_, iterations)| iterations.len());
+
+  if repeated
+    .iter()
+    .any(|(_, iterations)| iterations.len() != count)
+  {
+    return Err(MismatchedRepetition);
+  }
+
+  let iterations = (0..count)
+    .map(|index| {
+      let mut iteration = bindings.clone();
+
+      for (name, iterations) in &repeated {
+        iteration.insert(name.to_string(), iterations[index].clone());
+      }
+
+      iteration
+    })
+    .collect();
+
+  Ok(iterations)
+}
+
+fn metavars(transcribers: &[Transcriber], names: &mut Vec<String>) {
+  for transcriber in transcribers {
+    match transcriber {
+      Transcriber::Token(_) => {}
+      Transcriber::Delimited(_, transcribers)
+      | Transcriber::Repetition(transcribers, ..) => {
+        metavars(transcribers, names)
+      }
+      Transcriber::Metavar(name) => names.push(name.to_string()),
+    }
+  }
+}
+
+// the tokens given to the macro keep their spans, an expression is put in
+// parentheses so that it stays whole whatever surrounds it
+fn transcribe_fragment(
+  fragment: Fragment,
+  trees: &[TokenTree],
+  tokens: &mut Vec<(usize, Token, usize)>,
+) {
+  let is_grouped = fragment == Fragment::Expr && trees.len() > 1;
+  let span = trees
+    .iter()
+    .map(TokenTree::span)
+    .reduce(|lhs, rhs| Span::merge(&lhs, &rhs))
+    .unwrap_or(Span::ZERO);
+
+  if is_grouped {
+    tokens.push((span.lo as usize, Token::OpenParen, span.lo as usize));
+  }
+
+  flatten(trees, tokens);
+
+  if is_grouped {
+    tokens.push((span.hi as usize, Token::CloseParen, span.hi as usize));
+  }
+}
+
+fn flatten(trees: &[TokenTree], tokens: &mut Vec<(usize, Token, usize)>) {
+  for tree in trees {
+    match tree {
+      TokenTree::Token(token, span) => {
+        tokens.push((span.lo as usize, token.clone(), span.hi as usize))
+      }
+      TokenTree::Delimited(delimiter, trees, span) => {
+        tokens.push((span.lo as usize, delimiter.open(), span.lo as usize + 1));
+        flatten(trees, tokens);
+        tokens.push((
+          span.hi as usize - 1,
+          delimiter.close(),
+          span.hi as usize,
+        ));
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::front::expander::matcher::tests::trees;
+  use crate::front::expander::matcher::{
+    match_trees, metavar_depths, parse_matchers,
+  };
+
+  // the source of `transcriber` once `matcher` has matched `call`
+  fn expand(
+    matcher: &str,
+    transcriber: &str,
+    call: &str,
+  ) -> Result<String, MismatchedRepetition> {
+    let matchers = parse_matchers(&trees(matcher)).unwrap();
+    let mut depths = HashMap::new();
+
+    metavar_depths(&matchers, 0, &mut depths);
+
+    let body = trees(transcriber);
+    let transcribers = parse_transcribers(&body, &depths, 0).unwrap();
+    let bindings = match_trees(&matchers, &trees(call)).unwrap();
+    let mut declared = HashSet::new();
+
+    declared_names(&body, &mut declared);
+
+    let transcription = Transcription {
+      span: Span::ZERO,
+      mark: 7,
+      declared_names: &declared,
+    };
+
+    let mut tokens = vec![];
+
+    transcription.transcribe(&transcribers, &bindings, &mut tokens)?;
+
+    Ok(
+      tokens
+        .iter()
+        .map(|(_, token, _)| token.to_string())
+        .collect::<Vec<_>>()
+        .join(" "),
+    )
+  }
+
+  fn error(matcher: &str, transcriber: &str) -> String {
+    let matchers = parse_matchers(&trees(matcher)).unwrap();
+    let mut depths = HashMap::new();
+
+    metavar_depths(&matchers, 0, &mut depths);
+
+    parse_transcribers(&trees(transcriber), &depths, 0)
+      .unwrap_err()
+      .0
+  }
+
+  #[test]
+  fn repetitions_are_transcribed_with_their_separators() {
+    let source =
+      expand("$($x:ident = $v:lit),*", "$($x + $v)-*", "a = 1, b = 2");
+
+    assert_eq!(source.ok().unwrap(), "a + 1 - b + 2");
+  }
+
+  #[test]
+  fn a_metavariable_outside_its_repetition_is_repeated_along() {
+    let source = expand("$f:ident $($x:lit)*", "$($f($x);)*", "g 1 2");
+
+    assert_eq!(source.ok().unwrap(), "g ( 1 ) ; g ( 2 ) ;");
+  }
+
+  #[test]
+  fn an_expression_stays_whole() {
+    let source = expand("$e:expr", "$e * 2", "1 + 2");
+
+    assert_eq!(source.ok().unwrap(), "( 1 + 2 ) * 2");
+    assert_eq!(expand("$e:expr", "$e * 2", "3").ok().unwrap(), "3 * 2");
+  }
+
+  #[test]
+  fn repetitions_of_different_lengths_do_not_line_up() {
+    let source =
+      expand("[$($x:lit)*] [$($y:lit)*]", "$($x + $y)*", "[1 2] [3]");
+
+    assert!(source.is_err());
+  }
+
+  #[test]
+  fn declared_names_are_marked() {
+    let source = expand(
+      "$e:expr",
+      "{ imu x := $e; mut y := fn(z, w) { z + w + x }; y(x, tmp) }",
+      "tmp",
+    );
+
+    assert_eq!(
+      source.ok().unwrap(),
+      "{ imu x__7 := tmp ; mut y__7 := fn ( z__7 , w__7 ) \
+       { z__7 + w__7 + x__7 } ; y__7 ( x__7 , tmp ) }"
+    );
+  }
+
+  #[test]
+  fn the_names_of_the_call_site_are_not_marked() {
+    let source = expand("$x:ident", "{ imu x := 1; $x + x }", "x");
+
+    assert_eq!(source.ok().unwrap(), "{ imu x__7 := 1 ; x + x__7 }");
+  }
+
+  #[test]
+  fn unmarked_names_are_the_written_ones() {
+    assert_eq!(unmarked("x__7"), "x");
+    assert_eq!(unmarked("x__12"), "x");
+    assert_eq!(unmarked("my__name__3"), "my__name");
+    assert_eq!(unmarked("x__"), "x__");
+    assert_eq!(unmarked("x__y"), "x__y");
+    assert_eq!(unmarked("x"), "x");
+  }
+
+  #[test]
+  fn malformed_transcribers_are_rejected() {
+    assert_eq!(
+      error("$x:expr", "$y"),
+      "`$y` is not bound by the matcher of this rule"
+    );
+    assert_eq!(error("$($x:expr)*", "$x"), "`$x` is still repeating here");
+    assert_eq!(
+      error("$x:expr", "$(1)*"),
+      "this repetition does not repeat any metavariable"
+    );
+  }
+}
@@ -1,7 +1,11 @@
 // allow internal warnings related to the implementation of lalrpop
 #![allow(clippy::clone_on_copy)]
 #![allow(clippy::just_underscores_and_digits)]
+#![allow(clippy::let_unit_value)]
 #![allow(clippy::needless_lifetimes)]
+#![allow(clippy::ptr_arg)]
+#![allow(clippy::too_many_arguments)]
+#![allow(clippy::type_complexity)]
 #![allow(clippy::unused_unit)]
 
 lalrpop_mod!(grammar, "/front/grammar/grammar.rs");
@@ -0,0 +1,5 @@
+mod token;
+mod tokenizer;
+
+pub use token::{Suffixed, Token};
+pub use tokenizer::{Lexer, LexicalError, LexicalErrorKind, Spanned};
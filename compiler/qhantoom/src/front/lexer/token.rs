@@ -0,0 +1,278 @@
+use crate::front::parser::ast::TyKind;
+
+use std::fmt::{Display, Formatter, Result};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suffixed<T> {
+  pub value: T,
+  pub suffix: Option<TyKind>,
+}
+
+impl<T> Suffixed<T> {
+  pub fn new(value: T, suffix: Option<TyKind>) -> Self {
+    Self { value, suffix }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+  // literals
+  Int(Suffixed<i64>),
+  Float(Suffixed<f64>),
+  Char(char),
+  Str(String),
+  RawStr(String),
+  ByteStr(Vec<u8>),
+  Identifier(String),
+
+  // keywords
+  Abstract,
+  As,
+  Behavior,
+  Bind,
+  Break,
+  Continue,
+  Data,
+  Each,
+  Else,
+  Enum,
+  Ext,
+  False,
+  For,
+  Fun,
+  Fn,
+  FnTy,
+  If,
+  Impl,
+  Imu,
+  Is,
+  Load,
+  Loop,
+  Macro,
+  Match,
+  Mock,
+  Mut,
+  Pub,
+  Return,
+  Step,
+  Test,
+  True,
+  Type,
+  Unit,
+  Until,
+  Val,
+  When,
+  While,
+
+  // delimiters
+  OpenParen,
+  CloseParen,
+  OpenBrace,
+  CloseBrace,
+  OpenBracket,
+  CloseBracket,
+
+  // punctuation
+  OuterAttr,
+  InnerAttr,
+  Pound,
+  Dollar,
+  Arrow,
+  FatArrow,
+  Colon,
+  ColonColon,
+  ColonEq,
+  Comma,
+  Dot,
+  DotDot,
+  DotDotEq,
+  Question,
+  Semicolon,
+  Underscore,
+
+  // operators
+  Add,
+  Sub,
+  Concat,
+  Mul,
+  Div,
+  Rem,
+  AndAnd,
+  OrOr,
+  Not,
+  Lt,
+  Gt,
+  Le,
+  Ge,
+  EqEq,
+  Ne,
+  Shl,
+  Shr,
+  BitAnd,
+  BitOr,
+  BitXor,
+  Eq,
+  AddEq,
+  SubEq,
+  MulEq,
+  DivEq,
+  RemEq,
+  BitAndEq,
+  BitOrEq,
+  BitXorEq,
+  ShlEq,
+  ShrEq,
+}
+
+impl Token {
+  pub fn keyword(identifier: &str) -> Option<Self> {
+    match identifier {
+      "abstract" => Some(Self::Abstract),
+      "as" => Some(Self::As),
+      "behavior" => Some(Self::Behavior),
+      "bind" => Some(Self::Bind),
+      "break" => Some(Self::Break),
+      "continue" => Some(Self::Continue),
+      "data" => Some(Self::Data),
+      "each" => Some(Self::Each),
+      "else" => Some(Self::Else),
+      "enum" => Some(Self::Enum),
+      "ext" => Some(Self::Ext),
+      "false" => Some(Self::False),
+      "for" => Some(Self::For),
+      "fun" => Some(Self::Fun),
+      "fn" => Some(Self::Fn),
+      "Fn" => Some(Self::FnTy),
+      "if" => Some(Self::If),
+      "impl" => Some(Self::Impl),
+      "imu" => Some(Self::Imu),
+      "is" => Some(Self::Is),
+      "load" => Some(Self::Load),
+      "loop" => Some(Self::Loop),
+      "macro" => Some(Self::Macro),
+      "match" => Some(Self::Match),
+      "mut" => Some(Self::Mut),
+      "pub" => Some(Self::Pub),
+      "return" => Some(Self::Return),
+      "step" => Some(Self::Step),
+      "true" => Some(Self::True),
+      "type" => Some(Self::Type),
+      "until" => Some(Self::Until),
+      "val" => Some(Self::Val),
+      "when" => Some(Self::When),
+      "while" => Some(Self::While),
+      "_" => Some(Self::Underscore),
+      _ => None,
+    }
+  }
+}
+
+impl Display for Token {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    match self {
+      Self::Int(num) => match &num.suffix {
+        Some(suffix) => write!(f, "{}{suffix}", num.value),
+        None => write!(f, "{}", num.value),
+      },
+      Self::Float(num) => match &num.suffix {
+        Some(suffix) => write!(f, "{}{suffix}", num.value),
+        None => write!(f, "{}", num.value),
+      },
+      Self::Char(ch) => write!(f, "{ch:?}"),
+      Self::Str(string) => write!(f, "{string:?}"),
+      Self::RawStr(string) => write!(f, "$\"{string}\"$"),
+      Self::ByteStr(bytes) => write!(f, "`{}`", bytes.escape_ascii()),
+      Self::Identifier(identifier) => write!(f, "{identifier}"),
+      Self::Abstract => write!(f, "abstract"),
+      Self::As => write!(f, "as"),
+      Self::Behavior => write!(f, "behavior"),
+      Self::Bind => write!(f, "bind"),
+      Self::Break => write!(f, "break"),
+      Self::Continue => write!(f, "continue"),
+      Self::Data => write!(f, "data"),
+      Self::Each => write!(f, "each"),
+      Self::Else => write!(f, "else"),
+      Self::Enum => write!(f, "enum"),
+      Self::Ext => write!(f, "ext"),
+      Self::False => write!(f, "false"),
+      Self::For => write!(f, "for"),
+      Self::Fun => write!(f, "fun"),
+      Self::Fn => write!(f, "fn"),
+      Self::FnTy => write!(f, "Fn"),
+      Self::If => write!(f, "if"),
+      Self::Impl => write!(f, "impl"),
+      Self::Imu => write!(f, "imu"),
+      Self::Is => write!(f, "is"),
+      Self::Load => write!(f, "load"),
+      Self::Loop => write!(f, "loop"),
+      Self::Macro => write!(f, "macro"),
+      Self::Match => write!(f, "match"),
+      Self::Mock => write!(f, "mock"),
+      Self::Mut => write!(f, "mut"),
+      Self::Pub => write!(f, "pub"),
+      Self::Return => write!(f, "return"),
+      Self::Step => write!(f, "step"),
+      Self::Test => write!(f, "test"),
+      Self::True => write!(f, "true"),
+      Self::Type => write!(f, "type"),
+      Self::Unit => write!(f, "unit"),
+      Self::Until => write!(f, "until"),
+      Self::Val => write!(f, "val"),
+      Self::When => write!(f, "when"),
+      Self::While => write!(f, "while"),
+      Self::OpenParen => write!(f, "("),
+      Self::CloseParen => write!(f, ")"),
+      Self::OpenBrace => write!(f, "{{"),
+      Self::CloseBrace => write!(f, "}}"),
+      Self::OpenBracket => write!(f, "["),
+      Self::CloseBracket => write!(f, "]"),
+      Self::OuterAttr => write!(f, "#>"),
+      Self::InnerAttr => write!(f, "#!>"),
+      Self::Pound => write!(f, "#"),
+      Self::Dollar => write!(f, "$"),
+      Self::Arrow => write!(f, "->"),
+      Self::FatArrow => write!(f, "=>"),
+      Self::Colon => write!(f, ":"),
+      Self::ColonColon => write!(f, "::"),
+      Self::ColonEq => write!(f, ":="),
+      Self::Comma => write!(f, ","),
+      Self::Dot => write!(f, "."),
+      Self::DotDot => write!(f, ".."),
+      Self::DotDotEq => write!(f, "..="),
+      Self::Question => write!(f, "?"),
+      Self::Semicolon => write!(f, ";"),
+      Self::Underscore => write!(f, "_"),
+      Self::Add => write!(f, "+"),
+      Self::Sub => write!(f, "-"),
+      Self::Concat => write!(f, "++"),
+      Self::Mul => write!(f, "*"),
+      Self::Div => write!(f, "/"),
+      Self::Rem => write!(f, "%"),
+      Self::AndAnd => write!(f, "&&"),
+      Self::OrOr => write!(f, "||"),
+      Self::Not => write!(f, "!"),
+      Self::Lt => write!(f, "<"),
+      Self::Gt => write!(f, ">"),
+      Self::Le => write!(f, "<="),
+      Self::Ge => write!(f, ">="),
+      Self::EqEq => write!(f, "=="),
+      Self::Ne => write!(f, "!="),
+      Self::Shl => write!(f, "<<"),
+      Self::Shr => write!(f, ">>"),
+      Self::BitAnd => write!(f, "&"),
+      Self::BitOr => write!(f, "|"),
+      Self::BitXor => write!(f, "^"),
+      Self::Eq => write!(f, "="),
+      Self::AddEq => write!(f, "+="),
+      Self::SubEq => write!(f, "-="),
+      Self::MulEq => write!(f, "*="),
+      Self::DivEq => write!(f, "/="),
+      Self::RemEq => write!(f, "%="),
+      Self::BitAndEq => write!(f, "&="),
+      Self::BitOrEq => write!(f, "|="),
+      Self::BitXorEq => write!(f, "^="),
+      Self::ShlEq => write!(f, "<<="),
+      Self::ShrEq => write!(f, ">>="),
+    }
+  }
+}
@@ -0,0 +1,834 @@
+use super::token::{Suffixed, Token};
+
+use crate::front::parser::ast::TyKind;
+use crate::util::span::Span;
+
+use std::fmt;
+
+pub type Spanned = Result<(usize, Token, usize), LexicalError>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LexicalError {
+  pub kind: LexicalErrorKind,
+  pub span: Span,
+}
+
+impl LexicalError {
+  pub fn new(kind: LexicalErrorKind, span: Span) -> Self {
+    Self { kind, span }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LexicalErrorKind {
+  EmptyChar,
+  InvalidEscape(String),
+  InvalidNumber(String),
+  InvalidSuffix(String),
+  InvalidUnicodeEscape(String),
+  NonAsciiByte(char),
+  UnknownCharacter(char),
+  UnterminatedBlockComment,
+  UnterminatedByteStr,
+  UnterminatedChar,
+  UnterminatedRawStr,
+  UnterminatedStr,
+}
+
+impl fmt::Display for LexicalErrorKind {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::EmptyChar => write!(f, "empty character literal"),
+      Self::InvalidEscape(escape) => {
+        write!(f, "unknown character escape `{escape}`")
+      }
+      Self::InvalidNumber(num) => write!(f, "invalid number literal `{num}`"),
+      Self::InvalidSuffix(suffix) => {
+        write!(f, "invalid suffix `{suffix}` for number literal")
+      }
+      Self::InvalidUnicodeEscape(escape) => {
+        write!(f, "invalid unicode escape `{escape}`")
+      }
+      Self::NonAsciiByte(ch) => {
+        write!(f, "non-ASCII character `{ch}` in byte string")
+      }
+      Self::UnknownCharacter(ch) => write!(f, "unknown character `{ch}`"),
+      Self::UnterminatedBlockComment => {
+        write!(f, "unterminated block comment")
+      }
+      Self::UnterminatedByteStr => write!(f, "unterminated byte string"),
+      Self::UnterminatedChar => write!(f, "unterminated character literal"),
+      Self::UnterminatedRawStr => write!(f, "unterminated raw string"),
+      Self::UnterminatedStr => write!(f, "unterminated string"),
+    }
+  }
+}
+
+// a hand-written lexer that turns the source code into spanned tokens.
+// lexical errors are collected instead of stopping the token stream, the
+// faulty characters are skipped so that the parser can keep going
+pub struct Lexer<'a> {
+  source: &'a str,
+  pos: usize,
+  errors: Vec<LexicalError>,
+  // the number of open braces, and the one inside of the block of a unit
+  depth: usize,
+  unit_depth: Option<usize>,
+}
+
+impl<'a> Lexer<'a> {
+  pub fn new(source: &'a str) -> Self {
+    Self {
+      source,
+      pos: 0,
+      errors: vec![],
+      depth: 0,
+      unit_depth: None,
+    }
+  }
+
+  // the source is read from the offset, so that the spans of a source that
+  // follows others in the source map are distinct from theirs
+  pub fn with_offset(mut self, offset: usize) -> Self {
+    self.pos = offset;
+    self
+  }
+
+  pub fn errors(&mut self) -> Vec<LexicalError> {
+    std::mem::take(&mut self.errors)
+  }
+
+  fn current(&self) -> Option<char> {
+    self.source[self.pos..].chars().next()
+  }
+
+  fn lookahead(&self, n: usize) -> Option<char> {
+    self.source[self.pos..].chars().nth(n)
+  }
+
+  // the next character that is not a whitespace
+  fn next_visible(&self) -> Option<char> {
+    self.source[self.pos..].trim_start().chars().next()
+  }
+
+  fn bump(&mut self) -> Option<char> {
+    let ch = self.current()?;
+
+    self.pos += ch.len_utf8();
+
+    Some(ch)
+  }
+
+  fn eat(&mut self, ch: char) -> bool {
+    if self.current() == Some(ch) {
+      self.bump();
+      true
+    } else {
+      false
+    }
+  }
+
+  fn eat_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+    let lo = self.pos;
+
+    while let Some(ch) = self.current() {
+      if !predicate(ch) {
+        break;
+      }
+
+      self.bump();
+    }
+
+    &self.source[lo..self.pos]
+  }
+
+  fn error(&mut self, kind: LexicalErrorKind, lo: usize, hi: usize) {
+    self.errors.push(LexicalError::new(kind, Span::new(lo, hi)));
+  }
+
+  fn next_token(&mut self) -> Option<(usize, Token, usize)> {
+    loop {
+      self.eat_while(char::is_whitespace);
+
+      let lo = self.pos;
+
+      let token = match (self.current()?, self.lookahead(1)) {
+        ('-', Some('-')) => {
+          self.lex_line_comment();
+          continue;
+        }
+        ('-', Some('!')) if self.lookahead(2) == Some('-') => {
+          self.lex_line_comment();
+          continue;
+        }
+        ('-', Some('!')) => {
+          self.lex_block_comment(lo);
+          continue;
+        }
+        ('"', _) => self.lex_str(lo),
+        ('\'', _) => self.lex_char(lo),
+        ('`', _) => self.lex_byte_str(lo),
+        ('$', Some('"')) => self.lex_raw_str(lo),
+        (ch, _) if ch.is_ascii_digit() => self.lex_number(lo),
+        (ch, _) if is_id_start(ch) => self.lex_identifier(),
+        (ch, _) => match self.lex_punctuation() {
+          Some(token) => {
+            self.enclose(&token);
+            token
+          }
+          None => {
+            self.bump();
+            self.error(LexicalErrorKind::UnknownCharacter(ch), lo, self.pos);
+            continue;
+          }
+        },
+      };
+
+      return Some((lo, token, self.pos));
+    }
+  }
+
+  fn lex_line_comment(&mut self) {
+    self.eat_while(|ch| ch != '\n');
+  }
+
+  // block comments can be nested: `-! outer -! inner !- outer !-`
+  fn lex_block_comment(&mut self, lo: usize) {
+    let mut depth = 0;
+
+    loop {
+      match (self.current(), self.lookahead(1)) {
+        (Some('-'), Some('!')) => {
+          self.pos += 2;
+          depth += 1;
+        }
+        (Some('!'), Some('-')) => {
+          self.pos += 2;
+          depth -= 1;
+
+          if depth == 0 {
+            return;
+          }
+        }
+        (Some(_), _) => {
+          self.bump();
+        }
+        (None, _) => {
+          return self.error(
+            LexicalErrorKind::UnterminatedBlockComment,
+            lo,
+            lo + 2,
+          );
+        }
+      }
+    }
+  }
+
+  fn lex_str(&mut self, lo: usize) -> Token {
+    let mut string = String::new();
+
+    self.bump();
+
+    loop {
+      let escape_lo = self.pos;
+
+      match self.bump() {
+        Some('"') => break,
+        Some('\\') => {
+          if let Some(ch) = self.lex_escape(escape_lo) {
+            string.push(ch);
+          }
+        }
+        Some(ch) => string.push(ch),
+        None => {
+          self.error(LexicalErrorKind::UnterminatedStr, lo, lo + 1);
+          break;
+        }
+      }
+    }
+
+    Token::Str(string)
+  }
+
+  // a byte string only holds ASCII characters: `foo\n`
+  fn lex_byte_str(&mut self, lo: usize) -> Token {
+    let mut bytes = vec![];
+
+    self.bump();
+
+    loop {
+      let ch_lo = self.pos;
+
+      let ch = match self.bump() {
+        Some('`') => break,
+        Some('\\') => match self.lex_escape(ch_lo) {
+          Some(ch) => ch,
+          None => continue,
+        },
+        Some(ch) => ch,
+        None => {
+          self.error(LexicalErrorKind::UnterminatedByteStr, lo, lo + 1);
+          break;
+        }
+      };
+
+      if ch.is_ascii() {
+        bytes.push(ch as u8);
+      } else {
+        self.error(LexicalErrorKind::NonAsciiByte(ch), ch_lo, self.pos);
+      }
+    }
+
+    Token::ByteStr(bytes)
+  }
+
+  fn lex_char(&mut self, lo: usize) -> Token {
+    self.bump();
+
+    let escape_lo = self.pos;
+
+    let ch = match self.bump() {
+      Some('\'') => {
+        self.error(LexicalErrorKind::EmptyChar, lo, self.pos);
+        return Token::Char('\0');
+      }
+      Some('\\') => self.lex_escape(escape_lo).unwrap_or('\0'),
+      Some(ch) => ch,
+      None => '\0',
+    };
+
+    if !self.eat('\'') {
+      self.error(LexicalErrorKind::UnterminatedChar, lo, self.pos);
+    }
+
+    Token::Char(ch)
+  }
+
+  fn lex_escape(&mut self, lo: usize) -> Option<char> {
+    let ch = match self.bump()? {
+      'n' => '\n',
+      'r' => '\r',
+      't' => '\t',
+      '0' => '\0',
+      '\\' => '\\',
+      '"' => '"',
+      '\'' => '\'',
+      '`' => '`',
+      'u' => return self.lex_unicode_escape(lo),
+      ch => {
+        self.error(
+          LexicalErrorKind::InvalidEscape(format!("\\{ch}")),
+          lo,
+          self.pos,
+        );
+
+        return None;
+      }
+    };
+
+    Some(ch)
+  }
+
+  // a unicode escape names a scalar value by its hexadecimal code: `\u{1F47D}`
+  fn lex_unicode_escape(&mut self, lo: usize) -> Option<char> {
+    let ch = if self.eat('{') {
+      let digits = self.eat_while(|ch| ch.is_ascii_alphanumeric());
+      let ch = u32::from_str_radix(digits, 16)
+        .ok()
+        .and_then(char::from_u32);
+
+      if self.eat('}') && digits.len() <= 6 {
+        ch
+      } else {
+        None
+      }
+    } else {
+      None
+    };
+
+    if ch.is_none() {
+      let escape = self.source[lo..self.pos].to_string();
+
+      self.error(LexicalErrorKind::InvalidUnicodeEscape(escape), lo, self.pos);
+    }
+
+    ch
+  }
+
+  // raw strings are kept verbatim: `$" ... "$`, except for the layout of the
+  // multi-line ones
+  fn lex_raw_str(&mut self, lo: usize) -> Token {
+    self.pos += 2;
+
+    let content_lo = self.pos;
+
+    loop {
+      match (self.current(), self.lookahead(1)) {
+        (Some('"'), Some('$')) => {
+          let string = strip_indentation(&self.source[content_lo..self.pos]);
+
+          self.pos += 2;
+
+          return Token::RawStr(string);
+        }
+        (Some(_), _) => {
+          self.bump();
+        }
+        (None, _) => {
+          self.error(LexicalErrorKind::UnterminatedRawStr, lo, lo + 2);
+
+          return Token::RawStr(self.source[content_lo..].to_string());
+        }
+      }
+    }
+  }
+
+  fn lex_number(&mut self, lo: usize) -> Token {
+    let radix = match (self.current(), self.lookahead(1)) {
+      (Some('0'), Some('b')) => 2,
+      (Some('0'), Some('o')) => 8,
+      (Some('0'), Some('x')) => 16,
+      _ => 10,
+    };
+
+    if radix != 10 {
+      self.pos += 2;
+    }
+
+    let digits_lo = self.pos;
+    let mut is_float = false;
+
+    if radix == 16 {
+      self.eat_while(|ch| ch.is_ascii_hexdigit() || ch == '_');
+    } else {
+      self.eat_while(|ch| ch.is_ascii_digit() || ch == '_');
+    }
+
+    // `0..3` is a range, not a float
+    if radix == 10
+      && self.current() == Some('.')
+      && self.lookahead(1).is_some_and(|ch| ch.is_ascii_digit())
+    {
+      is_float = true;
+      self.bump();
+      self.eat_while(|ch| ch.is_ascii_digit() || ch == '_');
+    }
+
+    if radix == 10 && matches!(self.current(), Some('e' | 'E')) {
+      let has_exponent = match self.lookahead(1) {
+        Some('+' | '-') => {
+          self.lookahead(2).is_some_and(|ch| ch.is_ascii_digit())
+        }
+        Some(ch) => ch.is_ascii_digit(),
+        None => false,
+      };
+
+      if has_exponent {
+        is_float = true;
+        self.bump();
+        self.eat('+');
+        self.eat('-');
+        self.eat_while(|ch| ch.is_ascii_digit() || ch == '_');
+      }
+    }
+
+    let digits = self.source[digits_lo..self.pos].replace('_', "");
+    let suffix = self.lex_suffix(is_float);
+    let is_float =
+      is_float || matches!(suffix, Some(TyKind::F32 | TyKind::F64));
+    let number = &self.source[lo..self.pos];
+
+    if is_float {
+      match digits.parse::<f64>() {
+        Ok(num) => Token::Float(Suffixed::new(num, suffix)),
+        Err(_) => {
+          self.error(
+            LexicalErrorKind::InvalidNumber(number.to_string()),
+            lo,
+            self.pos,
+          );
+
+          Token::Float(Suffixed::new(0.0, suffix))
+        }
+      }
+    } else {
+      match i64::from_str_radix(&digits, radix) {
+        Ok(num) => Token::Int(Suffixed::new(num, suffix)),
+        Err(_) => {
+          self.error(
+            LexicalErrorKind::InvalidNumber(number.to_string()),
+            lo,
+            self.pos,
+          );
+
+          Token::Int(Suffixed::new(0, suffix))
+        }
+      }
+    }
+  }
+
+  // a number literal can be followed by the name of a primitive type:
+  // `1u8`, `2s64`, `3.0f32`
+  fn lex_suffix(&mut self, is_float: bool) -> Option<TyKind> {
+    let lo = self.pos;
+    let suffix = self.eat_while(is_id_continue);
+
+    if suffix.is_empty() {
+      return None;
+    }
+
+    let ty = match suffix {
+      "u8" => TyKind::U8,
+      "u16" => TyKind::U16,
+      "u32" => TyKind::U32,
+      "u64" => TyKind::U64,
+      "uint" => TyKind::UInt,
+      "s8" => TyKind::S8,
+      "s16" => TyKind::S16,
+      "s32" => TyKind::S32,
+      "s64" => TyKind::S64,
+      "sint" => TyKind::SInt,
+      "f32" => TyKind::F32,
+      "f64" => TyKind::F64,
+      _ => {
+        self.error(
+          LexicalErrorKind::InvalidSuffix(suffix.to_string()),
+          lo,
+          self.pos,
+        );
+
+        return None;
+      }
+    };
+
+    if is_float && !matches!(ty, TyKind::F32 | TyKind::F64) {
+      self.error(
+        LexicalErrorKind::InvalidSuffix(suffix.to_string()),
+        lo,
+        self.pos,
+      );
+
+      return None;
+    }
+
+    Some(ty)
+  }
+
+  fn lex_identifier(&mut self) -> Token {
+    let identifier = self.eat_while(is_id_continue);
+
+    if let Some(keyword) = Token::keyword(identifier) {
+      return keyword;
+    }
+
+    match self.lex_contextual_keyword(identifier) {
+      Some(keyword) => keyword,
+      None => Token::Identifier(identifier.to_string()),
+    }
+  }
+
+  // `unit` is only a keyword before a block at the top level, `test` and
+  // `mock` before the name of an item of that block. elsewhere they are
+  // identifiers: `#> cfg: test.`, `imu test := 1;`
+  fn lex_contextual_keyword(&mut self, identifier: &str) -> Option<Token> {
+    let next = self.next_visible();
+
+    match identifier {
+      "unit" if self.depth == 0 && next == Some('{') => {
+        self.unit_depth = Some(1);
+
+        Some(Token::Unit)
+      }
+      "test" | "mock"
+        if self.unit_depth == Some(self.depth)
+          && next.is_some_and(is_id_start) =>
+      {
+        match identifier {
+          "test" => Some(Token::Test),
+          _ => Some(Token::Mock),
+        }
+      }
+      _ => None,
+    }
+  }
+
+  // keeps track of the braces, the block of a unit ends with its own
+  fn enclose(&mut self, token: &Token) {
+    match token {
+      Token::OpenBrace => self.depth += 1,
+      Token::CloseBrace => {
+        self.depth = self.depth.saturating_sub(1);
+
+        if self.unit_depth.is_some_and(|depth| self.depth < depth) {
+          self.unit_depth = None;
+        }
+      }
+      _ => {}
+    }
+  }
+
+  // the longest punctuation wins: `<<=` before `<<` before `<`
+  fn lex_punctuation(&mut self) -> Option<Token> {
+    let rest = &self.source[self.pos..];
+
+    let (token, len) = match rest.get(..3) {
+      Some("#!>") => (Token::InnerAttr, 3),
+      Some("<<=") => (Token::ShlEq, 3),
+      Some(">>=") => (Token::ShrEq, 3),
+      Some("..=") => (Token::DotDotEq, 3),
+      _ => match rest.get(..2) {
+        Some("#>") => (Token::OuterAttr, 2),
+        Some("->") => (Token::Arrow, 2),
+        Some("=>") => (Token::FatArrow, 2),
+        Some("::") => (Token::ColonColon, 2),
+        Some(":=") => (Token::ColonEq, 2),
+        Some("..") => (Token::DotDot, 2),
+        Some("&&") => (Token::AndAnd, 2),
+        Some("||") => (Token::OrOr, 2),
+        Some("<=") => (Token::Le, 2),
+        Some(">=") => (Token::Ge, 2),
+        Some("==") => (Token::EqEq, 2),
+        Some("!=") => (Token::Ne, 2),
+        Some("<<") => (Token::Shl, 2),
+        Some(">>") => (Token::Shr, 2),
+        Some("++") => (Token::Concat, 2),
+        Some("+=") => (Token::AddEq, 2),
+        Some("-=") => (Token::SubEq, 2),
+        Some("*=") => (Token::MulEq, 2),
+        Some("/=") => (Token::DivEq, 2),
+        Some("%=") => (Token::RemEq, 2),
+        Some("&=") => (Token::BitAndEq, 2),
+        Some("|=") => (Token::BitOrEq, 2),
+        Some("^=") => (Token::BitXorEq, 2),
+        _ => match rest.chars().next()? {
+          '#' => (Token::Pound, 1),
+          '$' => (Token::Dollar, 1),
+          '(' => (Token::OpenParen, 1),
+          ')' => (Token::CloseParen, 1),
+          '{' => (Token::OpenBrace, 1),
+          '}' => (Token::CloseBrace, 1),
+          '[' => (Token::OpenBracket, 1),
+          ']' => (Token::CloseBracket, 1),
+          ':' => (Token::Colon, 1),
+          ',' => (Token::Comma, 1),
+          '.' => (Token::Dot, 1),
+          '?' => (Token::Question, 1),
+          ';' => (Token::Semicolon, 1),
+          '+' => (Token::Add, 1),
+          '-' => (Token::Sub, 1),
+          '*' => (Token::Mul, 1),
+          '/' => (Token::Div, 1),
+          '%' => (Token::Rem, 1),
+          '!' => (Token::Not, 1),
+          '<' => (Token::Lt, 1),
+          '>' => (Token::Gt, 1),
+          '&' => (Token::BitAnd, 1),
+          '|' => (Token::BitOr, 1),
+          '^' => (Token::BitXor, 1),
+          '=' => (Token::Eq, 1),
+          _ => return None,
+        },
+      },
+    };
+
+    self.pos += len;
+
+    Some(token)
+  }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+  type Item = Spanned;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.next_token().map(Ok)
+  }
+}
+
+// a raw string that starts with a line break is multi-line, its first line
+// break, its closing line and the indentation shared by its lines are removed
+fn strip_indentation(string: &str) -> String {
+  let Some(string) = string
+    .strip_prefix("\r\n")
+    .or_else(|| string.strip_prefix('\n'))
+  else {
+    return string.to_string();
+  };
+
+  let string = match string.rfind('\n') {
+    Some(pos) if string[pos + 1..].trim().is_empty() => &string[..pos],
+    _ => string,
+  };
+
+  let indent = string
+    .lines()
+    .filter(|line| !line.trim().is_empty())
+    .map(|line| line.len() - line.trim_start().len())
+    .min()
+    .unwrap_or(0);
+
+  string
+    .lines()
+    .map(|line| line.get(indent..).unwrap_or_default())
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+fn is_id_start(ch: char) -> bool {
+  ch.is_ascii_alphabetic() || ch == '_'
+}
+
+fn is_id_continue(ch: char) -> bool {
+  ch.is_ascii_alphanumeric() || ch == '_'
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn tokens(source: &str) -> (Vec<Token>, Vec<LexicalError>) {
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.by_ref().map(|spanned| spanned.unwrap().1).collect();
+
+    (tokens, lexer.errors())
+  }
+
+  fn int(value: i64, suffix: Option<TyKind>) -> Token {
+    Token::Int(Suffixed::new(value, suffix))
+  }
+
+  #[test]
+  fn nested_block_comments_are_skipped() {
+    let (tokens, errors) = tokens("1 -! a -! b !- c !- 2");
+
+    assert_eq!(tokens, vec![int(1, None), int(2, None)]);
+    assert!(errors.is_empty());
+  }
+
+  #[test]
+  fn unterminated_block_comment_points_at_its_opening() {
+    let (tokens, errors) = tokens("1 -! a -! b !- c");
+
+    assert_eq!(tokens, vec![int(1, None)]);
+    assert_eq!(
+      errors,
+      vec![LexicalError::new(
+        LexicalErrorKind::UnterminatedBlockComment,
+        Span::new(2, 4)
+      )]
+    );
+  }
+
+  #[test]
+  fn unit_keywords_are_contextual() {
+    let (unit_tokens, _) = tokens("unit { test foo() { test } mock bar }");
+
+    assert_eq!(
+      unit_tokens,
+      vec![
+        Token::Unit,
+        Token::OpenBrace,
+        Token::Test,
+        Token::Identifier("foo".to_string()),
+        Token::OpenParen,
+        Token::CloseParen,
+        Token::OpenBrace,
+        Token::Identifier("test".to_string()),
+        Token::CloseBrace,
+        Token::Mock,
+        Token::Identifier("bar".to_string()),
+        Token::CloseBrace,
+      ]
+    );
+
+    let (attr_tokens, _) = tokens("#> cfg: test. fun unit() {}");
+
+    assert_eq!(attr_tokens[3], Token::Identifier("test".to_string()));
+    assert_eq!(attr_tokens[6], Token::Identifier("unit".to_string()));
+  }
+
+  #[test]
+  fn escapes_are_decoded() {
+    let (tokens, errors) = tokens(r#""a\n\t\\\"\u{1F47D}" '\'' `\0`"#);
+
+    assert_eq!(
+      tokens,
+      vec![
+        Token::Str("a\n\t\\\"\u{1F47D}".to_string()),
+        Token::Char('\''),
+        Token::ByteStr(vec![0]),
+      ]
+    );
+    assert!(errors.is_empty());
+  }
+
+  #[test]
+  fn bad_escapes_are_reported() {
+    let (_, errors) = tokens(r#""\q" "\u{110000}""#);
+
+    let kinds = errors
+      .into_iter()
+      .map(|error| error.kind)
+      .collect::<Vec<_>>();
+
+    assert_eq!(
+      kinds,
+      vec![
+        LexicalErrorKind::InvalidEscape("\\q".to_string()),
+        LexicalErrorKind::InvalidUnicodeEscape("\\u{110000}".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn suffixes_are_read_after_numbers() {
+    let (tokens, errors) = tokens("1u8 0xffs64 2.5f32 3f64 0..3");
+
+    assert_eq!(
+      tokens,
+      vec![
+        int(1, Some(TyKind::U8)),
+        int(255, Some(TyKind::S64)),
+        Token::Float(Suffixed::new(2.5, Some(TyKind::F32))),
+        Token::Float(Suffixed::new(3.0, Some(TyKind::F64))),
+        int(0, None),
+        Token::DotDot,
+        int(3, None),
+      ]
+    );
+    assert!(errors.is_empty());
+  }
+
+  #[test]
+  fn bad_suffixes_are_reported() {
+    let (_, errors) = tokens("1u7 2.0u8");
+
+    let kinds = errors
+      .into_iter()
+      .map(|error| error.kind)
+      .collect::<Vec<_>>();
+
+    assert_eq!(
+      kinds,
+      vec![
+        LexicalErrorKind::InvalidSuffix("u7".to_string()),
+        LexicalErrorKind::InvalidSuffix("u8".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn raw_strings_are_verbatim() {
+    let (tokens, errors) = tokens(r#"$"a\n "b" "$"#);
+
+    assert_eq!(tokens, vec![Token::RawStr(r#"a\n "b" "#.to_string())]);
+    assert!(errors.is_empty());
+  }
+
+  #[test]
+  fn multi_line_raw_strings_lose_their_indentation() {
+    let (tokens, _) = tokens("$\"\n    foo\n      bar\n    \"$");
+
+    assert_eq!(tokens, vec![Token::RawStr("foo\n  bar".to_string())]);
+  }
+}
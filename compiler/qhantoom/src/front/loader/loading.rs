@@ -0,0 +1,293 @@
+use super::resolution::{fmt_path, paths, Resolver, Segment};
+
+use crate::front::parser::ast::*;
+use crate::front::parser::parse_source;
+use crate::util::error::{Label, LabelKind, LabelMessage};
+
+use crate::util::error::{
+  Report, ReportCode, ReportKind, ReportMessage, ReportOffset, Reporter,
+};
+
+use crate::util::span::Span;
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// loads the modules named by the `load` and `bind` items of the program and,
+// in turn, the ones named by the loaded modules. a module is parsed once
+// however many times it is loaded, its items are put before the ones of the
+// program so that they are declared before they are used
+pub fn load(program: &mut Program, search_paths: &[PathBuf]) {
+  let entry = program.reporter.source_map.sources[0].path.clone();
+  let root = directory_of(&entry);
+
+  let mut loader = Loader {
+    resolver: Resolver {
+      root: &root,
+      search_paths,
+    },
+    reporter: &mut program.reporter,
+    loaded: HashMap::new(),
+    stack: vec![(canonical(&entry), entry.display().to_string())],
+    unresolved: vec![],
+    imports: HashMap::new(),
+    attrs: vec![],
+    items: vec![],
+  };
+
+  let modules = loader.load_modules(&program.items, &root, 0);
+  let Loader {
+    imports,
+    attrs,
+    items,
+    ..
+  } = loader;
+
+  program.modules = modules;
+  program.imports = imports;
+  program.attrs.extend(attrs);
+  program.items.splice(0..0, items);
+}
+
+struct Loader<'a> {
+  resolver: Resolver<'a>,
+  reporter: &'a mut Reporter,
+  // the parsed modules and the names of their items, by canonical path
+  loaded: HashMap<PathBuf, (Module, HashSet<String>)>,
+  // the files being loaded from the entry file, to detect the cycles
+  stack: Vec<(PathBuf, String)>,
+  // the paths of a group share their first segments, a segment that cannot
+  // be resolved is only reported once
+  unresolved: Vec<Span>,
+  imports: HashMap<u32, Vec<Import>>,
+  attrs: Vec<Attribute>,
+  items: Vec<PBox<Item>>,
+}
+
+impl<'a> Loader<'a> {
+  fn load_modules(
+    &mut self,
+    items: &[PBox<Item>],
+    directory: &Path,
+    importer: u32,
+  ) -> Vec<Module> {
+    let mut modules: Vec<Module> = vec![];
+
+    for item in items {
+      let (path_view, public) = match &item.kind {
+        ItemKind::Load(load) => (&load.path_view, false),
+        ItemKind::Bind(bind) => {
+          (&bind.path_view, matches!(bind.public, Public::Yes(_)))
+        }
+        _ => continue,
+      };
+
+      for path in paths(path_view) {
+        let Some((module, module_path, item)) =
+          self.load_path(&path, directory)
+        else {
+          continue;
+        };
+
+        self.imports.entry(importer).or_default().push(Import {
+          source_id: module.source_id,
+          path: module_path,
+          item,
+          public,
+        });
+
+        if modules.iter().all(|loaded| loaded.path != module.path) {
+          modules.push(module);
+        }
+      }
+    }
+
+    modules
+  }
+
+  fn load_path(
+    &mut self,
+    path: &[Segment],
+    directory: &Path,
+  ) -> Option<(Module, String, Option<String>)> {
+    let target = match self.resolver.resolve(path, directory) {
+      Ok(target) => target,
+      Err((span, _)) if self.unresolved.contains(&span) => return None,
+      Err((span, reason)) => {
+        self.unresolved.push(span);
+        add_report_unresolved_load_error(self.reporter, path, span, reason);
+        return None;
+      }
+    };
+
+    let module_path = match target.item {
+      Some(_) => &path[..path.len() - 1],
+      None => path,
+    };
+
+    let file = canonical(&target.file);
+
+    let loading = self.stack.iter().position(|(loading, _)| *loading == file);
+
+    if let Some(index) = loading {
+      let mut cycle = self.stack[index..]
+        .iter()
+        .map(|(_, shown)| format!("`{shown}`"))
+        .collect::<Vec<_>>();
+
+      cycle.push(format!("`{}`", target.file.display()));
+
+      let cycle = cycle.join(" → ");
+
+      add_report_cyclic_load_error(self.reporter, module_path, &cycle);
+      return None;
+    }
+
+    if !self.loaded.contains_key(&file) {
+      self.parse_module(fmt_path(module_path), &target.file, file.clone());
+    }
+
+    let (module, names) = &self.loaded[&file];
+
+    match target.item {
+      Some(item) if !names.contains(&item.name) => {
+        let reason =
+          format!("`{}` does not define `{}`", module.name, item.name);
+
+        add_report_unresolved_load_error(
+          self.reporter,
+          path,
+          item.span,
+          reason,
+        );
+
+        None
+      }
+      item => Some((
+        module.clone(),
+        fmt_path(module_path),
+        item.map(|item| item.name),
+      )),
+    }
+  }
+
+  fn parse_module(&mut self, name: String, path: &Path, file: PathBuf) {
+    let source_id = self.reporter.add_source(path).unwrap();
+
+    let (attrs, items) = match parse_source(self.reporter, source_id) {
+      (Some(program), _) => program,
+      (None, _) => self.reporter.abort(),
+    };
+
+    self.stack.push((file.clone(), path.display().to_string()));
+
+    let modules = self.load_modules(&items, &directory_of(path), source_id);
+
+    self.stack.pop();
+
+    let mut names = items
+      .iter()
+      .filter_map(|item| item.name())
+      .collect::<HashSet<_>>();
+
+    names.extend(self.bound_names(source_id));
+
+    let module = Module::new(name, path.to_path_buf(), source_id, modules);
+
+    self.attrs.extend(attrs);
+    self.items.extend(items);
+    self.loaded.insert(file, (module, names));
+  }
+
+  // the names that a module binds with `pub bind`, they can be loaded from it
+  // as if it defined them
+  fn bound_names(&self, source_id: u32) -> Vec<String> {
+    let Some(imports) = self.imports.get(&source_id) else {
+      return vec![];
+    };
+
+    imports
+      .iter()
+      .filter(|import| import.public)
+      .flat_map(|import| match &import.item {
+        Some(item) => vec![item.to_owned()],
+        None => self
+          .loaded
+          .values()
+          .find(|(module, _)| module.source_id == import.source_id)
+          .map(|(_, names)| names.iter().cloned().collect())
+          .unwrap_or_default(),
+      })
+      .collect()
+  }
+}
+
+fn directory_of(path: &Path) -> PathBuf {
+  path.parent().unwrap_or(Path::new("")).to_path_buf()
+}
+
+// the same file can be named by several paths
+fn canonical(path: &Path) -> PathBuf {
+  fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn add_report_unresolved_load_error(
+  reporter: &Reporter,
+  path: &[Segment],
+  span: Span,
+  reason: String,
+) {
+  let source_id = reporter.source(span);
+  let code = reporter.code(source_id);
+  let path_name = reporter.path(span);
+
+  reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path_name.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(15)) // FIXME #2
+    .with_message(ReportMessage::UnresolvedLoad(fmt_path(path)))
+    .with_label(
+      Label::new(
+        LabelKind::Error,
+        (path_name.display().to_string(), span.into()),
+      )
+      .with_message(LabelMessage::UnresolvedLoad(reason)),
+    ),
+    path_name.display().to_string(),
+    code,
+  )
+}
+
+fn add_report_cyclic_load_error(
+  reporter: &Reporter,
+  path: &[Segment],
+  cycle: &str,
+) {
+  let span = Span::merge(&path[0].span, &path[path.len() - 1].span);
+  let source_id = reporter.source(span);
+  let code = reporter.code(source_id);
+  let path_name = reporter.path(span);
+
+  reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path_name.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(16)) // FIXME #2
+    .with_message(ReportMessage::CyclicLoad(fmt_path(path)))
+    .with_label(
+      Label::new(
+        LabelKind::Error,
+        (path_name.display().to_string(), span.into()),
+      )
+      .with_message(LabelMessage::CyclicLoad(cycle.to_string())),
+    ),
+    path_name.display().to_string(),
+    code,
+  )
+}
@@ -0,0 +1,6 @@
+mod loading;
+mod qualification;
+mod resolution;
+
+pub use loading::load;
+pub use qualification::qualify;
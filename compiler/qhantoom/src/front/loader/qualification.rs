@@ -0,0 +1,594 @@
+use crate::front::parser::ast::*;
+use crate::util::error::{Label, LabelKind, LabelMessage};
+
+use crate::util::error::{
+  Report, ReportCode, ReportKind, ReportMessage, ReportOffset, Reporter,
+};
+
+use crate::util::span::Span;
+
+use std::collections::{HashMap, HashSet};
+
+// the top-level items of a module by name, with whether they are public
+type Names = HashMap<String, bool>;
+
+// the items of every loaded module are renamed `module::name`, so that two
+// modules can each define an item of the same name. the uses of these items
+// are renamed along, whether they name them as `name` or through the path
+// under which their module is loaded. it runs once the macros are expanded,
+// since a macro call of a module may use the private items of that module
+pub fn qualify(program: &mut Program) {
+  let mut module_names = HashMap::new();
+
+  collect_module_names(&program.modules, &mut module_names);
+
+  let mut names: HashMap<u32, Names> = HashMap::new();
+
+  for item in &program.items {
+    let source_id = program.reporter.source(item.span);
+
+    for (name, public) in item_names(item) {
+      names.entry(source_id).or_default().insert(name, public);
+    }
+  }
+
+  let modules = Modules {
+    module_names,
+    names,
+    imports: &program.imports,
+  };
+
+  for item in &mut program.items {
+    let mut qualifier = Qualifier {
+      reporter: &program.reporter,
+      source_id: program.reporter.source(item.span),
+      modules: &modules,
+      locals: vec![],
+    };
+
+    qualifier.walk_item(item);
+  }
+}
+
+fn collect_module_names(modules: &[Module], names: &mut HashMap<u32, String>) {
+  for module in modules {
+    names
+      .entry(module.source_id)
+      .or_insert_with(|| module.name.to_owned());
+
+    collect_module_names(&module.modules, names);
+  }
+}
+
+// an external function keeps its name, which is the one of its symbol
+fn item_names(item: &Item) -> Vec<(String, bool)> {
+  let (name, public) = match &item.kind {
+    ItemKind::Val(decl) => (decl.pattern.to_string(), &decl.public),
+    ItemKind::Fun(fun) => (fun.prototype.name.to_string(), &fun.public),
+    ItemKind::Data(data) => (data.name.to_string(), &data.public),
+    ItemKind::Enum(enumeration) => {
+      (enumeration.name.to_string(), &enumeration.public)
+    }
+    ItemKind::Behavior(behavior) => {
+      (behavior.name.to_string(), &behavior.public)
+    }
+    _ => return vec![],
+  };
+
+  vec![(name, matches!(public, Public::Yes(_)))]
+}
+
+// what the qualifier knows of every source, the entry file has no name
+struct Modules<'a> {
+  module_names: HashMap<u32, String>,
+  names: HashMap<u32, Names>,
+  imports: &'a HashMap<u32, Vec<Import>>,
+}
+
+impl<'a> Modules<'a> {
+  // the name under which the item `name` of the source is declared
+  fn qualified(&self, source_id: u32, name: &str) -> String {
+    match self.module_names.get(&source_id) {
+      Some(module) => format!("{module}::{name}"),
+      None => name.to_string(),
+    }
+  }
+
+  fn defines(&self, source_id: u32, name: &str) -> Option<bool> {
+    self.names.get(&source_id)?.get(name).copied()
+  }
+
+  fn imports(&self, source_id: u32) -> impl Iterator<Item = &Import> {
+    self.imports.get(&source_id).into_iter().flatten()
+  }
+
+  // the source that defines the item `name` that the import brings in,
+  // either the imported module or one that it binds with `pub bind`
+  fn exporter(
+    &self,
+    import: &Import,
+    name: &str,
+    visited: &mut HashSet<u32>,
+  ) -> Option<u32> {
+    if import.item.as_ref().is_some_and(|item| item != name) {
+      return None;
+    }
+
+    if !visited.insert(import.source_id) {
+      return None;
+    }
+
+    if self.defines(import.source_id, name).is_some() {
+      return Some(import.source_id);
+    }
+
+    self
+      .imports(import.source_id)
+      .filter(|bound| bound.public)
+      .find_map(|bound| self.exporter(bound, name, visited))
+  }
+}
+
+struct Qualifier<'a> {
+  reporter: &'a Reporter,
+  // the source of the item being walked
+  source_id: u32,
+  modules: &'a Modules<'a>,
+  // the names bound in the scope being walked, they shadow the items
+  locals: Vec<String>,
+}
+
+impl<'a> Qualifier<'a> {
+  // the name under which the item `name` is declared, `None` if it is not an
+  // item. a name that the source does not import is still named after the
+  // module that defines it, so that the analyzer reports its use
+  fn resolve(&self, name: &str, span: Span) -> Option<String> {
+    if self.locals.iter().any(|local| local == name) {
+      return None;
+    }
+
+    let modules = self.modules;
+
+    if modules.defines(self.source_id, name).is_some() {
+      return Some(modules.qualified(self.source_id, name));
+    }
+
+    // a public item is preferred to a private one of the same name
+    let mut exporters = modules
+      .imports(self.source_id)
+      .filter_map(|import| {
+        let exporter = modules.exporter(import, name, &mut HashSet::new())?;
+
+        Some((modules.defines(exporter, name) != Some(true), exporter))
+      })
+      .collect::<Vec<_>>();
+
+    exporters.sort();
+    exporters.dedup();
+
+    let public = exporters
+      .iter()
+      .filter(|(private, _)| !private)
+      .filter_map(|(_, exporter)| modules.module_names.get(exporter))
+      .map(|module| format!("`{module}`"))
+      .collect::<Vec<_>>();
+
+    if public.len() > 1 {
+      add_report_ambiguous_name_error(self.reporter, name, &public, span);
+    }
+
+    let exporter = match exporters.first() {
+      Some((_, exporter)) => Some(*exporter),
+      None => {
+        let mut definers = modules
+          .names
+          .iter()
+          .filter(|(source_id, names)| {
+            **source_id != self.source_id && names.contains_key(name)
+          })
+          .map(|(source_id, _)| *source_id)
+          .collect::<Vec<_>>();
+
+        definers.sort();
+        definers.first().copied()
+      }
+    };
+
+    exporter.map(|exporter| modules.qualified(exporter, name))
+  }
+
+  // `module::name` names the item `name` of a module, the module is named by
+  // the path under which the source loads it or by the end of that path.
+  // the path of a module that the source does not load is still resolved
+  // so that the analyzer reports its use
+  fn resolve_path(&self, module: &str, name: &str) -> Option<String> {
+    let modules = self.modules;
+    let suffix = format!("::{module}");
+
+    let imported = modules
+      .imports(self.source_id)
+      .find(|import| import.path == module || import.path.ends_with(&suffix));
+
+    let source_id = match imported {
+      Some(import) => import.source_id,
+      None => {
+        *modules
+          .module_names
+          .iter()
+          .find(|(_, module_name)| *module_name == module)?
+          .0
+      }
+    };
+
+    let import = Import {
+      source_id,
+      path: module.to_string(),
+      item: None,
+      public: false,
+    };
+
+    let exporter = modules.exporter(&import, name, &mut HashSet::new())?;
+
+    Some(modules.qualified(exporter, name))
+  }
+
+  fn resolve_name(&self, name: &str, span: Span) -> Option<String> {
+    match name.rsplit_once("::") {
+      Some((module, name)) => self.resolve_path(module, name),
+      None => self.resolve(name, span),
+    }
+  }
+
+  fn qualify(&self, expr: &mut Expr) {
+    if let ExprKind::Identifier(name) = &mut expr.kind {
+      if let Some(qualified) = self.resolve(name, expr.span) {
+        *name = qualified;
+      }
+    }
+  }
+
+  fn bind(&mut self, pattern: &Pattern) {
+    match &pattern.kind {
+      PatternKind::Identifier(_, name) => self.locals.push(name.to_string()),
+      PatternKind::Variant(_, patterns) => {
+        for pattern in patterns {
+          self.bind(pattern);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn walk_item(&mut self, item: &mut Item) {
+    match &mut item.kind {
+      ItemKind::Ext(ext) => {
+        self.walk_prototype(&mut ext.prototype);
+
+        if let Some(body) = &mut ext.body {
+          self.walk_block(body);
+        }
+
+        self.locals.clear();
+      }
+      ItemKind::Val(decl) => {
+        if let PatternKind::Identifier(_, name) = &mut decl.pattern.kind {
+          self.qualify(name);
+        }
+
+        self.walk_decl(decl);
+      }
+      ItemKind::Fun(fun) => {
+        self.qualify(&mut fun.prototype.name);
+        self.walk_fun(fun);
+      }
+      ItemKind::Data(data) => {
+        self.qualify(&mut data.name);
+
+        for field in &mut data.fields {
+          self.walk_ty(&mut field.ty);
+        }
+      }
+      ItemKind::Enum(enumeration) => {
+        self.qualify(&mut enumeration.name);
+
+        for variant in &mut enumeration.variants {
+          for ty in &mut variant.tys {
+            self.walk_ty(ty);
+          }
+        }
+      }
+      ItemKind::Impl(imp) => {
+        self.walk_ty(&mut imp.ty);
+
+        if let Some(behavior) = &mut imp.behavior {
+          self.walk_expr(behavior);
+        }
+
+        for fun in &mut imp.funs {
+          self.walk_fun(fun);
+        }
+      }
+      ItemKind::Behavior(behavior) => {
+        self.qualify(&mut behavior.name);
+
+        for method in &mut behavior.methods {
+          self.walk_prototype(&mut method.prototype);
+
+          if let Some(body) = &mut method.body {
+            self.walk_block(body);
+          }
+
+          self.locals.clear();
+        }
+      }
+      ItemKind::Type(alias) => match &mut alias.kind {
+        TyAliasKind::Single(ty) => self.walk_ty(ty),
+        TyAliasKind::Group(fields) => {
+          for field in fields {
+            self.walk_ty(&mut field.ty);
+          }
+        }
+      },
+      ItemKind::Unit(unit) => {
+        for item in &mut unit.items {
+          match item {
+            // a test is named after its module, so that two modules can
+            // each have a test of the same name
+            UnitItem::Test(fun) => {
+              let name = &mut fun.prototype.name;
+
+              if let ExprKind::Identifier(name) = &mut name.kind {
+                *name = self.modules.qualified(self.source_id, name);
+              }
+
+              self.walk_fun(fun);
+            }
+            // a mock replaces the function of the same name
+            UnitItem::Mock(fun) => {
+              self.qualify(&mut fun.prototype.name);
+              self.walk_fun(fun);
+            }
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn walk_fun(&mut self, fun: &mut Fun) {
+    self.walk_prototype(&mut fun.prototype);
+    self.walk_block(&mut fun.body);
+    self.locals.clear();
+  }
+
+  // the inputs are bound until the end of the function
+  fn walk_prototype(&mut self, prototype: &mut Prototype) {
+    for ty_param in &mut prototype.ty_params {
+      for bound in &mut ty_param.bounds {
+        self.walk_expr(bound);
+      }
+    }
+
+    for input in &mut prototype.inputs {
+      self.walk_ty(&mut input.ty);
+      self.bind(&input.pattern);
+    }
+
+    if let ReturnTy::Ty(ty) = &mut prototype.output {
+      self.walk_ty(ty);
+    }
+  }
+
+  fn walk_ty(&mut self, ty: &mut Ty) {
+    match &mut ty.kind {
+      TyKind::Data(name) | TyKind::Enum(name) => {
+        if let Some(qualified) = self.resolve_name(name, ty.span) {
+          *name = qualified;
+        }
+      }
+      TyKind::App(name, tys) => {
+        if let Some(qualified) = self.resolve_name(name, ty.span) {
+          *name = qualified;
+        }
+
+        for ty in tys {
+          self.walk_ty(ty);
+        }
+      }
+      TyKind::Fn(input_tys, return_ty) => {
+        for ty in input_tys {
+          self.walk_ty(ty);
+        }
+
+        self.walk_ty(return_ty);
+      }
+      TyKind::Array(ty, _) | TyKind::Option(ty) => self.walk_ty(ty),
+      TyKind::Result(ok_ty, err_ty) => {
+        self.walk_ty(ok_ty);
+        self.walk_ty(err_ty);
+      }
+      _ => {}
+    }
+  }
+
+  fn walk_block(&mut self, block: &mut Block) {
+    let depth = self.locals.len();
+
+    for stmt in &mut block.stmts {
+      match &mut stmt.kind {
+        StmtKind::Item(item) => match &mut item.kind {
+          ItemKind::Val(decl) => self.walk_decl(decl),
+          _ => self.walk_item(item),
+        },
+        StmtKind::Decl(decl) => self.walk_decl(decl),
+        StmtKind::Expr(expr) => self.walk_expr(expr),
+      }
+    }
+
+    self.locals.truncate(depth);
+  }
+
+  // the name of a local declaration is bound once its value is walked
+  fn walk_decl(&mut self, decl: &mut Decl) {
+    if let Some(ty) = &mut decl.ty {
+      self.walk_ty(ty);
+    }
+
+    self.walk_expr(&mut decl.value);
+    self.bind(&decl.pattern);
+  }
+
+  fn walk_pattern(&mut self, pattern: &mut Pattern) {
+    if let PatternKind::Variant(path, patterns) = &mut pattern.kind {
+      self.walk_expr(path);
+
+      for pattern in patterns {
+        self.walk_pattern(pattern);
+      }
+    }
+  }
+
+  fn walk_expr(&mut self, expr: &mut Expr) {
+    match &mut expr.kind {
+      ExprKind::Identifier(_) => self.qualify(expr),
+      ExprKind::Path(path, name) => {
+        match self.resolve_path(&path.to_string(), &name.to_string()) {
+          Some(name) => expr.kind = ExprKind::Identifier(name),
+          None => self.walk_expr(path),
+        }
+      }
+      ExprKind::Call(callee, args) => {
+        self.walk_expr(callee);
+        self.walk_exprs(args);
+      }
+      ExprKind::UnOp(_, rhs) => self.walk_expr(rhs),
+      ExprKind::BinOp(lhs, _, rhs)
+      | ExprKind::Assign(lhs, _, rhs)
+      | ExprKind::AssignOp(lhs, _, rhs) => {
+        self.walk_expr(lhs);
+        self.walk_expr(rhs);
+      }
+      ExprKind::Return(Some(value)) | ExprKind::Break(Some(value)) => {
+        self.walk_expr(value)
+      }
+      ExprKind::Block(block) | ExprKind::Loop(block) => self.walk_block(block),
+      ExprKind::While(condition, block) => {
+        self.walk_expr(condition);
+        self.walk_block(block);
+      }
+      ExprKind::When(condition, consequence, alternative) => {
+        self.walk_expr(condition);
+        self.walk_expr(consequence);
+        self.walk_expr(alternative);
+      }
+      ExprKind::IfElse(condition, consequence, alternative) => {
+        let depth = self.locals.len();
+
+        self.walk_expr(condition);
+        self.walk_expr(consequence);
+        self.locals.truncate(depth);
+
+        if let Some(alternative) = alternative {
+          self.walk_expr(alternative);
+        }
+      }
+      ExprKind::Field(lhs, _) | ExprKind::Try(lhs) => self.walk_expr(lhs),
+      // the names bound by `is` are only used in the consequence of an `if`
+      ExprKind::Is(lhs, pattern) => {
+        self.walk_expr(lhs);
+        self.walk_pattern(pattern);
+        self.bind(pattern);
+      }
+      ExprKind::Struct(name, fields) => {
+        self.walk_expr(name);
+
+        for field in fields {
+          if let Some(value) = &mut field.value {
+            self.walk_expr(value);
+          }
+        }
+      }
+      ExprKind::Match(scrutinee, arms) => {
+        self.walk_expr(scrutinee);
+
+        for arm in arms {
+          let depth = self.locals.len();
+
+          self.walk_pattern(&mut arm.pattern);
+          self.bind(&arm.pattern);
+          self.walk_expr(&mut arm.body);
+          self.locals.truncate(depth);
+        }
+      }
+      ExprKind::For(name, range, step, block) => {
+        self.walk_expr(range);
+
+        if let Some(step) = step {
+          self.walk_expr(step);
+        }
+
+        self.locals.push(name.to_string());
+        self.walk_block(block);
+        self.locals.pop();
+      }
+      ExprKind::ForEach(iterable, block) => {
+        self.walk_expr(iterable);
+        self.locals.push("it".to_string());
+        self.walk_block(block);
+        self.locals.pop();
+      }
+      ExprKind::Array(elements) => self.walk_exprs(elements),
+      ExprKind::Lambda(lambda) => {
+        let depth = self.locals.len();
+
+        for input in &mut lambda.inputs {
+          if let Some(ty) = &mut input.ty {
+            self.walk_ty(ty);
+          }
+
+          self.bind(&input.pattern);
+        }
+
+        self.walk_block(&mut lambda.body);
+        self.locals.truncate(depth);
+      }
+      _ => {}
+    }
+  }
+
+  fn walk_exprs(&mut self, exprs: &mut [PBox<Expr>]) {
+    for expr in exprs {
+      self.walk_expr(expr);
+    }
+  }
+}
+
+fn add_report_ambiguous_name_error(
+  reporter: &Reporter,
+  name: &str,
+  modules: &[String],
+  span: Span,
+) {
+  let source_id = reporter.source(span);
+  let code = reporter.code(source_id);
+  let path_name = reporter.path(span);
+
+  reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path_name.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(25)) // FIXME #2
+    .with_message(ReportMessage::AmbiguousName(name.to_string()))
+    .with_label(
+      Label::new(
+        LabelKind::Error,
+        (path_name.display().to_string(), span.into()),
+      )
+      .with_message(LabelMessage::AmbiguousName(modules.join(" and "))),
+    ),
+    path_name.display().to_string(),
+    code,
+  )
+}
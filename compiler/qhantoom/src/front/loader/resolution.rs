@@ -0,0 +1,298 @@
+use crate::front::parser::ast::{Expr, PathView, PathViewKind};
+use crate::util::constant::{PACKAGE_ROOT, SOURCE_EXTENSION};
+use crate::util::span::Span;
+
+use std::path::{Path, PathBuf};
+
+// a segment of a path view, as it is written
+#[derive(Clone, Debug)]
+pub struct Segment {
+  pub name: String,
+  pub span: Span,
+}
+
+impl From<&Expr> for Segment {
+  fn from(expr: &Expr) -> Self {
+    Self {
+      name: expr.to_string(),
+      span: expr.span,
+    }
+  }
+}
+
+// every path that a path view names, `a::(b, c)` names `a::b` and `a::c`
+pub fn paths(path_view: &PathView) -> Vec<Vec<Segment>> {
+  match &path_view.kind {
+    PathViewKind::Identifier(name) => vec![vec![Segment::from(&**name)]],
+    PathViewKind::Path(path_view, names) => paths(path_view)
+      .into_iter()
+      .flat_map(|path| {
+        names.iter().map(move |name| {
+          let mut path = path.to_vec();
+
+          path.push(Segment::from(&**name));
+          path
+        })
+      })
+      .collect(),
+  }
+}
+
+pub fn fmt_path(path: &[Segment]) -> String {
+  path
+    .iter()
+    .map(|segment| segment.name.as_str())
+    .collect::<Vec<_>>()
+    .join("::")
+}
+
+// what a path resolves to: the file of a module and, when the last segment
+// is not a module, the segment that names an item of that module
+#[derive(Debug)]
+pub struct Target {
+  pub file: PathBuf,
+  pub item: Option<Segment>,
+}
+
+// the segment that cannot be resolved and why
+pub type ResolutionError = (Span, String);
+
+// every segment but the last names a directory, the last one names a file.
+// the segment before the last can also name a file, the last one is then an
+// item of that module
+pub struct Resolver<'a> {
+  // the directory of the entry file, to which `pack` refers
+  pub root: &'a Path,
+  pub search_paths: &'a [PathBuf],
+}
+
+impl<'a> Resolver<'a> {
+  pub fn resolve(
+    &self,
+    path: &[Segment],
+    importer: &Path,
+  ) -> Result<Target, ResolutionError> {
+    let (base, path) = match path {
+      [first] if first.name == PACKAGE_ROOT => {
+        return Err((first.span, "the package itself cannot be loaded".into()))
+      }
+      [first, rest @ ..] if first.name == PACKAGE_ROOT => {
+        (self.root.to_path_buf(), rest)
+      }
+      [first, ..] => {
+        let mut bases = vec![importer.to_path_buf()];
+
+        bases.extend(self.search_paths.iter().cloned());
+
+        match bases.iter().find(|base| names_module(base, first)) {
+          Some(base) => (base.to_path_buf(), path),
+          None => return Err((first.span, not_found(first, &bases))),
+        }
+      }
+      [] => unreachable!(),
+    };
+
+    resolve_in(&base, path)
+  }
+}
+
+fn resolve_in(
+  base: &Path,
+  path: &[Segment],
+) -> Result<Target, ResolutionError> {
+  let mut directory = base.to_path_buf();
+
+  for (index, segment) in path.iter().enumerate() {
+    let file = directory.join(file_name(segment));
+
+    match &path[index + 1..] {
+      [] if file.is_file() => return Ok(Target { file, item: None }),
+      [item] if file.is_file() && !is_module(&directory, segment, item) => {
+        return Ok(Target {
+          file,
+          item: Some(item.clone()),
+        })
+      }
+      [_, ..] if directory.join(&segment.name).is_dir() => {
+        directory.push(&segment.name)
+      }
+      _ => return Err((segment.span, not_found(segment, &[directory]))),
+    }
+  }
+
+  unreachable!()
+}
+
+// the segment names a file or a directory of the base directory
+fn names_module(base: &Path, segment: &Segment) -> bool {
+  base.join(file_name(segment)).is_file() || base.join(&segment.name).is_dir()
+}
+
+// `a::b` names the module `b` rather than the item `b` of `a` when both exist
+fn is_module(directory: &Path, segment: &Segment, item: &Segment) -> bool {
+  directory
+    .join(&segment.name)
+    .join(file_name(item))
+    .is_file()
+}
+
+fn file_name(segment: &Segment) -> String {
+  format!("{}.{SOURCE_EXTENSION}", segment.name)
+}
+
+fn not_found(segment: &Segment, directories: &[PathBuf]) -> String {
+  let directories = directories
+    .iter()
+    .map(|directory| match directory.as_os_str().is_empty() {
+      true => "`.`".to_string(),
+      false => format!("`{}`", directory.display()),
+    })
+    .collect::<Vec<_>>()
+    .join(", ");
+
+  format!("there is no module `{}` in {directories}", segment.name)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::fs;
+
+  // a directory tree of empty modules, removed once the test is over
+  struct Tree(PathBuf);
+
+  impl Tree {
+    fn new(name: &str, files: &[&str]) -> Self {
+      let root = std::env::temp_dir()
+        .join(format!("qhantoom-resolution-{}-{name}", std::process::id()));
+
+      for file in files {
+        let file = root.join(file);
+
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(file, "").unwrap();
+      }
+
+      Self(root)
+    }
+  }
+
+  impl Drop for Tree {
+    fn drop(&mut self) {
+      let _ = fs::remove_dir_all(&self.0);
+    }
+  }
+
+  fn path(path: &str) -> Vec<Segment> {
+    path
+      .split("::")
+      .map(|name| Segment {
+        name: name.to_string(),
+        span: Span::ZERO,
+      })
+      .collect()
+  }
+
+  fn resolve(
+    tree: &Tree,
+    search_paths: &[PathBuf],
+    importer: &str,
+    module: &str,
+  ) -> Result<(PathBuf, Option<String>), String> {
+    let resolver = Resolver {
+      root: &tree.0.join("app"),
+      search_paths,
+    };
+
+    resolver
+      .resolve(&path(module), &tree.0.join(importer))
+      .map(|target| {
+        let file = target.file.strip_prefix(&tree.0).unwrap().to_path_buf();
+
+        (file, target.item.map(|item| item.name))
+      })
+      .map_err(|(_, message)| message)
+  }
+
+  #[test]
+  fn pack_starts_at_the_entry_directory() {
+    let tree = Tree::new("pack", &["app/geo/rect.qh", "app/sub/geo/rect.qh"]);
+    let target = resolve(&tree, &[], "app/sub", "pack::geo::rect");
+
+    assert_eq!(target, Ok(("app/geo/rect.qh".into(), None)));
+  }
+
+  #[test]
+  fn other_paths_start_at_the_importer() {
+    let tree =
+      Tree::new("relative", &["app/geo/rect.qh", "app/sub/geo/rect.qh"]);
+    let target = resolve(&tree, &[], "app/sub", "geo::rect");
+
+    assert_eq!(target, Ok(("app/sub/geo/rect.qh".into(), None)));
+  }
+
+  #[test]
+  fn search_paths_come_after_the_importer() {
+    let tree =
+      Tree::new("search", &["lib/math.qh", "lib/io/fs.qh", "app/io/fs.qh"]);
+    let search_paths = [tree.0.join("lib")];
+
+    assert_eq!(
+      resolve(&tree, &search_paths, "app", "math"),
+      Ok(("lib/math.qh".into(), None))
+    );
+    assert_eq!(
+      resolve(&tree, &search_paths, "app", "io::fs"),
+      Ok(("app/io/fs.qh".into(), None))
+    );
+  }
+
+  #[test]
+  fn the_last_segment_can_name_an_item() {
+    let tree = Tree::new("item", &["app/geo.qh"]);
+    let target = resolve(&tree, &[], "app", "pack::geo::area");
+
+    assert_eq!(target, Ok(("app/geo.qh".into(), Some("area".into()))));
+  }
+
+  #[test]
+  fn a_module_is_preferred_to_an_item() {
+    let tree = Tree::new("module", &["app/geo.qh", "app/geo/area.qh"]);
+    let target = resolve(&tree, &[], "app", "geo::area");
+
+    assert_eq!(target, Ok(("app/geo/area.qh".into(), None)));
+  }
+
+  #[test]
+  fn missing_modules_are_errors() {
+    let tree = Tree::new("missing", &["app/geo/rect.qh"]);
+    let search_paths = [tree.0.join("lib")];
+    let error =
+      |module| resolve(&tree, &search_paths, "app", module).unwrap_err();
+
+    assert_eq!(
+      error("shapes::rect"),
+      format!(
+        "there is no module `shapes` in `{}`, `{}`",
+        tree.0.join("app").display(),
+        tree.0.join("lib").display()
+      )
+    );
+    assert_eq!(
+      error("pack::geo::circle"),
+      format!(
+        "there is no module `circle` in `{}`",
+        tree.0.join("app/geo").display()
+      )
+    );
+    assert_eq!(
+      error("geo::rect::area::size"),
+      format!(
+        "there is no module `rect` in `{}`",
+        tree.0.join("app/geo").display()
+      )
+    );
+    assert_eq!(error("pack"), "the package itself cannot be loaded");
+  }
+}
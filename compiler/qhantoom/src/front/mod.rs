@@ -1,3 +1,7 @@
 pub mod analyzer;
+pub mod expander;
 pub mod grammar;
+pub mod lexer;
+pub mod loader;
+pub mod monomorphizer;
 pub mod parser;
@@ -0,0 +1,3 @@
+mod monomorphization;
+
+pub use monomorphization::monomorphize;
@@ -0,0 +1,715 @@
+use crate::front::parser::ast::*;
+use crate::util::error::{Label, LabelKind, LabelMessage};
+
+use crate::util::error::{
+  Report, ReportCode, ReportKind, ReportMessage, ReportOffset, Reporter,
+};
+
+use crate::util::span::Span;
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+// the number of instances that can be nested in the instance of an item
+const INSTANCE_LIMIT: usize = 64;
+
+// an instance is put after the last item that it uses: the position of that
+// item, then the data types before the functions, a data type after the ones
+// of its type arguments and an instance after the ones of the generic items
+// declared before its own
+type Place = (usize, bool, usize, usize);
+
+struct Pending {
+  name: String,
+  generic: String,
+  ty_args: Vec<PBox<Ty>>,
+  depth: usize,
+  span: Span,
+}
+
+struct Monomorphizer<'a> {
+  reporter: &'a Reporter,
+  // the generic functions and data types with their position in the program
+  funs: HashMap<String, (usize, PBox<Fun>)>,
+  datas: HashMap<String, (usize, PBox<Data>)>,
+  aliases: HashMap<String, PBox<Ty>>,
+  behaviors: HashMap<String, PBox<Behavior>>,
+  // the position and the rank of every data type and enum, the rank of an
+  // instance is above the ones of its type arguments
+  places: HashMap<String, (usize, usize)>,
+  names: HashSet<String>,
+  pending: Vec<Pending>,
+  instances: Vec<(Place, PBox<Item>)>,
+  // the type parameters of the instance being walked and their arguments
+  ty_params: Vec<String>,
+  ty_args: Vec<PBox<Ty>>,
+  depth: usize,
+}
+
+// replaces every generic function and data type by one instance for each list
+// of type arguments with which it is used, so that the code generation only
+// sees concrete types. an instance is named after its type arguments
+pub fn monomorphize(program: &mut Program) {
+  let items = std::mem::take(&mut program.items);
+
+  let mut monomorphizer = Monomorphizer {
+    reporter: &program.reporter,
+    funs: HashMap::new(),
+    datas: HashMap::new(),
+    aliases: HashMap::new(),
+    behaviors: HashMap::new(),
+    places: HashMap::new(),
+    names: HashSet::new(),
+    pending: vec![],
+    instances: vec![],
+    ty_params: vec![],
+    ty_args: vec![],
+    depth: 0,
+  };
+
+  let slots = monomorphizer.declare_items(items);
+
+  let mut slots = slots
+    .into_iter()
+    .map(|slot| {
+      slot.map(|mut item| {
+        monomorphizer.walk_item(&mut item);
+        item
+      })
+    })
+    .collect::<Vec<_>>();
+
+  while let Some(pending) = monomorphizer.pending.pop() {
+    monomorphizer.instantiate(pending);
+  }
+
+  let mut instances = std::mem::take(&mut monomorphizer.instances);
+
+  instances.sort_by_key(|(place, _)| *place);
+
+  let mut instances = instances.into_iter().peekable();
+
+  for (x, slot) in slots.iter_mut().enumerate() {
+    program.items.extend(slot.take());
+
+    while let Some((_, instance)) =
+      instances.next_if(|((position, ..), _)| *position == x)
+    {
+      program.items.push(instance);
+    }
+  }
+}
+
+impl<'a> Monomorphizer<'a> {
+  // the generic items are set apart, the slot of each one is left empty
+  fn declare_items(
+    &mut self,
+    items: Vec<PBox<Item>>,
+  ) -> Vec<Option<PBox<Item>>> {
+    for (x, item) in items.iter().enumerate() {
+      match &item.kind {
+        ItemKind::Data(data) => {
+          self.places.insert(data.name.to_string(), (x, 0));
+        }
+        ItemKind::Enum(enumeration) => {
+          self.places.insert(enumeration.name.to_string(), (x, 0));
+        }
+        ItemKind::Type(alias) => self.declare_ty_alias(alias),
+        ItemKind::Behavior(behavior) => {
+          self
+            .behaviors
+            .insert(behavior.name.to_string(), behavior.to_owned());
+        }
+        _ => {}
+      }
+    }
+
+    items
+      .into_iter()
+      .enumerate()
+      .map(|(x, item)| match &item.kind {
+        ItemKind::Fun(fun) if !fun.prototype.ty_params.is_empty() => {
+          let name = fun.prototype.name.to_string();
+
+          self.funs.insert(name, (x, fun.to_owned()));
+          None
+        }
+        ItemKind::Data(data) if !data.ty_params.is_empty() => {
+          self
+            .datas
+            .insert(data.name.to_string(), (x, data.to_owned()));
+          None
+        }
+        _ => Some(item),
+      })
+      .collect()
+  }
+
+  fn declare_ty_alias(&mut self, alias: &TyAlias) {
+    for (name, ty) in alias.tys() {
+      self.aliases.insert(name, ty.to_owned());
+    }
+  }
+
+  fn instantiate(&mut self, pending: Pending) {
+    if pending.depth > INSTANCE_LIMIT {
+      self
+        .raise_report_instance_recursion_error(&pending.generic, pending.span);
+    }
+
+    let place = self.place_of_tys(&pending.ty_args);
+
+    let (place, kind, span) = match self.funs.get(&pending.generic) {
+      Some((x, fun)) => {
+        let (x, mut fun) = (*x, fun.to_owned());
+        let ty_params = names(&fun.prototype.ty_params);
+
+        fun.prototype.name.kind = ExprKind::Identifier(pending.name);
+        fun.prototype.ty_params = vec![];
+
+        self.with_ty_args(ty_params, pending.ty_args, pending.depth, |this| {
+          this.walk_fun(&mut fun)
+        });
+
+        let span = fun.span;
+
+        ((place.0.max(x), true, 0, x), ItemKind::Fun(fun), span)
+      }
+      None => {
+        let (x, mut data) = self.datas[&pending.generic].to_owned();
+        let ty_params = names(&data.ty_params);
+
+        data.name.kind = ExprKind::Identifier(pending.name.to_owned());
+        data.ty_params = vec![];
+
+        self.with_ty_args(ty_params, pending.ty_args, pending.depth, |this| {
+          this.walk_data(&mut data)
+        });
+
+        let rank = self.places[&pending.name].1;
+        let span = data.span;
+
+        ((place.0.max(x), false, rank, x), ItemKind::Data(data), span)
+      }
+    };
+
+    self.instances.push((place, pbox(Item::new(kind, span))));
+  }
+
+  fn with_ty_args(
+    &mut self,
+    ty_params: Vec<String>,
+    ty_args: Vec<PBox<Ty>>,
+    depth: usize,
+    walk: impl FnOnce(&mut Self),
+  ) {
+    let ty_params = std::mem::replace(&mut self.ty_params, ty_params);
+    let ty_args = std::mem::replace(&mut self.ty_args, ty_args);
+    let depth = std::mem::replace(&mut self.depth, depth);
+
+    walk(self);
+
+    self.ty_params = ty_params;
+    self.ty_args = ty_args;
+    self.depth = depth;
+  }
+
+  // the name of the instance of a generic item, it is created the first time
+  // that it is used
+  fn instance(
+    &mut self,
+    name: &str,
+    ty_args: Vec<PBox<Ty>>,
+    span: Span,
+  ) -> String {
+    let instance = format!("{name}<{}>", Sep(&ty_args, ", "));
+
+    if !self.names.insert(instance.to_owned()) {
+      return instance;
+    }
+
+    if let Some((x, _)) = self.datas.get(name) {
+      let (position, rank) = self.place_of_tys(&ty_args);
+
+      self
+        .places
+        .insert(instance.to_owned(), (position.max(*x), rank + 1));
+    }
+
+    self.pending.push(Pending {
+      name: instance.to_owned(),
+      generic: name.to_string(),
+      ty_args,
+      depth: self.depth + 1,
+      span,
+    });
+
+    instance
+  }
+
+  fn place_of_tys(&self, tys: &[PBox<Ty>]) -> (usize, usize) {
+    tys.iter().map(|ty| self.place_of(ty)).fold(
+      (0, 0),
+      |(position, rank), (ty_position, ty_rank)| {
+        (position.max(ty_position), rank.max(ty_rank))
+      },
+    )
+  }
+
+  fn place_of(&self, ty: &Ty) -> (usize, usize) {
+    match &ty.kind {
+      TyKind::Data(name) | TyKind::Enum(name) => {
+        self.places.get(name).copied().unwrap_or_default()
+      }
+      TyKind::Fn(input_tys, return_ty) => {
+        let (position, rank) = self.place_of_tys(input_tys);
+        let (return_position, return_rank) = self.place_of(return_ty);
+
+        (position.max(return_position), rank.max(return_rank))
+      }
+      TyKind::Array(ty, _) | TyKind::Option(ty) => self.place_of(ty),
+      TyKind::Result(ok_ty, err_ty) => {
+        self.place_of_tys(&[ok_ty.to_owned(), err_ty.to_owned()])
+      }
+      _ => (0, 0),
+    }
+  }
+
+  // replaces the type parameters by their arguments, the aliases by the types
+  // that they name and the generic data types by their instances
+  fn concrete(&mut self, ty: &Ty) -> PBox<Ty> {
+    let kind = match &ty.kind {
+      TyKind::Param(name) | TyKind::Data(name)
+        if self.ty_params.contains(name) =>
+      {
+        let x = self.ty_params.iter().position(|x| x == name).unwrap();
+
+        self.ty_args[x].kind.to_owned()
+      }
+      TyKind::Data(name) if self.aliases.contains_key(name) => {
+        let alias_ty = self.aliases[name].to_owned();
+
+        self.concrete(&alias_ty).kind.to_owned()
+      }
+      TyKind::Fn(input_tys, return_ty) => TyKind::Fn(
+        input_tys.iter().map(|ty| self.concrete(ty)).collect(),
+        self.concrete(return_ty),
+      ),
+      TyKind::Array(element_ty, len) => {
+        TyKind::Array(self.concrete(element_ty), *len)
+      }
+      TyKind::Option(some_ty) => TyKind::Option(self.concrete(some_ty)),
+      TyKind::Result(ok_ty, err_ty) => {
+        TyKind::Result(self.concrete(ok_ty), self.concrete(err_ty))
+      }
+      TyKind::App(name, tys) => {
+        let tys = tys.iter().map(|ty| self.concrete(ty)).collect();
+
+        TyKind::Data(self.instance(name, tys, ty.span))
+      }
+      kind => kind.to_owned(),
+    };
+
+    Ty::new(kind, ty.span).into()
+  }
+
+  fn walk_item(&mut self, item: &mut Item) {
+    match &mut item.kind {
+      ItemKind::Ext(ext) => {
+        self.walk_prototype(&mut ext.prototype);
+
+        if let Some(body) = &mut ext.body {
+          self.walk_block(body);
+        }
+      }
+      ItemKind::Val(decl) => self.walk_decl(decl),
+      ItemKind::Fun(fun) => self.walk_fun(fun),
+      ItemKind::Data(data) => self.walk_data(data),
+      ItemKind::Enum(enumeration) => {
+        for variant in &mut enumeration.variants {
+          for ty in &mut variant.tys {
+            *ty = self.concrete(ty);
+          }
+        }
+      }
+      ItemKind::Impl(imp) => {
+        imp.ty = self.concrete(&imp.ty);
+
+        for fun in &mut imp.funs {
+          self.walk_fun(fun);
+        }
+
+        self.walk_default_methods(imp);
+      }
+      ItemKind::Type(alias) => match &mut alias.kind {
+        TyAliasKind::Single(ty) => *ty = self.concrete(ty),
+        TyAliasKind::Group(fields) => {
+          for field in fields {
+            field.ty = self.concrete(&field.ty);
+          }
+        }
+      },
+      ItemKind::Unit(unit) => {
+        for item in &mut unit.items {
+          let (UnitItem::Test(fun) | UnitItem::Mock(fun)) = item;
+
+          self.walk_fun(fun);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  // the default methods that an impl does not replace are copied in it, with
+  // `Self` as the type of the impl
+  fn walk_default_methods(&mut self, imp: &mut Impl) {
+    let Some(behavior) = imp
+      .behavior
+      .as_ref()
+      .and_then(|behavior| self.behaviors.get(&behavior.to_string()))
+      .cloned()
+    else {
+      return;
+    };
+
+    let mut ty_params = self.ty_params.to_owned();
+    let mut ty_args = self.ty_args.to_owned();
+
+    ty_params.push("Self".into());
+    ty_args.push(imp.ty.to_owned());
+
+    for method in &behavior.methods {
+      let Some(body) = &method.body else { continue };
+      let name = method.prototype.name.to_string();
+
+      if imp
+        .funs
+        .iter()
+        .any(|fun| fun.prototype.name.to_string() == name)
+      {
+        continue;
+      }
+
+      let mut fun = Fun::new(
+        behavior.public.to_owned(),
+        method.prototype.to_owned(),
+        body.to_owned(),
+        method.span,
+      );
+
+      self.with_ty_args(
+        ty_params.to_owned(),
+        ty_args.to_owned(),
+        self.depth,
+        |this| this.walk_fun(&mut fun),
+      );
+
+      imp.funs.push(pbox(fun));
+    }
+  }
+
+  fn walk_fun(&mut self, fun: &mut Fun) {
+    self.walk_prototype(&mut fun.prototype);
+    self.walk_block(&mut fun.body);
+  }
+
+  fn walk_prototype(&mut self, prototype: &mut Prototype) {
+    for input in &mut prototype.inputs {
+      input.ty = self.concrete(&input.ty);
+    }
+
+    if let ReturnTy::Ty(ty) = &mut prototype.output {
+      *ty = self.concrete(ty);
+    }
+  }
+
+  fn walk_data(&mut self, data: &mut Data) {
+    for field in &mut data.fields {
+      field.ty = self.concrete(&field.ty);
+    }
+  }
+
+  fn walk_block(&mut self, block: &mut Block) {
+    for stmt in &block.stmts {
+      if let StmtKind::Item(item) = &stmt.kind {
+        if let ItemKind::Type(alias) = &item.kind {
+          self.declare_ty_alias(alias);
+        }
+      }
+    }
+
+    for stmt in &mut block.stmts {
+      match &mut stmt.kind {
+        StmtKind::Item(item) => self.walk_item(item),
+        StmtKind::Decl(decl) => self.walk_decl(decl),
+        StmtKind::Expr(expr) => self.walk_expr(expr),
+      }
+    }
+  }
+
+  fn walk_decl(&mut self, decl: &mut Decl) {
+    if let Some(ty) = &mut decl.ty {
+      *ty = self.concrete(ty);
+    }
+
+    // the declaration may be shared with the other instances of its item
+    let inferred_ty = decl.inferred_ty.borrow().to_owned();
+
+    if let Some(ty) = inferred_ty {
+      decl.inferred_ty = Rc::new(RefCell::new(Some(self.concrete(&ty))));
+    }
+
+    self.walk_expr(&mut decl.value);
+  }
+
+  fn walk_expr(&mut self, expr: &mut Expr) {
+    // the expression names a generic item, it now names the instance
+    let ty_args = expr.ty_args.borrow().to_owned();
+
+    if !ty_args.is_empty() {
+      let ty_args = ty_args.iter().map(|ty| self.concrete(ty)).collect();
+      let name = self.instance(&expr.to_string(), ty_args, expr.span);
+
+      expr.kind = ExprKind::Identifier(name);
+      expr.ty_args = TyArgs::default();
+    }
+
+    match &mut expr.kind {
+      ExprKind::Call(callee, args) => {
+        self.walk_expr(callee);
+        self.walk_exprs(args);
+      }
+      ExprKind::UnOp(_, rhs) => self.walk_expr(rhs),
+      // the rhs of `as` is a type, it may be a type parameter
+      ExprKind::BinOp(lhs, op, rhs) if matches!(op.node, BinOpKind::As) => {
+        self.walk_expr(lhs);
+
+        let ty = self.concrete(&Ty::from(&**rhs));
+
+        if let ExprKind::Identifier(name) = &mut rhs.kind {
+          *name = ty.to_string();
+        }
+      }
+      ExprKind::BinOp(lhs, _, rhs)
+      | ExprKind::Assign(lhs, _, rhs)
+      | ExprKind::AssignOp(lhs, _, rhs) => {
+        self.walk_expr(lhs);
+        self.walk_expr(rhs);
+      }
+      ExprKind::Return(Some(value)) | ExprKind::Break(Some(value)) => {
+        self.walk_expr(value)
+      }
+      ExprKind::Block(block) | ExprKind::Loop(block) => self.walk_block(block),
+      ExprKind::While(condition, block) => {
+        self.walk_expr(condition);
+        self.walk_block(block);
+      }
+      ExprKind::When(condition, consequence, alternative) => {
+        self.walk_expr(condition);
+        self.walk_expr(consequence);
+        self.walk_expr(alternative);
+      }
+      ExprKind::IfElse(condition, consequence, alternative) => {
+        self.walk_expr(condition);
+        self.walk_expr(consequence);
+
+        if let Some(alternative) = alternative {
+          self.walk_expr(alternative);
+        }
+      }
+      ExprKind::Field(lhs, _) | ExprKind::Is(lhs, _) | ExprKind::Try(lhs) => {
+        self.walk_expr(lhs)
+      }
+      ExprKind::Struct(name, fields) => {
+        self.walk_expr(name);
+
+        for field in fields {
+          if let Some(value) = &mut field.value {
+            self.walk_expr(value);
+          }
+        }
+      }
+      ExprKind::Match(scrutinee, arms) => {
+        self.walk_expr(scrutinee);
+
+        for arm in arms {
+          self.walk_expr(&mut arm.body);
+        }
+      }
+      ExprKind::For(_, range, step, block) => {
+        self.walk_expr(range);
+
+        if let Some(step) = step {
+          self.walk_expr(step);
+        }
+
+        self.walk_block(block);
+      }
+      ExprKind::ForEach(iterable, block) => {
+        self.walk_expr(iterable);
+        self.walk_block(block);
+      }
+      ExprKind::Array(elements) => self.walk_exprs(elements),
+      ExprKind::Lambda(lambda) => {
+        for input in &mut lambda.inputs {
+          if let Some(ty) = &mut input.ty {
+            *ty = self.concrete(ty);
+          }
+        }
+
+        self.walk_block(&mut lambda.body);
+      }
+      _ => {}
+    }
+  }
+
+  fn walk_exprs(&mut self, exprs: &mut [PBox<Expr>]) {
+    for expr in exprs {
+      self.walk_expr(expr);
+    }
+  }
+
+  fn raise_report_instance_recursion_error(&self, name: &str, span: Span) -> ! {
+    let source_id = self.reporter.source(span);
+    let code = self.reporter.code(source_id);
+    let path = self.reporter.path(span);
+
+    self.reporter.raise(
+      Report::new(
+        ReportKind::Error,
+        path.display().to_string(),
+        ReportOffset(span.lo),
+      )
+      .with_code(ReportCode(20)) // FIXME #2
+      .with_message(ReportMessage::InstanceRecursion(name.to_string()))
+      .with_label(
+        Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+          .with_message(LabelMessage::InstanceRecursion),
+      ),
+      path.display().to_string(),
+      code,
+    )
+  }
+}
+
+fn names(ty_params: &[PBox<TyParam>]) -> Vec<String> {
+  ty_params
+    .iter()
+    .map(|ty_param| ty_param.name.to_string())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::front::analyzer::analyze;
+  use crate::front::parser::parse;
+
+  // the items of the monomorphized program, by name
+  fn monomorphized(name: &str, source: &str) -> HashMap<String, String> {
+    let path = std::env::temp_dir().join(format!(
+      "qhantoom-monomorphization-{}-{name}.qh",
+      std::process::id()
+    ));
+
+    std::fs::write(&path, source).unwrap();
+
+    let (mut program, _) = parse(&path);
+
+    std::fs::remove_file(&path).unwrap();
+    analyze(&program).unwrap();
+    monomorphize(&mut program);
+
+    program
+      .items
+      .iter()
+      .filter_map(|item| match &item.kind {
+        ItemKind::Fun(fun) => Some((fun.prototype.name.to_string(), item)),
+        ItemKind::Data(data) => Some((data.name.to_string(), item)),
+        _ => None,
+      })
+      .map(|(name, item)| (name, item.to_string()))
+      .collect()
+  }
+
+  const SOURCE: &str = r#"
+data Pair<A, B> {
+  first: A,
+  second: B,
+}
+
+fun id<T>(x: T): T {
+  return x;
+}
+
+fun twice<T>(x: T): T {
+  return id(id(x));
+}
+
+fun pair<A, B>(first: A, second: B): Pair<A, B> {
+  Pair { first: first, second: second }
+}
+
+fun main(): uint {
+  imu p := pair(1, true);
+  imu q := pair(false, 2);
+  twice(3) + id(4) + p.first + q.second + twice(true) as uint
+}
+"#;
+
+  #[test]
+  fn each_list_of_type_arguments_has_its_instance() {
+    let items = monomorphized("instances", SOURCE);
+    let mut names = items.keys().map(String::as_str).collect::<Vec<_>>();
+
+    names.sort();
+
+    assert_eq!(
+      names,
+      vec![
+        "Pair<bool, uint>",
+        "Pair<uint, bool>",
+        "id<bool>",
+        "id<uint>",
+        "main",
+        "pair<bool, uint>",
+        "pair<uint, bool>",
+        "twice<bool>",
+        "twice<uint>",
+      ]
+    );
+    assert_eq!(
+      items["Pair<uint, bool>"],
+      "data Pair<uint, bool> { first: uint, second: bool }"
+    );
+    assert_eq!(
+      items["Pair<bool, uint>"],
+      "data Pair<bool, uint> { first: bool, second: uint }"
+    );
+  }
+
+  #[test]
+  fn the_uses_name_the_instances() {
+    let items = monomorphized("uses", SOURCE);
+
+    assert!(items["main"]
+      .contains("imu p: Pair<uint, bool> = pair<uint, bool>(1, true);"));
+    assert!(items["main"]
+      .contains("imu q: Pair<bool, uint> = pair<bool, uint>(false, 2);"));
+    assert!(items["main"].contains("(twice<bool>(true) as uint)"));
+    assert!(items["pair<bool, uint>"]
+      .contains("(first: bool, second: uint) : Pair<bool, uint> {"));
+    assert!(items["pair<bool, uint>"]
+      .contains("Pair<bool, uint> { first: first, second: second }"));
+  }
+
+  #[test]
+  fn nested_generic_calls_are_substituted() {
+    let items = monomorphized("nested", SOURCE);
+
+    assert!(items["twice<uint>"].contains("return id<uint>(id<uint>(x));"));
+    assert!(items["twice<bool>"].contains("return id<bool>(id<bool>(x));"));
+  }
+}
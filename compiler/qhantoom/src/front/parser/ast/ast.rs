@@ -1,9 +1,15 @@
 use super::pbox::{pbox, PBox};
-use super::ty::{AsTy, Ty};
+use super::ty::{AsTy, Ty, TyKind};
 
+use crate::front::lexer::Token;
 use crate::util::error::Reporter;
 use crate::util::span::{Span, Spanned};
 
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+
 #[derive(Clone, Debug)]
 pub enum Public {
   Yes(Span),
@@ -16,28 +22,113 @@ pub enum Async {
   No,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Mutability {
   Not,
   Yes,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct BindingAnnotation(pub Mutability);
 
 #[derive(Debug)]
 pub struct Program {
+  pub attrs: Vec<Attribute>,
   pub items: Vec<PBox<Item>>,
+  // the modules loaded by the entry file, their items are in `items`
+  pub modules: Vec<Module>,
+  // the modules named by the `load` and `bind` items of each source file,
+  // by source id
+  pub imports: HashMap<u32, Vec<Import>>,
   pub reporter: Reporter,
 }
 
 impl Program {
-  pub fn new(items: Vec<PBox<Item>>, reporter: Reporter) -> Self {
-    Self { items, reporter }
+  pub fn new(
+    attrs: Vec<Attribute>,
+    items: Vec<PBox<Item>>,
+    reporter: Reporter,
+  ) -> Self {
+    Self {
+      attrs,
+      items,
+      modules: vec![],
+      imports: HashMap::new(),
+      reporter,
+    }
   }
 }
 
-#[derive(Debug)]
+// a source file loaded by `load` and the modules that it loads in turn
+#[derive(Clone, Debug)]
+pub struct Module {
+  pub name: String,
+  pub path: PathBuf,
+  pub source_id: u32,
+  pub modules: Vec<Module>,
+}
+
+impl Module {
+  pub fn new(
+    name: String,
+    path: PathBuf,
+    source_id: u32,
+    modules: Vec<Module>,
+  ) -> Self {
+    Self {
+      name,
+      path,
+      source_id,
+      modules,
+    }
+  }
+}
+
+// a module named by a `load` or a `bind`, `item` is set when only one of its
+// items is named. the items of a module that is bound with `pub bind` can
+// be used by the modules that import the binding one
+#[derive(Clone, Debug)]
+pub struct Import {
+  pub source_id: u32,
+  // the path of the module as the importer writes it
+  pub path: String,
+  pub item: Option<String>,
+  pub public: bool,
+}
+
+// `#> name: args.` applies to what follows it, `#!> name: args.` to the file
+// in which it is written
+#[derive(Clone, Debug)]
+pub struct Attribute {
+  pub style: AttrStyle,
+  pub name: PBox<Expr>,
+  pub args: Vec<PBox<Expr>>,
+  pub span: Span,
+}
+
+impl Attribute {
+  pub fn new(
+    style: AttrStyle,
+    name: PBox<Expr>,
+    args: Vec<PBox<Expr>>,
+    span: Span,
+  ) -> Self {
+    Self {
+      style,
+      name,
+      args,
+      span,
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub enum AttrStyle {
+  Outer,
+  Inner,
+}
+
+#[derive(Clone, Debug)]
 pub struct Load {
   pub path_view: PathView,
   pub span: Span,
@@ -49,7 +140,26 @@ impl Load {
   }
 }
 
-#[derive(Debug)]
+// `bind path;` loads the module like `load`, a `pub bind` also lets the
+// modules that load this one use what it names
+#[derive(Clone, Debug)]
+pub struct Bind {
+  pub public: Public,
+  pub path_view: PathView,
+  pub span: Span,
+}
+
+impl Bind {
+  pub fn new(public: Public, path_view: PathView, span: Span) -> Self {
+    Self {
+      public,
+      path_view,
+      span,
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
 pub struct PathView {
   pub kind: PathViewKind,
   pub span: Span,
@@ -61,13 +171,13 @@ impl PathView {
   }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum PathViewKind {
   Identifier(PBox<Expr>),
   Path(PBox<PathView>, Vec<PBox<Expr>>),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Pattern {
   pub kind: PatternKind,
   pub span: Span,
@@ -77,40 +187,222 @@ impl Pattern {
   pub fn new(kind: PatternKind, span: Span) -> Self {
     Self { kind, span }
   }
+
+  pub fn is_irrefutable(&self) -> bool {
+    matches!(
+      self.kind,
+      PatternKind::Underscore | PatternKind::Identifier(..)
+    )
+  }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum PatternKind {
   Underscore,
   Identifier(BindingAnnotation, PBox<Expr>),
   Lit(PBox<Expr>),
+  Receiver,
+  Variant(PBox<Expr>, Vec<Pattern>),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Item {
   pub kind: ItemKind,
+  pub attrs: Vec<Attribute>,
   pub span: Span,
 }
 
 impl Item {
   pub fn new(kind: ItemKind, span: Span) -> Self {
-    Self { kind, span }
+    Self {
+      kind,
+      attrs: vec![],
+      span,
+    }
+  }
+
+  // the attributes of a function, an external function or a value are kept
+  // by the node itself, so that they are also found where it is not an item
+  pub fn with_attrs(mut self, attrs: Vec<Attribute>) -> Self {
+    match &mut self.kind {
+      ItemKind::Fun(fun) => fun.attrs = attrs,
+      ItemKind::Ext(ext) => ext.attrs = attrs,
+      ItemKind::Val(decl) => decl.attrs = attrs,
+      _ => self.attrs = attrs,
+    }
+
+    self
+  }
+
+  pub fn attrs(&self) -> &[Attribute] {
+    match &self.kind {
+      ItemKind::Fun(fun) => &fun.attrs,
+      ItemKind::Ext(ext) => &ext.attrs,
+      ItemKind::Val(decl) => &decl.attrs,
+      _ => &self.attrs,
+    }
+  }
+
+  // the name that the item declares, a load, an impl or a unit has none
+  pub fn name(&self) -> Option<String> {
+    match &self.kind {
+      ItemKind::Ext(ext) => Some(ext.prototype.name.to_string()),
+      ItemKind::Val(decl) => Some(decl.pattern.to_string()),
+      ItemKind::Fun(fun) => Some(fun.prototype.name.to_string()),
+      ItemKind::Data(data) => Some(data.name.to_string()),
+      ItemKind::Enum(enumeration) => Some(enumeration.name.to_string()),
+      ItemKind::Type(alias) => Some(alias.name.to_string()),
+      ItemKind::Macro(mac) => Some(mac.name.to_string()),
+      ItemKind::Behavior(behavior) => Some(behavior.name.to_string()),
+      ItemKind::Load(_)
+      | ItemKind::Bind(_)
+      | ItemKind::Impl(_)
+      | ItemKind::MacroCall(_)
+      | ItemKind::Unit(_) => None,
+    }
   }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum ItemKind {
   Load(PBox<Load>),
+  Bind(PBox<Bind>),
   Ext(PBox<Ext>),
   Val(PBox<Decl>),
   Fun(PBox<Fun>),
+  Data(PBox<Data>),
+  Enum(PBox<Enum>),
+  Impl(PBox<Impl>),
+  Behavior(PBox<Behavior>),
+  Type(PBox<TyAlias>),
+  Macro(PBox<Macro>),
+  MacroCall(PBox<MacroCall>),
+  Unit(PBox<Unit>),
 }
 
-#[derive(Debug)]
+// `macro name ( (matcher) {transcriber}; ... )`, the delimiter around the
+// rules is the one with which the macro is invoked
+#[derive(Clone, Debug)]
+pub struct Macro {
+  pub public: Public,
+  pub name: PBox<Expr>,
+  pub delimiter: Delimiter,
+  pub rules: Vec<PBox<MacroRule>>,
+  pub span: Span,
+}
+
+impl Macro {
+  pub fn new(
+    public: Public,
+    name: PBox<Expr>,
+    delimiter: Delimiter,
+    rules: Vec<PBox<MacroRule>>,
+    span: Span,
+  ) -> Self {
+    Self {
+      public,
+      name,
+      delimiter,
+      rules,
+      span,
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct MacroRule {
+  pub matcher: Vec<TokenTree>,
+  pub transcriber: Vec<TokenTree>,
+  pub span: Span,
+}
+
+impl MacroRule {
+  pub fn new(
+    matcher: Vec<TokenTree>,
+    transcriber: Vec<TokenTree>,
+    span: Span,
+  ) -> Self {
+    Self {
+      matcher,
+      transcriber,
+      span,
+    }
+  }
+}
+
+// `#name(tokens)`, `#name{tokens}` or `#name[tokens]`
+#[derive(Clone, Debug)]
+pub struct MacroCall {
+  pub name: PBox<Expr>,
+  pub delimiter: Delimiter,
+  pub tokens: Vec<TokenTree>,
+  pub span: Span,
+}
+
+impl MacroCall {
+  pub fn new(
+    name: PBox<Expr>,
+    delimiter: Delimiter,
+    tokens: Vec<TokenTree>,
+    span: Span,
+  ) -> Self {
+    Self {
+      name,
+      delimiter,
+      tokens,
+      span,
+    }
+  }
+}
+
+// the tokens given to a macro are kept as they were lexed, grouped by their
+// delimiters
+#[derive(Clone, Debug)]
+pub enum TokenTree {
+  Token(Token, Span),
+  Delimited(Delimiter, Vec<TokenTree>, Span),
+}
+
+impl TokenTree {
+  pub fn span(&self) -> Span {
+    match self {
+      Self::Token(_, span) => *span,
+      Self::Delimited(_, _, span) => *span,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Delimiter {
+  Paren,
+  Brace,
+  Bracket,
+}
+
+impl Delimiter {
+  pub fn open(&self) -> Token {
+    match self {
+      Self::Paren => Token::OpenParen,
+      Self::Brace => Token::OpenBrace,
+      Self::Bracket => Token::OpenBracket,
+    }
+  }
+
+  pub fn close(&self) -> Token {
+    match self {
+      Self::Paren => Token::CloseParen,
+      Self::Brace => Token::CloseBrace,
+      Self::Bracket => Token::CloseBracket,
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
 pub struct Ext {
   pub public: Public,
   pub prototype: Prototype,
   pub body: Option<PBox<Block>>,
+  pub attrs: Vec<Attribute>,
   pub span: Span,
 }
 
@@ -125,18 +417,22 @@ impl Ext {
       public,
       prototype,
       body,
+      attrs: vec![],
       span,
     }
   }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Decl {
+  pub public: Public,
   pub mutability: Mutability,
   pub kind: DeclKind,
   pub pattern: Pattern,
-  pub ty: PBox<Ty>,
+  pub ty: Option<PBox<Ty>>,
+  pub inferred_ty: InferredTy,
   pub value: PBox<Expr>,
+  pub attrs: Vec<Attribute>,
   pub span: Span,
 }
 
@@ -145,44 +441,356 @@ impl Decl {
     mutability: Mutability,
     kind: DeclKind,
     pattern: Pattern,
-    ty: PBox<Ty>,
+    ty: Option<PBox<Ty>>,
     value: PBox<Expr>,
     span: Span,
   ) -> Self {
     Self {
+      public: Public::No,
       mutability,
       kind,
       pattern,
       ty,
+      inferred_ty: InferredTy::default(),
       value,
+      attrs: vec![],
       span,
     }
   }
+
+  pub fn with_attrs(mut self, attrs: Vec<Attribute>) -> Self {
+    self.attrs = attrs;
+    self
+  }
+
+  // only a `val` item can be public
+  pub fn with_public(mut self, public: Public) -> Self {
+    self.public = public;
+    self
+  }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum DeclKind {
   Val,
   Imu,
   Mut,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+pub struct Data {
+  pub public: Public,
+  pub name: PBox<Expr>,
+  pub ty_params: Vec<PBox<TyParam>>,
+  pub fields: Vec<PBox<FieldDef>>,
+  pub span: Span,
+}
+
+impl Data {
+  pub fn new(
+    public: Public,
+    name: PBox<Expr>,
+    fields: Vec<PBox<FieldDef>>,
+    span: Span,
+  ) -> Self {
+    Self {
+      public,
+      name,
+      ty_params: vec![],
+      fields,
+      span,
+    }
+  }
+
+  pub fn with_ty_params(mut self, ty_params: Vec<PBox<TyParam>>) -> Self {
+    self.ty_params = ty_params;
+    self
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct FieldDef {
+  pub name: PBox<Expr>,
+  pub ty: PBox<Ty>,
+  pub span: Span,
+}
+
+impl FieldDef {
+  pub fn new(name: PBox<Expr>, ty: PBox<Ty>, span: Span) -> Self {
+    Self { name, ty, span }
+  }
+}
+
+// `type Foo = u32;` names a type, `type Foo { Bar: u32 }` names the type
+// `Foo::Bar`
+#[derive(Clone, Debug)]
+pub struct TyAlias {
+  pub name: PBox<Expr>,
+  pub kind: TyAliasKind,
+  pub span: Span,
+}
+
+impl TyAlias {
+  pub fn new(name: PBox<Expr>, kind: TyAliasKind, span: Span) -> Self {
+    Self { name, kind, span }
+  }
+
+  // the name and the type of every alias that it declares
+  pub fn tys(&self) -> Vec<(String, &PBox<Ty>)> {
+    match &self.kind {
+      TyAliasKind::Single(ty) => vec![(self.name.to_string(), ty)],
+      TyAliasKind::Group(fields) => fields
+        .iter()
+        .map(|field| (format!("{}::{}", self.name, field.name), &field.ty))
+        .collect(),
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub enum TyAliasKind {
+  Single(PBox<Ty>),
+  Group(Vec<PBox<FieldDef>>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Enum {
+  pub public: Public,
+  pub name: PBox<Expr>,
+  pub variants: Vec<PBox<Variant>>,
+  pub span: Span,
+}
+
+impl Enum {
+  pub fn new(
+    public: Public,
+    name: PBox<Expr>,
+    variants: Vec<PBox<Variant>>,
+    span: Span,
+  ) -> Self {
+    Self {
+      public,
+      name,
+      variants,
+      span,
+    }
+  }
+
+  // the tag of each variant, a variant without an explicit discriminant
+  // takes the discriminant of the previous variant plus one, skipping the
+  // values that are already taken: `Foo, Bar = 0` makes `Foo` be 1
+  pub fn discriminants(&self) -> Vec<i64> {
+    let mut taken = self
+      .variants
+      .iter()
+      .filter_map(|variant| variant.discriminant)
+      .collect::<Vec<_>>();
+
+    let mut next = 0;
+
+    self
+      .variants
+      .iter()
+      .map(|variant| {
+        let discriminant = match variant.discriminant {
+          Some(discriminant) => discriminant,
+          None => {
+            while taken.contains(&next) {
+              next += 1;
+            }
+
+            taken.push(next);
+            next
+          }
+        };
+
+        next = discriminant + 1;
+        discriminant
+      })
+      .collect::<Vec<_>>()
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct Variant {
+  pub name: PBox<Expr>,
+  pub tys: Vec<PBox<Ty>>,
+  pub discriminant: Option<i64>,
+  pub span: Span,
+}
+
+impl Variant {
+  pub fn new(
+    name: PBox<Expr>,
+    tys: Vec<PBox<Ty>>,
+    discriminant: Option<i64>,
+    span: Span,
+  ) -> Self {
+    Self {
+      name,
+      tys,
+      discriminant,
+      span,
+    }
+  }
+}
+
+// `impl Ty {}` or `impl Behavior for Ty {}`, the functions of the latter are
+// the methods of the behavior
+#[derive(Clone, Debug)]
+pub struct Impl {
+  pub ty: PBox<Ty>,
+  pub behavior: Option<PBox<Expr>>,
+  pub funs: Vec<PBox<Fun>>,
+  pub span: Span,
+}
+
+impl Impl {
+  pub fn new(ty: PBox<Ty>, funs: Vec<PBox<Fun>>, span: Span) -> Self {
+    Self {
+      ty,
+      behavior: None,
+      funs,
+      span,
+    }
+  }
+
+  pub fn with_behavior(mut self, behavior: PBox<Expr>) -> Self {
+    self.behavior = Some(behavior);
+    self
+  }
+}
+
+// `behavior Name { fun name(.): Ty; fun other(.) {} }`, the methods that a
+// type implements. a method with a body is a default, an `impl` may leave it
+// out
+#[derive(Clone, Debug)]
+pub struct Behavior {
+  pub public: Public,
+  pub kind: BehaviorKind,
+  pub name: PBox<Expr>,
+  pub methods: Vec<PBox<Method>>,
+  pub span: Span,
+}
+
+impl Behavior {
+  pub fn new(
+    public: Public,
+    kind: BehaviorKind,
+    name: PBox<Expr>,
+    methods: Vec<PBox<Method>>,
+    span: Span,
+  ) -> Self {
+    Self {
+      public,
+      kind,
+      name,
+      methods,
+      span,
+    }
+  }
+
+  pub fn method(&self, name: &str) -> Option<&PBox<Method>> {
+    self
+      .methods
+      .iter()
+      .find(|method| method.prototype.name.to_string() == name)
+  }
+}
+
+#[derive(Clone, Debug)]
+pub enum BehaviorKind {
+  Behavior,
+  Abstract,
+}
+
+#[derive(Clone, Debug)]
+pub struct Method {
+  pub prototype: Prototype,
+  pub body: Option<PBox<Block>>,
+  pub span: Span,
+}
+
+impl Method {
+  pub fn new(
+    prototype: Prototype,
+    body: Option<PBox<Block>>,
+    span: Span,
+  ) -> Self {
+    Self {
+      prototype,
+      body,
+      span,
+    }
+  }
+}
+
+// `unit { test name() {} mock name() {} }`, a unit is left out of the
+// program and only compiled into the test executable
+#[derive(Clone, Debug)]
+pub struct Unit {
+  pub items: Vec<UnitItem>,
+  pub span: Span,
+}
+
+impl Unit {
+  pub fn new(items: Vec<UnitItem>, span: Span) -> Self {
+    Self { items, span }
+  }
+
+  pub fn tests(&self) -> impl Iterator<Item = &PBox<Fun>> {
+    self.items.iter().filter_map(|item| match item {
+      UnitItem::Test(fun) => Some(fun),
+      UnitItem::Mock(_) => None,
+    })
+  }
+
+  pub fn mocks(&self) -> impl Iterator<Item = &PBox<Fun>> {
+    self.items.iter().filter_map(|item| match item {
+      UnitItem::Mock(fun) => Some(fun),
+      UnitItem::Test(_) => None,
+    })
+  }
+}
+
+// a test takes no inputs and returns nothing, a mock replaces the function
+// of the same name in the tests of its unit
+#[derive(Clone, Debug)]
+pub enum UnitItem {
+  Test(PBox<Fun>),
+  Mock(PBox<Fun>),
+}
+
+#[derive(Clone, Debug)]
 pub struct Fun {
+  pub public: Public,
   pub prototype: Prototype,
   pub body: PBox<Block>,
+  pub attrs: Vec<Attribute>,
   pub span: Span,
 }
 
 impl Fun {
-  pub fn new(prototype: Prototype, body: PBox<Block>, span: Span) -> Self {
+  pub fn new(
+    public: Public,
+    prototype: Prototype,
+    body: PBox<Block>,
+    span: Span,
+  ) -> Self {
     Self {
+      public,
       prototype,
       body,
+      attrs: vec![],
       span,
     }
   }
 
+  pub fn with_attrs(mut self, attrs: Vec<Attribute>) -> Self {
+    self.attrs = attrs;
+    self
+  }
+
   pub fn as_inputs_tys(&self) -> Vec<PBox<Ty>> {
     self.prototype.as_inputs_tys()
   }
@@ -194,9 +802,10 @@ impl AsTy for Fun {
   }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Prototype {
   pub name: PBox<Expr>,
+  pub ty_params: Vec<PBox<TyParam>>,
   pub inputs: Vec<PBox<Arg>>,
   pub output: ReturnTy,
 }
@@ -209,11 +818,17 @@ impl Prototype {
   ) -> Self {
     Self {
       name,
+      ty_params: vec![],
       inputs,
       output,
     }
   }
 
+  pub fn with_ty_params(mut self, ty_params: Vec<PBox<TyParam>>) -> Self {
+    self.ty_params = ty_params;
+    self
+  }
+
   pub fn as_inputs_tys(&self) -> Vec<PBox<Ty>> {
     self
       .inputs
@@ -221,6 +836,13 @@ impl Prototype {
       .map(|input| input.ty.to_owned())
       .collect::<Vec<_>>()
   }
+
+  pub fn has_receiver(&self) -> bool {
+    self
+      .inputs
+      .first()
+      .is_some_and(|input| matches!(input.pattern.kind, PatternKind::Receiver))
+  }
 }
 
 impl AsTy for Prototype {
@@ -229,7 +851,32 @@ impl AsTy for Prototype {
   }
 }
 
-#[derive(Debug)]
+// `T` in `fun id<T>(x: T): T`, it is replaced by a concrete type in each
+// instance of the function. `T: Show` only takes the types that implement
+// the behavior `Show`
+#[derive(Clone, Debug)]
+pub struct TyParam {
+  pub name: PBox<Expr>,
+  pub bounds: Vec<PBox<Expr>>,
+  pub span: Span,
+}
+
+impl TyParam {
+  pub fn new(name: PBox<Expr>, span: Span) -> Self {
+    Self {
+      name,
+      bounds: vec![],
+      span,
+    }
+  }
+
+  pub fn with_bounds(mut self, bounds: Vec<PBox<Expr>>) -> Self {
+    self.bounds = bounds;
+    self
+  }
+}
+
+#[derive(Clone, Debug)]
 pub struct Arg {
   pub pattern: Pattern,
   pub ty: PBox<Ty>,
@@ -257,7 +904,7 @@ impl AsTy for ReturnTy {
   }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Block {
   pub stmts: Vec<PBox<Stmt>>,
   pub span: Span,
@@ -269,7 +916,7 @@ impl Block {
   }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Stmt {
   pub kind: StmtKind,
   pub span: Span,
@@ -281,26 +928,41 @@ impl Stmt {
   }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum StmtKind {
   Item(PBox<Item>),
   Decl(PBox<Decl>),
   Expr(PBox<Expr>),
 }
 
-#[derive(Debug)]
+// the type of a declaration written with `:=`. it is finalized by the
+// typechecker once the enclosing item is checked, so the handle is kept until
+// then
+pub type InferredTy = Rc<RefCell<Option<PBox<Ty>>>>;
+
+// the type arguments of a generic function or data type named by an
+// expression. they are inferred by the typechecker once the enclosing item is
+// checked, so the handle is kept until then
+pub type TyArgs = Rc<RefCell<Vec<PBox<Ty>>>>;
+
+#[derive(Clone, Debug)]
 pub struct Expr {
   pub kind: ExprKind,
+  pub ty_args: TyArgs,
   pub span: Span,
 }
 
 impl Expr {
   pub fn new(kind: ExprKind, span: Span) -> Self {
-    Self { kind, span }
+    Self {
+      kind,
+      ty_args: Rc::new(RefCell::new(vec![])),
+      span,
+    }
   }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum ExprKind {
   Lit(PBox<Lit>),
   Identifier(String),
@@ -317,52 +979,148 @@ pub enum ExprKind {
   Continue,
   When(PBox<Expr>, PBox<Expr>, PBox<Expr>),
   IfElse(PBox<Expr>, PBox<Expr>, Option<PBox<Expr>>),
+  Path(PBox<Expr>, PBox<Expr>),
+  Receiver,
+  Field(PBox<Expr>, PBox<Expr>),
+  Struct(PBox<Expr>, Vec<PBox<FieldExpr>>),
+  Match(PBox<Expr>, Vec<PBox<Arm>>),
+  Is(PBox<Expr>, Pattern),
+  For(PBox<Expr>, PBox<Expr>, Option<PBox<Expr>>, PBox<Block>),
+  ForEach(PBox<Expr>, PBox<Block>),
+  Array(Vec<PBox<Expr>>),
+  Lambda(PBox<Lambda>),
+  MacroCall(PBox<MacroCall>),
+  // `x?` is the value of `Some` or `Ok`, `None` and `Err` are returned
+  Try(PBox<Expr>),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+pub struct Lambda {
+  pub inputs: Vec<PBox<LambdaArg>>,
+  pub body: PBox<Block>,
+  // the names of the enclosing scopes used by the body, they are found by the
+  // typechecker and copied into the environment of the closure
+  pub captures: RefCell<Vec<String>>,
+  pub span: Span,
+}
+
+impl Lambda {
+  pub fn new(
+    inputs: Vec<PBox<LambdaArg>>,
+    body: PBox<Block>,
+    span: Span,
+  ) -> Self {
+    Self {
+      inputs,
+      body,
+      captures: RefCell::new(vec![]),
+      span,
+    }
+  }
+}
+
+// the type of an input may be left out when it is known from the context
+#[derive(Clone, Debug)]
+pub struct LambdaArg {
+  pub pattern: Pattern,
+  pub ty: Option<PBox<Ty>>,
+  pub span: Span,
+}
+
+impl LambdaArg {
+  pub fn new(pattern: Pattern, ty: Option<PBox<Ty>>, span: Span) -> Self {
+    Self { pattern, ty, span }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct Arm {
+  pub pattern: Pattern,
+  pub body: PBox<Expr>,
+  pub span: Span,
+}
+
+impl Arm {
+  pub fn new(pattern: Pattern, body: PBox<Expr>, span: Span) -> Self {
+    Self {
+      pattern,
+      body,
+      span,
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct FieldExpr {
+  pub name: PBox<Expr>,
+  pub value: Option<PBox<Expr>>,
+  pub span: Span,
+}
+
+impl FieldExpr {
+  pub fn new(name: PBox<Expr>, value: Option<PBox<Expr>>, span: Span) -> Self {
+    Self { name, value, span }
+  }
+}
+
+#[derive(Clone, Debug)]
 pub struct Lit {
   pub kind: LitKind,
+  pub suffix: Option<TyKind>,
   pub span: Span,
 }
 
 impl Lit {
   pub fn new(kind: LitKind, span: Span) -> Self {
-    Self { kind, span }
+    Self {
+      kind,
+      suffix: None,
+      span,
+    }
+  }
+
+  pub fn with_suffix(mut self, suffix: Option<TyKind>) -> Self {
+    self.suffix = suffix;
+    self
   }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum LitKind {
   Bool(bool),
   Int(i64),
   Float(f64),
+  Char(char),
   Str(String),
+  ByteStr(Vec<u8>),
 }
 
 pub type BinOp = Spanned<BinOpKind>;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum BinOpKind {
-  Add,    // +
-  Sub,    // -
-  Mul,    // *
-  Div,    // /
-  Rem,    // %
-  And,    // &&
-  Or,     // ||
-  Lt,     // <
-  Gt,     // >
-  Le,     // <=
-  Ge,     // >=
-  Eq,     // ==
-  Ne,     // !=
-  Shl,    // <<
-  Shr,    // >>
-  BitAnd, // &
-  BitOr,  // |
-  BitXor, // ^
-  As,     // as
-  Range,  // ..
+  Add,            // +
+  Sub,            // -
+  Concat,         // ++
+  Mul,            // *
+  Div,            // /
+  Rem,            // %
+  And,            // &&
+  Or,             // ||
+  Lt,             // <
+  Gt,             // >
+  Le,             // <=
+  Ge,             // >=
+  Eq,             // ==
+  Ne,             // !=
+  Shl,            // <<
+  Shr,            // >>
+  BitAnd,         // &
+  BitOr,          // |
+  BitXor,         // ^
+  As,             // as
+  Range,          // ..
+  RangeInclusive, // ..=
 }
 
 impl BinOpKind {
@@ -377,13 +1135,15 @@ impl BinOpKind {
         | Self::BitXor
         | Self::BitAnd
         | Self::BitOr
+        | Self::Shl
+        | Self::Shr
     )
   }
 }
 
 pub type UnOp = Spanned<UnOpKind>;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum UnOpKind {
   Not,
   Neg,
@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 mod ast;
 mod pbox;
 mod pp;
@@ -5,4 +6,5 @@ mod ty;
 
 pub use ast::*;
 pub use pbox::{pbox, PBox};
+pub use pp::Sep;
 pub use ty::*;
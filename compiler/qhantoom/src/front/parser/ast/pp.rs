@@ -53,19 +53,55 @@ impl Display for BindingAnnotation {
 
 impl Display for Program {
   fn fmt(&self, f: &mut Formatter) -> Result {
+    for attr in &self.attrs {
+      writeln!(f, "{attr}")?;
+    }
+
     write!(f, "{}", Sep(&self.items, "\n"))
   }
 }
 
+impl Display for Attribute {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(f, "{} {}", self.style, self.name)?;
+
+    if !self.args.is_empty() {
+      write!(f, ": {}", Sep(&self.args, ", "))?;
+    }
+
+    write!(f, ".")
+  }
+}
+
+impl Display for AttrStyle {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    match self {
+      Self::Outer => write!(f, "#>"),
+      Self::Inner => write!(f, "#!>"),
+    }
+  }
+}
+
 impl Display for Load {
   fn fmt(&self, f: &mut Formatter) -> Result {
     write!(f, "load {}", self.path_view)
   }
 }
 
+impl Display for Bind {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    let _ = match &self.public {
+      Public::No => write!(f, ""),
+      Public::Yes(_) => write!(f, "pub "),
+    };
+
+    write!(f, "bind {}", self.path_view)
+  }
+}
+
 impl Display for PathView {
   fn fmt(&self, f: &mut Formatter) -> Result {
-    write!(f, "load {}", self.kind)
+    write!(f, "{}", self.kind)
   }
 }
 
@@ -73,7 +109,10 @@ impl Display for PathViewKind {
   fn fmt(&self, f: &mut Formatter) -> Result {
     match self {
       Self::Identifier(name) => write!(f, "{name}"),
-      Self::Path(path, names) => write!(f, "{path}::({})", Sep(names, ", ")),
+      Self::Path(path, names) => match names.as_slice() {
+        [name] => write!(f, "{path}::{name}"),
+        _ => write!(f, "{path}::({})", Sep(names, ", ")),
+      },
     }
   }
 }
@@ -90,12 +129,23 @@ impl Display for PatternKind {
       Self::Underscore => write!(f, "_"),
       Self::Identifier(_, name) => write!(f, "{name}"),
       Self::Lit(lit) => write!(f, "{lit}"),
+      Self::Receiver => write!(f, "."),
+      Self::Variant(path, patterns) if patterns.is_empty() => {
+        write!(f, "{path}")
+      }
+      Self::Variant(path, patterns) => {
+        write!(f, "{path}({})", Sep(patterns, ", "))
+      }
     }
   }
 }
 
 impl Display for Item {
   fn fmt(&self, f: &mut Formatter) -> Result {
+    for attr in &self.attrs {
+      writeln!(f, "{attr}")?;
+    }
+
     write!(f, "{}", self.kind)
   }
 }
@@ -104,13 +154,85 @@ impl Display for ItemKind {
   fn fmt(&self, f: &mut Formatter) -> Result {
     match self {
       Self::Val(decl) => write!(f, "{decl}"),
-      _ => todo!(),
+      Self::Data(data) => write!(f, "{data}"),
+      Self::Enum(enumeration) => write!(f, "{enumeration}"),
+      Self::Impl(imp) => write!(f, "{imp}"),
+      Self::Behavior(behavior) => write!(f, "{behavior}"),
+      Self::Type(alias) => write!(f, "{alias}"),
+      Self::Load(load) => write!(f, "{load};"),
+      Self::Bind(bind) => write!(f, "{bind};"),
+      Self::Ext(ext) => write!(f, "{ext}"),
+      Self::Fun(fun) => write!(f, "{fun}"),
+      Self::Macro(mac) => write!(f, "{mac}"),
+      Self::MacroCall(call) => write!(f, "{call};"),
+      Self::Unit(unit) => write!(f, "{unit}"),
+    }
+  }
+}
+
+impl Display for Macro {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    let _ = match &self.public {
+      Public::No => write!(f, ""),
+      Public::Yes(_) => write!(f, "pub "),
+    };
+
+    write!(
+      f,
+      "macro {} {}{}{}",
+      self.name,
+      self.delimiter.open(),
+      Sep(&self.rules, "; "),
+      self.delimiter.close(),
+    )
+  }
+}
+
+impl Display for MacroRule {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(
+      f,
+      "({}) {{ {} }}",
+      Sep(&self.matcher, " "),
+      Sep(&self.transcriber, " ")
+    )
+  }
+}
+
+impl Display for MacroCall {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(
+      f,
+      "#{}{}{}{}",
+      self.name,
+      self.delimiter.open(),
+      Sep(&self.tokens, " "),
+      self.delimiter.close(),
+    )
+  }
+}
+
+impl Display for TokenTree {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    match self {
+      Self::Token(token, _) => write!(f, "{token}"),
+      Self::Delimited(delimiter, trees, _) => write!(
+        f,
+        "{}{}{}",
+        delimiter.open(),
+        Sep(trees, " "),
+        delimiter.close()
+      ),
     }
   }
 }
 
 impl Display for Ext {
   fn fmt(&self, f: &mut Formatter) -> Result {
+    for attr in &self.attrs {
+      writeln!(f, "{attr}")?;
+    }
+
     let _ = match &self.public {
       Public::No => write!(f, ""),
       Public::Yes(_) => write!(f, "pub "),
@@ -126,11 +248,27 @@ impl Display for Ext {
 
 impl Display for Decl {
   fn fmt(&self, f: &mut Formatter) -> Result {
-    write!(
-      f,
-      "{} {}: {} = {};",
-      self.kind, self.pattern, self.ty, self.value
-    )
+    for attr in &self.attrs {
+      writeln!(f, "{attr}")?;
+    }
+
+    let _ = match &self.public {
+      Public::No => write!(f, ""),
+      Public::Yes(_) => write!(f, "pub "),
+    };
+
+    let inferred_ty = self.inferred_ty.borrow();
+
+    match self.ty.as_ref().or(inferred_ty.as_ref()) {
+      Some(ty) => {
+        write!(
+          f,
+          "{} {}: {} = {};",
+          self.kind, self.pattern, ty, self.value
+        )
+      }
+      None => write!(f, "{} {} := {};", self.kind, self.pattern, self.value),
+    }
   }
 }
 
@@ -144,27 +282,183 @@ impl Display for DeclKind {
   }
 }
 
-impl Display for Fun {
+impl Display for Data {
   fn fmt(&self, f: &mut Formatter) -> Result {
-    write!(f, "fun {} {}", self.prototype, self.body)
+    let _ = match &self.public {
+      Public::No => write!(f, ""),
+      Public::Yes(_) => write!(f, "pub "),
+    };
+
+    write!(f, "data {}", self.name)?;
+
+    if !self.ty_params.is_empty() {
+      write!(f, "<{}>", Sep(&self.ty_params, ", "))?;
+    }
+
+    write!(f, " {{ {} }}", Sep(&self.fields, ", "))
   }
 }
 
-impl Display for Prototype {
+impl Display for FieldDef {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(f, "{}: {}", self.name, self.ty)
+  }
+}
+
+impl Display for TyAlias {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    match &self.kind {
+      TyAliasKind::Single(ty) => write!(f, "type {} = {ty};", self.name),
+      TyAliasKind::Group(fields) => {
+        write!(f, "type {} {{ {} }}", self.name, Sep(fields, ", "))
+      }
+    }
+  }
+}
+
+impl Display for Enum {
   fn fmt(&self, f: &mut Formatter) -> Result {
+    let _ = match &self.public {
+      Public::No => write!(f, ""),
+      Public::Yes(_) => write!(f, "pub "),
+    };
+
+    write!(f, "enum {} {{ {} }}", self.name, Sep(&self.variants, ", "))
+  }
+}
+
+impl Display for Variant {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(f, "{}", self.name)?;
+
+    if !self.tys.is_empty() {
+      write!(f, "({})", Sep(&self.tys, ", "))?;
+    }
+
+    let Some(discriminant) = self.discriminant else {
+      return Ok(());
+    };
+
+    write!(f, " = {discriminant}")
+  }
+}
+
+impl Display for Impl {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(f, "impl ")?;
+
+    if let Some(behavior) = &self.behavior {
+      write!(f, "{behavior} for ")?;
+    }
+
+    write!(f, "{} {{\n{}\n}}", self.ty, Sep(&self.funs, "\n"))
+  }
+}
+
+impl Display for Behavior {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    let _ = match &self.public {
+      Public::No => write!(f, ""),
+      Public::Yes(_) => write!(f, "pub "),
+    };
+
     write!(
       f,
-      "{} ({}) {}",
+      "{} {} {{\n{}\n}}",
+      self.kind,
       self.name,
-      Sep(&self.inputs, ", "),
-      self.output
+      Sep(&self.methods, "\n")
     )
   }
 }
 
+impl Display for BehaviorKind {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    match self {
+      Self::Behavior => write!(f, "behavior"),
+      Self::Abstract => write!(f, "abstract"),
+    }
+  }
+}
+
+impl Display for Method {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(f, "fun {}", self.prototype)?;
+
+    let Some(body) = &self.body else {
+      return write!(f, ";");
+    };
+
+    write!(f, " {}", body)
+  }
+}
+
+impl Display for Unit {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(f, "unit {{\n{}\n}}", Sep(&self.items, "\n"))
+  }
+}
+
+impl Display for UnitItem {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    let (keyword, fun) = match self {
+      Self::Test(fun) => ("test", fun),
+      Self::Mock(fun) => ("mock", fun),
+    };
+
+    for attr in &fun.attrs {
+      writeln!(f, "{attr}")?;
+    }
+
+    write!(f, "{keyword} {} {}", fun.prototype, fun.body)
+  }
+}
+
+impl Display for Fun {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    for attr in &self.attrs {
+      writeln!(f, "{attr}")?;
+    }
+
+    let _ = match &self.public {
+      Public::No => write!(f, ""),
+      Public::Yes(_) => write!(f, "pub "),
+    };
+
+    write!(f, "fun {} {}", self.prototype, self.body)
+  }
+}
+
+impl Display for Prototype {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(f, "{}", self.name)?;
+
+    if !self.ty_params.is_empty() {
+      write!(f, "<{}>", Sep(&self.ty_params, ", "))?;
+    }
+
+    write!(f, " ({}) {}", Sep(&self.inputs, ", "), self.output)
+  }
+}
+
+impl Display for TyParam {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(f, "{}", self.name)?;
+
+    if self.bounds.is_empty() {
+      return Ok(());
+    }
+
+    write!(f, ": {}", Sep(&self.bounds, " + "))
+  }
+}
+
 impl Display for Arg {
   fn fmt(&self, f: &mut Formatter) -> Result {
-    write!(f, "{}: {}", self.pattern, self.ty)
+    match &self.pattern.kind {
+      PatternKind::Receiver => write!(f, "{}", self.pattern),
+      _ => write!(f, "{}: {}", self.pattern, self.ty),
+    }
   }
 }
 
@@ -180,9 +474,40 @@ impl Display for ReturnTy {
 impl Display for Block {
   fn fmt(&self, f: &mut Formatter) -> Result {
     if self.stmts.is_empty() {
-      write!(f, "{{}}")
-    } else {
-      write!(f, "{{\n{}\n}}", Sep(&self.stmts, "\n"))
+      return write!(f, "{{}}");
+    }
+
+    writeln!(f, "{{")?;
+
+    for (index, stmt) in self.stmts.iter().enumerate() {
+      let is_last = index + 1 == self.stmts.len();
+
+      writeln!(
+        f,
+        "{stmt}{}",
+        if stmt.needs_semi(is_last) { ";" } else { "" }
+      )?;
+    }
+
+    write!(f, "}}")
+  }
+}
+
+impl Stmt {
+  // an expression is followed by a semicolon, except the ones ending with a
+  // block and the last value of a block
+  fn needs_semi(&self, is_last: bool) -> bool {
+    let StmtKind::Expr(expr) = &self.kind else {
+      return false;
+    };
+
+    match &expr.kind {
+      ExprKind::IfElse(..)
+      | ExprKind::Match(..)
+      | ExprKind::For(..)
+      | ExprKind::ForEach(..) => false,
+      ExprKind::Return(_) | ExprKind::Break(_) | ExprKind::Continue => true,
+      _ => !is_last,
     }
   }
 }
@@ -216,21 +541,31 @@ impl Display for ExprKind {
       Self::Identifier(identifier) => write!(f, "{identifier}"),
       Self::Call(callee, args) => write!(f, "{callee}({})", Sep(args, ", ")),
       Self::UnOp(op, rhs) => write!(f, "{}({})", op.node, rhs),
+      // a range binds looser than any other operator
+      Self::BinOp(lhs, op, rhs)
+        if matches!(op.node, BinOpKind::Range | BinOpKind::RangeInclusive) =>
+      {
+        write!(f, "{lhs}{op}{rhs}")
+      }
       Self::BinOp(lhs, op, rhs) => write!(f, "({lhs} {op} {rhs})"),
-      Self::Assign(lhs, op, rhs) => write!(f, "{lhs} {op} {rhs}"),
-      Self::AssignOp(lhs, op, rhs) => write!(f, "{lhs} {op} {rhs}"),
+      Self::Assign(lhs, _, rhs) => write!(f, "{lhs} = {rhs}"),
+      Self::AssignOp(lhs, op, rhs) => write!(f, "{lhs} {op}= {rhs}"),
       Self::Return(maybe_expr) => {
-        let Some(expr) = maybe_expr else { return write!(f, "return;"); };
+        let Some(expr) = maybe_expr else {
+          return write!(f, "return");
+        };
 
-        write!(f, "return {expr};")
+        write!(f, "return {expr}")
       }
       Self::Block(body) => write!(f, "{body}"),
-      Self::Loop(body) => write!(f, "for {body}"),
+      Self::Loop(body) => write!(f, "loop {body}"),
       Self::While(condition, body) => write!(f, "while {condition} {body}"),
       Self::Break(maybe_expr) => {
-        let Some(expr) = maybe_expr else { return write!(f, "break;"); };
+        let Some(expr) = maybe_expr else {
+          return write!(f, "break");
+        };
 
-        write!(f, "break {expr};")
+        write!(f, "break {expr}")
       }
       Self::Continue => write!(f, "continue"),
       Self::When(condition, consequence, alternative) => {
@@ -241,15 +576,78 @@ impl Display for ExprKind {
 
         let Some(alternative) = maybe_alternative else { return write!(f, ""); };
 
-        write!(f, " {alternative}")
+        write!(f, " else {alternative}")
+      }
+      Self::Path(path, name) => write!(f, "{path}::{name}"),
+      Self::Receiver => write!(f, "."),
+      Self::Field(lhs, name) => match &lhs.kind {
+        Self::Receiver => write!(f, ".{name}"),
+        _ => write!(f, "{lhs}.{name}"),
+      },
+      Self::Struct(name, fields) => {
+        write!(f, "{name} {{ {} }}", Sep(fields, ", "))
       }
+      Self::Match(scrutinee, arms) => {
+        write!(f, "match {scrutinee} {{\n{}\n}}", Sep(arms, ",\n"))
+      }
+      Self::Is(expr, pattern) => write!(f, "{expr} is {pattern}"),
+      Self::Try(expr) => write!(f, "{expr}?"),
+      Self::For(name, range, maybe_step, body) => {
+        let Some(step) = maybe_step else {
+          return write!(f, "for {name} := {range} {body}");
+        };
+
+        write!(f, "for {name} := {range} step {step} {body}")
+      }
+      Self::ForEach(iterable, body) => write!(f, "for each {iterable} {body}"),
+      Self::Array(elements) => write!(f, "[{}]", Sep(elements, ", ")),
+      Self::Lambda(lambda) => write!(f, "{lambda}"),
+      Self::MacroCall(call) => write!(f, "{call}"),
     }
   }
 }
 
+impl Display for Lambda {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(f, "fn({}) {}", Sep(&self.inputs, ", "), self.body)
+  }
+}
+
+impl Display for LambdaArg {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    let Some(ty) = &self.ty else {
+      return write!(f, "{}", self.pattern);
+    };
+
+    write!(f, "{}: {ty}", self.pattern)
+  }
+}
+
+impl Display for Arm {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(f, "{} => {}", self.pattern, self.body)
+  }
+}
+
+impl Display for FieldExpr {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    let Some(value) = &self.value else {
+      return write!(f, "{}", self.name);
+    };
+
+    write!(f, "{}: {value}", self.name)
+  }
+}
+
 impl Display for Lit {
   fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-    write!(f, "{}", self.kind)
+    write!(f, "{}", self.kind)?;
+
+    let Some(suffix) = &self.suffix else {
+      return write!(f, "");
+    };
+
+    write!(f, "{suffix}")
   }
 }
 
@@ -258,8 +656,11 @@ impl Display for LitKind {
     match self {
       Self::Bool(boolean) => write!(f, "{boolean}"),
       Self::Int(num) => write!(f, "{num}"),
-      Self::Float(num) => write!(f, "{num}"),
-      Self::Str(string) => write!(f, "{string}"),
+      // a float is written with its fraction or its exponent: `1.0`, `1e77`
+      Self::Float(num) => write!(f, "{num:?}"),
+      Self::Char(ch) => write!(f, "{ch:?}"),
+      Self::Str(string) => write!(f, "{string:?}"),
+      Self::ByteStr(bytes) => write!(f, "`{}`", bytes.escape_ascii()),
     }
   }
 }
@@ -269,6 +670,7 @@ impl Display for BinOpKind {
     match self {
       Self::Add => write!(f, "+"),
       Self::Sub => write!(f, "-"),
+      Self::Concat => write!(f, "++"),
       Self::Mul => write!(f, "*"),
       Self::Div => write!(f, "/"),
       Self::Rem => write!(f, "%"),
@@ -287,6 +689,7 @@ impl Display for BinOpKind {
       Self::BitXor => write!(f, "^"),
       Self::As => write!(f, "as"),
       Self::Range => write!(f, ".."),
+      Self::RangeInclusive => write!(f, "..="),
     }
   }
 }
@@ -302,7 +705,10 @@ impl Display for UnOpKind {
 
 impl Display for Ty {
   fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-    write!(f, "{}", self.kind)
+    match &self.alias {
+      Some(alias) => write!(f, "{alias}"),
+      None => write!(f, "{}", self.kind),
+    }
   }
 }
 
@@ -323,8 +729,18 @@ impl Display for TyKind {
       Self::SInt => write!(f, "sint"),
       Self::F32 => write!(f, "f32"),
       Self::F64 => write!(f, "f64"),
+      Self::Char => write!(f, "char"),
       Self::Str => write!(f, "str"),
       Self::Fn(args, ty) => write!(f, "Fn({}): {ty}", Sep(args, ", ")),
+      Self::Data(name) => write!(f, "{name}"),
+      Self::Enum(name) => write!(f, "{name}"),
+      Self::Array(ty, len) => write!(f, "[{ty}; {len}]"),
+      Self::Option(ty) => write!(f, "Option<{ty}>"),
+      Self::Result(ok, err) => write!(f, "Result<{ok}, {err}>"),
+      Self::App(name, args) => write!(f, "{name}<{}>", Sep(args, ", ")),
+      Self::Param(name) => write!(f, "{name}"),
+      Self::Infer(_) => write!(f, "_"),
+      Self::InferInt(_) => write!(f, "{{integer}}"),
     }
   }
 }
@@ -7,10 +7,13 @@ pub trait AsTy: Sized {
   fn as_ty(&self) -> PBox<Ty>;
 }
 
+// a type written through an alias keeps the name of the alias to be
+// displayed, its kind is the underlying type
 #[derive(Clone, Debug, PartialEq)]
 pub struct Ty {
   pub kind: TyKind,
   pub span: Span,
+  pub alias: Option<String>,
 }
 
 impl Ty {
@@ -23,7 +26,19 @@ impl Ty {
   pub const VOID: Self = Self::new(TyKind::Void, Span::ZERO);
 
   pub const fn new(kind: TyKind, span: Span) -> Self {
-    Self { kind, span }
+    Self {
+      kind,
+      span,
+      alias: None,
+    }
+  }
+
+  pub const fn with_alias(kind: TyKind, span: Span, alias: String) -> Self {
+    Self {
+      kind,
+      span,
+      alias: Some(alias),
+    }
   }
 
   pub const fn with_void(span: Span) -> Self {
@@ -42,10 +57,38 @@ impl Ty {
     Self::new(TyKind::F64, span)
   }
 
+  pub const fn with_char(span: Span) -> Self {
+    Self::new(TyKind::Char, span)
+  }
+
   pub const fn with_str(span: Span) -> Self {
     Self::new(TyKind::Str, span)
   }
 
+  pub const fn with_data(name: String, span: Span) -> Self {
+    Self::new(TyKind::Data(name), span)
+  }
+
+  pub const fn with_option(ty: PBox<Ty>, span: Span) -> Self {
+    Self::new(TyKind::Option(ty), span)
+  }
+
+  pub const fn with_result(ok: PBox<Ty>, err: PBox<Ty>, span: Span) -> Self {
+    Self::new(TyKind::Result(ok, err), span)
+  }
+
+  // `Option` and `Result` are the only types that take type arguments, any
+  // other type written with arguments is reported by the typechecker
+  pub fn with_args(name: &Expr, mut args: Vec<PBox<Ty>>, span: Span) -> Self {
+    let kind = match (name.to_string().as_str(), args.len()) {
+      ("Option", 1) => TyKind::Option(args.remove(0)),
+      ("Result", 2) => TyKind::Result(args.remove(0), args.remove(0)),
+      (name, _) => TyKind::App(name.to_string(), args),
+    };
+
+    Self::new(kind, span)
+  }
+
   pub const fn with_fn(
     args: Vec<PBox<Ty>>,
     return_ty: PBox<Ty>,
@@ -61,17 +104,72 @@ impl Ty {
   pub fn is_boolean(&self) -> bool {
     self.kind.is_boolean()
   }
+
+  pub fn is_integer(&self) -> bool {
+    self.kind.is_integer()
+  }
+
+  pub fn is_char(&self) -> bool {
+    matches!(self.kind, TyKind::Char)
+  }
+
+  // a value of this type may be left out of a struct literal, it is zeroed
+  pub fn is_zeroable(&self) -> bool {
+    self.is_numeric() || self.is_boolean() || self.is_char()
+  }
+
+  pub fn is_self(&self) -> bool {
+    matches!(&self.kind, TyKind::Data(name) if name == "Self")
+  }
+
+  // replaces each type parameter by the type argument at the same position
+  pub fn substitute(&self, params: &[String], args: &[PBox<Ty>]) -> PBox<Ty> {
+    let substitute = |ty: &PBox<Ty>| ty.substitute(params, args);
+
+    let kind = match &self.kind {
+      TyKind::Param(name) => match params.iter().position(|x| x == name) {
+        Some(x) => return Ty::new(args[x].kind.to_owned(), self.span).into(),
+        None => TyKind::Param(name.to_owned()),
+      },
+      TyKind::Fn(input_tys, return_ty) => TyKind::Fn(
+        input_tys.iter().map(substitute).collect(),
+        substitute(return_ty),
+      ),
+      TyKind::Array(element_ty, len) => {
+        TyKind::Array(substitute(element_ty), *len)
+      }
+      TyKind::Option(some_ty) => TyKind::Option(substitute(some_ty)),
+      TyKind::Result(ok_ty, err_ty) => {
+        TyKind::Result(substitute(ok_ty), substitute(err_ty))
+      }
+      TyKind::App(name, tys) => {
+        TyKind::App(name.to_owned(), tys.iter().map(substitute).collect())
+      }
+      kind => kind.to_owned(),
+    };
+
+    Ty {
+      kind,
+      span: self.span,
+      alias: self.alias.to_owned(),
+    }
+    .into()
+  }
 }
 
 impl From<PBox<Expr>> for Ty {
   fn from(expr: PBox<Expr>) -> Self {
-    let kind = if let ExprKind::Identifier(identifier) = &expr.kind {
-      match identifier.as_str() {
-        "uint" => TyKind::UInt,
-        _ => panic!("from ty error"),
-      }
-    } else {
-      TyKind::Void
+    Self::from(&*expr)
+  }
+}
+
+impl From<&Expr> for Ty {
+  fn from(expr: &Expr) -> Self {
+    let kind = match &expr.kind {
+      ExprKind::Identifier(identifier) => TyKind::primitive(identifier)
+        .unwrap_or_else(|| TyKind::Data(identifier.to_string())),
+      ExprKind::Path(..) => TyKind::Data(expr.to_string()),
+      _ => TyKind::Void,
     };
 
     Ty::new(kind, expr.span)
@@ -106,16 +204,53 @@ pub enum TyKind {
   SInt,
   F32,
   F64,
+  Char,
   Str,
   Fn(Vec<PBox<Ty>>, PBox<Ty>),
+  Data(String),
+  Enum(String),
+  Array(PBox<Ty>, usize),
+  Option(PBox<Ty>),
+  Result(PBox<Ty>, PBox<Ty>),
+  App(String, Vec<PBox<Ty>>),
+  Param(String),
+  Infer(usize),
+  InferInt(usize),
 }
 
 impl TyKind {
+  pub const PRIMITIVES: [&'static str; 16] = [
+    "void", "bool", "u8", "u16", "u32", "u64", "uint", "s8", "s16", "s32",
+    "s64", "sint", "f32", "f64", "char", "str",
+  ];
+
+  pub fn primitive(name: &str) -> Option<Self> {
+    match name {
+      "void" => Some(Self::Void),
+      "bool" => Some(Self::Bool),
+      "u8" => Some(Self::U8),
+      "u16" => Some(Self::U16),
+      "u32" => Some(Self::U32),
+      "u64" => Some(Self::U64),
+      "uint" => Some(Self::UInt),
+      "s8" => Some(Self::S8),
+      "s16" => Some(Self::S16),
+      "s32" => Some(Self::S32),
+      "s64" => Some(Self::S64),
+      "sint" => Some(Self::SInt),
+      "f32" => Some(Self::F32),
+      "f64" => Some(Self::F64),
+      "char" => Some(Self::Char),
+      "str" => Some(Self::Str),
+      _ => None,
+    }
+  }
+
   fn is_boolean(&self) -> bool {
     matches!(self, Self::Bool)
   }
 
-  fn is_numeric(&self) -> bool {
+  fn is_integer(&self) -> bool {
     matches!(
       self,
       Self::U8
@@ -128,10 +263,13 @@ impl TyKind {
         | Self::S32
         | Self::S64
         | Self::SInt
-        | Self::F32
-        | Self::F64
+        | Self::InferInt(_)
     )
   }
+
+  fn is_numeric(&self) -> bool {
+    self.is_integer() || matches!(self, Self::F32 | Self::F64)
+  }
 }
 
 impl PartialEq for TyKind {
@@ -151,10 +289,38 @@ impl PartialEq for TyKind {
       | (Self::SInt, Self::SInt)
       | (Self::F32, Self::F32)
       | (Self::F64, Self::F64)
+      | (Self::Char, Self::Char)
       | (Self::Str, Self::Str) => true,
-      (Self::Fn(_, lhs_return_ty), Self::Fn(_, rhs_return_ty)) => {
-        lhs_return_ty == rhs_return_ty
+      (Self::Fn(lhs_tys, lhs_return_ty), Self::Fn(rhs_tys, rhs_return_ty)) => {
+        lhs_tys.len() == rhs_tys.len()
+          && lhs_tys
+            .iter()
+            .zip(rhs_tys)
+            .all(|(lhs, rhs)| lhs.kind == rhs.kind)
+          && lhs_return_ty.kind == rhs_return_ty.kind
+      }
+      (Self::Data(lhs_name), Self::Data(rhs_name)) => lhs_name == rhs_name,
+      (Self::Enum(lhs_name), Self::Enum(rhs_name)) => lhs_name == rhs_name,
+      (Self::Array(lhs_ty, lhs_len), Self::Array(rhs_ty, rhs_len)) => {
+        lhs_ty.kind == rhs_ty.kind && lhs_len == rhs_len
+      }
+      (Self::Option(lhs_ty), Self::Option(rhs_ty)) => {
+        lhs_ty.kind == rhs_ty.kind
+      }
+      (Self::Result(lhs_ok, lhs_err), Self::Result(rhs_ok, rhs_err)) => {
+        lhs_ok.kind == rhs_ok.kind && lhs_err.kind == rhs_err.kind
+      }
+      (Self::App(lhs_name, lhs_tys), Self::App(rhs_name, rhs_tys)) => {
+        lhs_name == rhs_name
+          && lhs_tys.len() == rhs_tys.len()
+          && lhs_tys
+            .iter()
+            .zip(rhs_tys)
+            .all(|(lhs, rhs)| lhs.kind == rhs.kind)
       }
+      (Self::Param(lhs_name), Self::Param(rhs_name)) => lhs_name == rhs_name,
+      (Self::Infer(lhs_id), Self::Infer(rhs_id))
+      | (Self::InferInt(lhs_id), Self::InferInt(rhs_id)) => lhs_id == rhs_id,
       _ => false,
     }
   }
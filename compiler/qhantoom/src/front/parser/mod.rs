@@ -2,4 +2,6 @@ mod syntax;
 
 pub mod ast;
 
-pub use syntax::parse;
+pub use syntax::{
+  parse, parse_source, parse_tokens, SyntaxError, SyntaxErrorKind,
+};
@@ -1,17 +1,330 @@
 use crate::front::grammar::ProgramParser;
+use crate::front::lexer::{Lexer, LexicalError, LexicalErrorKind, Token};
 use crate::front::parser::ast::*;
-use crate::util::error::Reporter;
+use crate::util::error::{Label, LabelKind, LabelMessage};
+use crate::util::error::{Note, NoteKind};
+
+use crate::util::error::{
+  Report, ReportCode, ReportKind, ReportMessage, ReportOffset, Reporter,
+};
+
+use crate::util::span::Span;
+
+use lalrpop_util::ParseError;
 
 use std::path::PathBuf;
 
-pub fn parse<P: Into<PathBuf>>(pathname: P) -> Program {
+// the inner attributes and the items of a source
+type ParsedItems = (Vec<Attribute>, Vec<PBox<Item>>);
+
+#[derive(Debug)]
+pub struct SyntaxError {
+  pub kind: SyntaxErrorKind,
+  pub span: Span,
+}
+
+impl SyntaxError {
+  pub fn new(kind: SyntaxErrorKind, span: Span) -> Self {
+    Self { kind, span }
+  }
+}
+
+impl From<LexicalError> for SyntaxError {
+  fn from(error: LexicalError) -> Self {
+    Self::new(SyntaxErrorKind::Lexical(error.kind), error.span)
+  }
+}
+
+impl From<ParseError<usize, Token, LexicalError>> for SyntaxError {
+  fn from(error: ParseError<usize, Token, LexicalError>) -> Self {
+    match error {
+      ParseError::InvalidToken { location } => Self::new(
+        SyntaxErrorKind::InvalidToken,
+        Span::new(location, location + 1),
+      ),
+      ParseError::UnrecognizedEOF { location, expected } => Self::new(
+        SyntaxErrorKind::UnrecognizedEof(fmt_expected(expected)),
+        Span::new(location.saturating_sub(1), location),
+      ),
+      ParseError::UnrecognizedToken {
+        token: (lo, token, hi),
+        expected,
+      } => Self::new(
+        SyntaxErrorKind::UnrecognizedToken(
+          token.to_string(),
+          fmt_expected(expected),
+        ),
+        Span::new(lo, hi),
+      ),
+      ParseError::ExtraToken {
+        token: (lo, token, hi),
+      } => Self::new(
+        SyntaxErrorKind::ExtraToken(token.to_string()),
+        Span::new(lo, hi),
+      ),
+      ParseError::User { error } => Self::from(error),
+    }
+  }
+}
+
+#[derive(Debug)]
+pub enum SyntaxErrorKind {
+  InvalidToken,
+  UnrecognizedEof(Vec<String>),
+  UnrecognizedToken(String, Vec<String>),
+  ExtraToken(String),
+  Lexical(LexicalErrorKind),
+}
+
+// parses the source file, recovering at item and statement boundaries.
+// every syntax error is reported, the partial program is still returned
+// so that the items that parsed can be analyzed. an error that cannot be
+// recovered from aborts the compilation
+pub fn parse<P: Into<PathBuf>>(pathname: P) -> (Program, Vec<SyntaxError>) {
   let mut reporter = Reporter::default();
   let source_id = reporter.add_source(pathname.into()).unwrap();
-  let source_code = reporter.code(source_id);
+
+  match parse_source(&reporter, source_id) {
+    (Some((attrs, items)), errors) => {
+      (Program::new(attrs, items, reporter), errors)
+    }
+    (None, _) => reporter.abort(),
+  }
+}
+
+// parses one of the sources of the reporter, the items are missing when an
+// error cannot be recovered from
+pub fn parse_source(
+  reporter: &Reporter,
+  source_id: u32,
+) -> (Option<ParsedItems>, Vec<SyntaxError>) {
+  let offset = reporter.offset(source_id);
+  let end = offset + reporter.code(source_id).len();
+  let source_code = &reporter.source_map.code[..end];
   let parser = ProgramParser::new();
+  let mut lexer = Lexer::new(source_code).with_offset(offset);
+  let mut recovered_errors = vec![];
+
+  let result = parser.parse(&mut recovered_errors, lexer.by_ref());
 
-  match parser.parse(source_code) {
-    Ok(items) => Program::new(items, reporter),
-    Err(error) => panic!("{error}"),
+  let mut errors = lexer
+    .errors()
+    .into_iter()
+    .map(SyntaxError::from)
+    .chain(
+      recovered_errors
+        .into_iter()
+        .map(|recovery| SyntaxError::from(recovery.error)),
+    )
+    .collect::<Vec<_>>();
+
+  let program = match result {
+    Ok(program) => Some(program),
+    Err(error) => {
+      errors.push(SyntaxError::from(error));
+      None
+    }
+  };
+
+  errors.sort_by_key(|error| error.span.lo);
+
+  for error in &errors {
+    add_report_syntax_error(reporter, error);
   }
+
+  (program, errors)
+}
+
+// parses the tokens produced by a macro expansion, their spans are the ones of
+// the call site so the errors are reported there
+pub fn parse_tokens(
+  reporter: &Reporter,
+  tokens: Vec<(usize, Token, usize)>,
+) -> Option<ParsedItems> {
+  let parser = ProgramParser::new();
+  let mut recovered_errors = vec![];
+
+  let result = parser.parse(&mut recovered_errors, tokens.into_iter().map(Ok));
+
+  let mut errors = recovered_errors
+    .into_iter()
+    .map(|recovery| SyntaxError::from(recovery.error))
+    .collect::<Vec<_>>();
+
+  let program = match result {
+    Ok(program) => Some(program),
+    Err(error) => {
+      errors.push(SyntaxError::from(error));
+      None
+    }
+  };
+
+  for error in &errors {
+    add_report_syntax_error(reporter, error);
+  }
+
+  match errors.is_empty() {
+    true => program,
+    false => None,
+  }
+}
+
+fn add_report_syntax_error(reporter: &Reporter, error: &SyntaxError) {
+  match &error.kind {
+    SyntaxErrorKind::InvalidToken => {
+      add_report_invalid_token_error(reporter, error.span)
+    }
+    SyntaxErrorKind::UnrecognizedEof(expected) => {
+      add_report_unrecognized_eof_error(reporter, error.span, expected)
+    }
+    SyntaxErrorKind::UnrecognizedToken(token, expected) => {
+      add_report_unrecognized_token_error(reporter, token, error.span, expected)
+    }
+    SyntaxErrorKind::ExtraToken(token) => {
+      add_report_extra_token_error(reporter, token, error.span)
+    }
+    SyntaxErrorKind::Lexical(kind) => {
+      add_report_lexical_error(reporter, kind, error.span)
+    }
+  }
+}
+
+fn add_report_invalid_token_error(reporter: &Reporter, span: Span) {
+  let source_id = reporter.source(span);
+  let code = reporter.code(source_id);
+  let path = reporter.path(span);
+
+  reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(8))
+    .with_message(ReportMessage::InvalidToken)
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::InvalidToken),
+    ),
+    path.display().to_string(),
+    code,
+  )
+}
+
+fn add_report_unrecognized_eof_error(
+  reporter: &Reporter,
+  span: Span,
+  expected: &[String],
+) {
+  let source_id = reporter.source(span);
+  let code = reporter.code(source_id);
+  let path = reporter.path(span);
+
+  reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(9))
+    .with_message(ReportMessage::UnrecognizedEof)
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::UnrecognizedEof),
+    )
+    .with_note(Note::new(NoteKind::ExpectedTokens(expected.to_vec()))),
+    path.display().to_string(),
+    code,
+  )
+}
+
+fn add_report_unrecognized_token_error(
+  reporter: &Reporter,
+  token: &str,
+  span: Span,
+  expected: &[String],
+) {
+  let source_id = reporter.source(span);
+  let code = reporter.code(source_id);
+  let path = reporter.path(span);
+
+  reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(10))
+    .with_message(ReportMessage::UnrecognizedToken(token.to_string()))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::UnrecognizedToken),
+    )
+    .with_note(Note::new(NoteKind::ExpectedTokens(expected.to_vec()))),
+    path.display().to_string(),
+    code,
+  )
+}
+
+fn add_report_extra_token_error(reporter: &Reporter, token: &str, span: Span) {
+  let source_id = reporter.source(span);
+  let code = reporter.code(source_id);
+  let path = reporter.path(span);
+
+  reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(11))
+    .with_message(ReportMessage::ExtraToken(token.to_string()))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(LabelMessage::ExtraToken),
+    ),
+    path.display().to_string(),
+    code,
+  )
+}
+
+fn add_report_lexical_error(
+  reporter: &Reporter,
+  kind: &LexicalErrorKind,
+  span: Span,
+) {
+  let source_id = reporter.source(span);
+  let code = reporter.code(source_id);
+  let path = reporter.path(span);
+
+  // the span of an unterminated comment is its opening `-!`
+  let label_message = match kind {
+    LexicalErrorKind::UnterminatedBlockComment => LabelMessage::UnclosedComment,
+    _ => LabelMessage::LexicalError,
+  };
+
+  reporter.add_report(
+    Report::new(
+      ReportKind::Error,
+      path.display().to_string(),
+      ReportOffset(span.lo),
+    )
+    .with_code(ReportCode(12))
+    .with_message(ReportMessage::LexicalError(kind.to_string()))
+    .with_label(
+      Label::new(LabelKind::Error, (path.display().to_string(), span.into()))
+        .with_message(label_message),
+    ),
+    path.display().to_string(),
+    code,
+  )
+}
+
+// the names of the expected tokens are taken from the grammar, so the
+// surrounding quotes are removed before displaying them
+fn fmt_expected(expected: Vec<String>) -> Vec<String> {
+  expected
+    .into_iter()
+    .map(|token| token.trim_matches('"').to_string())
+    .collect::<Vec<_>>()
 }
@@ -6,3 +6,12 @@ pub const PATH_DIRECTORY: &str = "program";
 
 // the entry point of the program
 pub const PROGRAM_ENTRY: &str = "main";
+
+// the executable into which the tests are compiled
+pub const TEST_EXE: &str = "test";
+
+// the extension of the source files
+pub const SOURCE_EXTENSION: &str = "qh";
+
+// the first segment of a path that starts at the directory of the entry file
+pub const PACKAGE_ROOT: &str = "pack";
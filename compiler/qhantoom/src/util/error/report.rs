@@ -57,6 +57,25 @@ impl Report {
     self.help = Some(help);
     self
   }
+
+  // turns the offsets in the code of the source map into offsets in the code
+  // of their own source, `locate` gives the path of the source in which an
+  // offset is and the offset at which that source starts
+  pub fn relocate(mut self, locate: impl Fn(usize) -> (String, usize)) -> Self {
+    let (path, start) = locate(self.offset.into());
+
+    self.offset = ReportOffset(self.offset.0 - start as u32);
+    self.source = (path, 0..self.offset.into());
+
+    for label in &mut self.labels {
+      let range = &label.source.1;
+      let (path, start) = locate(range.start);
+
+      label.source = (path, range.start - start..range.end - start);
+    }
+
+    self
+  }
 }
 
 impl From<Report> for ariadne::Report<Source> {
@@ -116,28 +135,153 @@ impl fmt::Display for ReportCode {
 }
 
 pub enum ReportMessage {
+  AmbiguousName(String),
+  CannotInfer(String),
+  CannotInferTyArg(String, String),
+  CyclicAlias(String),
+  CyclicLoad(String),
   DuplicateDeclaration(String),
+  DuplicateDiscriminant(i64),
+  DuplicateField(String),
+  DuplicateItem(String),
+  DuplicateVariant(String),
+  ExtraToken(String),
+  InstanceRecursion(String),
+  InvalidCast(String, String),
+  InvalidMacroRule(String),
+  InvalidToken,
+  InvalidTry(String),
+  LexicalError(String),
+  MacroRecursion(String),
   MainHasInputs,
   MainNotFound,
+  MalformedAttribute(String),
+  MethodMismatch(String, String),
+  MismatchedRepetition,
+  MisplacedAttribute(String, String),
+  MisplacedBinding(String),
+  MisplacedTry(String),
+  MissingFields,
   MissingInputs,
+  MissingMethods(String),
+  MockMismatch(String),
   NameClash,
   NamingConvention(String, String),
+  NoMacroRule(String),
+  NonExhaustiveMatch,
+  NonPositiveStep,
+  NotBehaviorMethod(String, String),
+  NotImported(String),
+  NotIterable(String),
   OutOfLoop(String),
+  PrivateItem(String),
+  ShadowedIt,
   TypeMismatch,
+  UndefinedField(String, String),
+  UndefinedMacro(String),
+  UndefinedMethod(String, String),
   UndefinedName(String),
+  UndefinedType(String),
+  UndefinedVariant(String, String),
+  UnknownAttribute(String),
+  UnreachablePattern,
+  UnrecognizedEof,
+  UnrecognizedToken(String),
+  UnresolvedLoad(String),
+  UnsatisfiedBound(String, String),
+  Unsupported(String),
   WrongAssignOp,
   WrongBinOp,
+  WrongPatternCount(String),
+  WrongTyArgCount(String),
   WrongUnOp(String),
 }
 
 impl fmt::Display for ReportMessage {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match self {
+      Self::AmbiguousName(name) => {
+        write!(f, "{}", "the name".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "is ambiguous".fg(Color::BLUE_100))
+      }
+      Self::CannotInfer(name) => {
+        write!(f, "{}", "cannot infer the type of".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`{name}`").fg(Color::GREEN_100))
+      }
+      Self::CannotInferTyArg(ty_param, name) => {
+        write!(f, "{}", "cannot infer the type".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{ty_param}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "of".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`{name}`").fg(Color::GREEN_100))
+      }
+      Self::CyclicAlias(name) => {
+        write!(f, "{}", "the type alias".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "refers to itself".fg(Color::BLUE_100))
+      }
+      Self::CyclicLoad(name) => {
+        write!(f, "{}", "the module".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "is loaded in a cycle".fg(Color::BLUE_100))
+      }
       Self::DuplicateDeclaration(name) => {
         write!(f, "{}", "variable".fg(Color::BLUE_100))?;
         write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?; // TODO: backticks should be in colour too
         write!(f, "{}", "already exist".fg(Color::BLUE_100))
       }
+      Self::DuplicateDiscriminant(value) => {
+        write!(f, "{}", "discriminant value".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{value}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "is assigned more than once".fg(Color::BLUE_100))
+      }
+      Self::DuplicateField(name) => {
+        write!(f, "{}", "field".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "is already defined".fg(Color::BLUE_100))
+      }
+      Self::DuplicateItem(name) => {
+        write!(f, "{}", "the name".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "is defined multiple times".fg(Color::BLUE_100))
+      }
+      Self::DuplicateVariant(name) => {
+        write!(f, "{}", "variant".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "is already defined".fg(Color::BLUE_100))
+      }
+      Self::ExtraToken(token) => {
+        write!(f, "{}", "extra token".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`{token}`").fg(Color::GREEN_100))
+      }
+      Self::InstanceRecursion(name) => {
+        write!(f, "{}", "too many nested instances of".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`{name}`").fg(Color::GREEN_100))
+      }
+      Self::InvalidCast(t1, t2) => {
+        write!(f, "{}", "cannot cast".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{t1}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "as".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`{t2}`").fg(Color::GREEN_100))
+      }
+      Self::InvalidMacroRule(name) => {
+        write!(f, "{}", "invalid rule in the macro".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`{name}`").fg(Color::GREEN_100))
+      }
+      Self::InvalidToken => {
+        write!(f, "{}", "invalid token".fg(Color::BLUE_100))
+      }
+      Self::InvalidTry(ty) => {
+        write!(f, "{}", "the".fg(Color::BLUE_100))?;
+        write!(f, " {} ", "`?`".fg(Color::GREEN_100))?;
+        write!(f, "{}", "operator cannot be applied to".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`{ty}`").fg(Color::GREEN_100))
+      }
+      Self::LexicalError(error) => write!(f, "{}", error.fg(Color::BLUE_100)),
+      Self::MacroRecursion(name) => {
+        write!(f, "{}", "too many nested calls of".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`#{name}`").fg(Color::GREEN_100))
+      }
       Self::MainHasInputs => {
         write!(f, "{} ", "`main`".fg(Color::GREEN_100))?;
         write!(f, "{}", "function defined with args".fg(Color::BLUE_100))
@@ -146,9 +290,63 @@ impl fmt::Display for ReportMessage {
         write!(f, "{} ", "`main`".fg(Color::GREEN_100))?;
         write!(f, "{} ", "function not found".fg(Color::BLUE_100))
       }
+      Self::MalformedAttribute(name) => {
+        write!(f, "{}", "malformed".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "attribute".fg(Color::BLUE_100))
+      }
+      Self::MethodMismatch(name, behavior) => {
+        write!(f, "{}", "the method".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(
+          f,
+          "{}",
+          "does not match its declaration in".fg(Color::BLUE_100)
+        )?;
+        write!(f, " {}", format!("`{behavior}`").fg(Color::GREEN_100))
+      }
+      Self::MisplacedAttribute(name, target) => {
+        write!(f, "{}", "the attribute".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "cannot be applied to".fg(Color::BLUE_100))?;
+        write!(f, " {}", target.fg(Color::BLUE_100))
+      }
+      Self::MismatchedRepetition => write!(
+        f,
+        "{}",
+        "metavariables repeat a different number of times".fg(Color::BLUE_100)
+      ),
+      Self::MisplacedBinding(name) => {
+        write!(f, "{}", "the name".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "cannot be bound here".fg(Color::BLUE_100))
+      }
+      Self::MisplacedTry(ty) => {
+        write!(f, "{}", "the".fg(Color::BLUE_100))?;
+        write!(f, " {} ", "`?`".fg(Color::GREEN_100))?;
+        write!(f, "{}", "operator cannot return from".fg(Color::BLUE_100))?;
+        write!(f, " {} ", "a function returning".fg(Color::BLUE_100))?;
+        write!(f, "{}", format!("`{ty}`").fg(Color::GREEN_100))
+      }
+      Self::MissingFields => {
+        write!(f, "{}", "missing fields".fg(Color::BLUE_100))
+      }
       Self::MissingInputs => {
         write!(f, "{}", "missing input arguments".fg(Color::BLUE_100))
       }
+      Self::MissingMethods(behavior) => {
+        write!(f, "{}", "missing methods of".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`{behavior}`").fg(Color::GREEN_100))
+      }
+      Self::MockMismatch(name) => {
+        write!(f, "{}", "the mock".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(
+          f,
+          "{}",
+          "does not match the function it replaces".fg(Color::BLUE_100)
+        )
+      }
       Self::NameClash => write!(f, "{}", "name clash".fg(Color::BLUE_100)),
       Self::NamingConvention(name, convention) => {
         write!(f, "{}", "variable".fg(Color::BLUE_100))?;
@@ -156,24 +354,135 @@ impl fmt::Display for ReportMessage {
         write!(f, "{}", "should have a".fg(Color::BLUE_100))?;
         write!(f, " {} ", convention.fg(Color::BLUE_100))
       }
+      Self::NoMacroRule(name) => {
+        write!(f, "{}", "no rule of the macro".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "matches this call".fg(Color::BLUE_100))
+      }
+      Self::NonExhaustiveMatch => {
+        write!(f, "{}", "non-exhaustive patterns".fg(Color::BLUE_100))
+      }
+      Self::NonPositiveStep => write!(
+        f,
+        "{}",
+        "the step of a loop must be positive".fg(Color::BLUE_100)
+      ),
+      Self::NotBehaviorMethod(name, behavior) => {
+        write!(f, "{}", "the method".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "is not a member of".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`{behavior}`").fg(Color::GREEN_100))
+      }
+      Self::NotImported(name) => {
+        write!(f, "{}", "the item".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "is not imported here".fg(Color::BLUE_100))
+      }
+      Self::NotIterable(ty) => {
+        write!(f, "{}", "the type".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{ty}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "cannot be iterated".fg(Color::BLUE_100))
+      }
       Self::OutOfLoop(behavior) => {
         write!(f, "{} ", format!("`{behavior}`").fg(Color::GREEN_100)).ok();
         write!(f, "{}", "outside of the loop".fg(Color::BLUE_100))
       }
+      Self::PrivateItem(name) => {
+        write!(f, "{}", "the item".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "is private".fg(Color::BLUE_100))
+      }
+      Self::ShadowedIt => {
+        write!(f, "{}", "the outer".fg(Color::BLUE_100))?;
+        write!(f, " {} ", "`it`".fg(Color::GREEN_100))?;
+        write!(f, "{}", "is shadowed".fg(Color::BLUE_100))
+      }
       Self::TypeMismatch => {
         write!(f, "{}", "type mismatch".fg(Color::BLUE_100))
       }
+      Self::UndefinedField(name, ty) => {
+        write!(f, "{}", "no field".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "on type".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`{ty}`").fg(Color::GREEN_100))
+      }
+      Self::UndefinedMacro(name) => {
+        write!(f, "{}", "the macro".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "does not exist in this scope".fg(Color::BLUE_100))
+      }
+      Self::UndefinedMethod(name, ty) => {
+        write!(f, "{}", "no method".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "on type".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`{ty}`").fg(Color::GREEN_100))
+      }
       Self::UndefinedName(name) => {
         write!(f, "{}", "the name".fg(Color::BLUE_100))?;
         write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
         write!(f, "{}", "does not exist in this scope".fg(Color::BLUE_100))
       }
+      Self::UndefinedType(name) => {
+        write!(f, "{}", "the type".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "does not exist in this scope".fg(Color::BLUE_100))
+      }
+      Self::UndefinedVariant(name, ty) => {
+        write!(f, "{}", "no variant".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{name}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "on enum".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`{ty}`").fg(Color::GREEN_100))
+      }
+      Self::UnknownAttribute(name) => {
+        write!(f, "{}", "unknown attribute".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`{name}`").fg(Color::GREEN_100))
+      }
+      Self::UnrecognizedEof => {
+        write!(f, "{}", "unexpected end of file".fg(Color::BLUE_100))
+      }
+      Self::UnreachablePattern => {
+        write!(f, "{}", "unreachable pattern".fg(Color::BLUE_100))
+      }
+      Self::UnrecognizedToken(token) => {
+        write!(f, "{}", "unexpected token".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`{token}`").fg(Color::GREEN_100))
+      }
+      Self::UnresolvedLoad(path) => {
+        write!(f, "{}", "cannot load".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`{path}`").fg(Color::GREEN_100))
+      }
+      Self::UnsatisfiedBound(ty, behavior) => {
+        write!(f, "{}", "the type".fg(Color::BLUE_100))?;
+        write!(f, " {} ", format!("`{ty}`").fg(Color::GREEN_100))?;
+        write!(f, "{}", "does not implement".fg(Color::BLUE_100))?;
+        write!(f, " {}", format!("`{behavior}`").fg(Color::GREEN_100))
+      }
+      Self::Unsupported(what) => {
+        write!(f, "{}", what.fg(Color::BLUE_100))?;
+        write!(f, " {}", "is not supported yet".fg(Color::BLUE_100))
+      }
       Self::WrongAssignOp => write!(
         f,
         "{}",
         "wrong assignment operator expression".fg(Color::BLUE_100)
       ),
       Self::WrongBinOp => write!(f, "wrong binary operation expression"),
+      Self::WrongPatternCount(path) => {
+        write!(
+          f,
+          "{}",
+          "wrong number of fields in the pattern".fg(Color::BLUE_100)
+        )?;
+        write!(f, " {}", format!("`{path}`").fg(Color::GREEN_100))
+      }
+      Self::WrongTyArgCount(name) => {
+        write!(
+          f,
+          "{}",
+          "wrong number of type arguments for".fg(Color::BLUE_100)
+        )?;
+        write!(f, " {}", format!("`{name}`").fg(Color::GREEN_100))
+      }
       Self::WrongUnOp(op) => {
         write!(f, "{}", "wrong unary op expression".fg(Color::BLUE_100))?;
         write!(f, " {}", format!("`{op}`").fg(Color::GREEN_100))
@@ -248,30 +557,161 @@ impl From<LabelKind> for ariadne::Color {
 }
 
 pub enum LabelMessage {
+  AmbiguousName(String),
+  CannotInfer(String),
+  CannotInferTyArg(String),
+  CyclicAlias(String),
+  CyclicLoad(String),
+  DefinedHere,
+  DefinedWithoutPub,
   DuplicateDeclaration,
+  DuplicateDiscriminant,
+  DuplicateField,
+  ExtraToken,
+  InMacroExpansion,
+  InstanceRecursion,
+  InvalidCast(String),
+  InvalidMacroRule(String),
+  InvalidToken,
+  InvalidTry,
+  LexicalError,
+  MacroRecursion,
   MainHasInputs,
   MainNotFound(String),
+  MalformedAttribute(String),
+  MethodMismatch(String, String),
+  MismatchedRepetition,
+  MisplacedAttribute,
+  MisplacedBinding,
+  MisplacedTry(String),
+  MissingFields(String),
   MissingInputs(String),
+  MissingMethods(String),
+  MockMismatch(String, String),
   NameClash,
   NamingConvention(String, String),
+  NoMacroRule,
+  NonExhaustiveMatch(String),
+  NonPositiveStep(String),
+  NotBehaviorMethod(String),
+  NotImported(String),
+  NotIterable,
   OutOfLoop(String),
+  PrivateItem(String),
+  ShadowedIt,
   TypeMismatch(String, String),
   TypeMismatchDefinedAs(String),
+  UnclosedComment,
+  UndefinedField(String),
+  UndefinedMacro,
+  UndefinedMethod(String),
   UndefinedName,
+  UndefinedType,
+  UndefinedVariant(String),
+  UnknownAttribute,
+  UnreachablePattern,
+  UnrecognizedEof,
   UnrecognizedToken,
+  UnresolvedLoad(String),
+  UnsatisfiedBound(String),
+  Unsupported(String),
   WrongAssignOp(String, String),
   WrongBinOp(String, String),
+  WrongPatternCount(usize, usize),
+  WrongTyArgCount(usize, usize),
   WrongUnOp(String),
 }
 
 impl fmt::Display for LabelMessage {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match self {
+      Self::AmbiguousName(modules) => write!(
+        f,
+        "{}",
+        format!("exported by {modules}, name it through its module")
+          .fg(Color::RED_100)
+      ),
+      Self::CannotInfer(name) => write!(
+        f,
+        "{}",
+        format!("add a type annotation: `{name}: ...`").fg(Color::RED_100)
+      ),
+      Self::CannotInferTyArg(ty_param) => write!(
+        f,
+        "{}",
+        format!("the type `{ty_param}` is not known here").fg(Color::RED_100)
+      ),
+      Self::CyclicAlias(name) => write!(
+        f,
+        "{}",
+        format!("`{name}` is used in its own definition here")
+          .fg(Color::RED_100)
+      ),
+      Self::CyclicLoad(cycle) => write!(
+        f,
+        "{}",
+        format!("this loads it again: {cycle}").fg(Color::RED_100)
+      ),
+      Self::DefinedHere => {
+        write!(f, "{}", "defined here".fg(Color::BLUE_200))
+      }
+      Self::DefinedWithoutPub => {
+        write!(f, "{}", "defined here without `pub`".fg(Color::BLUE_200))
+      }
       Self::DuplicateDeclaration => write!(
         f,
         "{}",
         "this name is already declared in the scope".fg(Color::RED_100)
       ),
+      Self::DuplicateDiscriminant => write!(
+        f,
+        "{}",
+        "this value is already used by another variant".fg(Color::RED_100)
+      ),
+      Self::DuplicateField => {
+        write!(f, "{}", "this field is already used".fg(Color::RED_100))
+      }
+      Self::ExtraToken => write!(
+        f,
+        "{}",
+        "this token is not expected here".fg(Color::RED_100)
+      ),
+      Self::InMacroExpansion => {
+        write!(f, "{}", "in this macro expansion".fg(Color::BLUE_100))
+      }
+      Self::InstanceRecursion => write!(
+        f,
+        "{}",
+        "each instance of this item needs a new one".fg(Color::RED_100)
+      ),
+      Self::InvalidCast(ty) => write!(
+        f,
+        "{}",
+        format!("this value cannot be turned into a `{ty}`").fg(Color::RED_100)
+      ),
+      Self::InvalidMacroRule(reason) => {
+        write!(f, "{}", reason.fg(Color::RED_100))
+      }
+      Self::InvalidToken => write!(
+        f,
+        "{}",
+        "i don't know how to read this character".fg(Color::RED_100)
+      ),
+      Self::InvalidTry => write!(
+        f,
+        "{}",
+        "expected an `Option` or a `Result`".fg(Color::RED_100)
+      ),
+      Self::LexicalError => write!(
+        f,
+        "{}",
+        "i can't make a token out of this".fg(Color::RED_100)
+      ),
+      Self::MacroRecursion => write!(
+        f,
+        "{}",
+        "the expansion of this call never ends".fg(Color::RED_100)
+      ),
       Self::MainHasInputs => write!(
         f,
         "{}",
@@ -285,12 +725,59 @@ impl fmt::Display for LabelMessage {
         )
         .fg(Color::RED_100)
       ),
+      Self::MalformedAttribute(form) => write!(
+        f,
+        "{}",
+        format!("this attribute is written `{form}`").fg(Color::RED_100)
+      ),
+      Self::MethodMismatch(expected, found) => write!(
+        f,
+        "{}",
+        format!("expected `{expected}`, found `{found}`").fg(Color::RED_100)
+      ),
+      Self::MisplacedAttribute => write!(
+        f,
+        "{}",
+        "this attribute is not allowed here".fg(Color::RED_100)
+      ),
+      Self::MismatchedRepetition => write!(
+        f,
+        "{}",
+        "the repetitions of this call do not line up".fg(Color::RED_100)
+      ),
+      Self::MisplacedBinding => write!(
+        f,
+        "{}",
+        "an `is` only binds names in the condition of an `if`"
+          .fg(Color::RED_100)
+      ),
+      Self::MisplacedTry(ty) => write!(
+        f,
+        "{}",
+        format!("this returns `{ty}` early").fg(Color::RED_100)
+      ),
+      Self::MissingFields(fields) => write!(
+        f,
+        "{}",
+        format!("the field(s) {fields} must be initialized").fg(Color::RED_100)
+      ),
       Self::MissingInputs(inputs) => write!(
         f,
         "{}",
         format!("the input argument(s) of type {inputs} are required")
           .fg(Color::RED_100)
       ),
+      Self::MissingMethods(methods) => write!(
+        f,
+        "{}",
+        format!("the method(s) {methods} must be implemented")
+          .fg(Color::RED_100)
+      ),
+      Self::MockMismatch(expected, found) => write!(
+        f,
+        "{}",
+        format!("expected `{expected}`, found `{found}`").fg(Color::RED_100)
+      ),
       Self::NameClash => {
         write!(
           f,
@@ -308,6 +795,35 @@ impl fmt::Display for LabelMessage {
           .fg(Color::YELLOW_100)
         )
       }
+      Self::NoMacroRule => write!(
+        f,
+        "{}",
+        "these tokens do not match any rule".fg(Color::RED_100)
+      ),
+      Self::NonExhaustiveMatch(pats) => write!(
+        f,
+        "{}",
+        format!("the pattern(s) {pats} are not covered").fg(Color::RED_100)
+      ),
+      Self::NonPositiveStep(step) => write!(
+        f,
+        "{}",
+        format!("a step of `{step}` never reaches the end of the range")
+          .fg(Color::RED_100)
+      ),
+      Self::NotBehaviorMethod(behavior) => write!(
+        f,
+        "{}",
+        format!("`{behavior}` does not declare this method").fg(Color::RED_100)
+      ),
+      Self::NotImported(module) => write!(
+        f,
+        "{}",
+        format!("`{module}` is not loaded by this module").fg(Color::RED_100)
+      ),
+      Self::NotIterable => {
+        write!(f, "{}", "expected an array or a range".fg(Color::RED_100))
+      }
       Self::OutOfLoop(behavior) => {
         write!(
           f,
@@ -315,6 +831,17 @@ impl fmt::Display for LabelMessage {
           format!("cannot `{behavior}` out of the loop").fg(Color::RED_100)
         )
       }
+      Self::PrivateItem(module) => write!(
+        f,
+        "{}",
+        format!("this item is private to `{module}`").fg(Color::RED_100)
+      ),
+      Self::ShadowedIt => write!(
+        f,
+        "{}",
+        "`it` refers to the elements of this loop from here"
+          .fg(Color::YELLOW_100)
+      ),
       Self::TypeMismatch(t1, t2) => {
         write!(
           f,
@@ -325,12 +852,51 @@ impl fmt::Display for LabelMessage {
       Self::TypeMismatchDefinedAs(ty) => {
         write!(f, "{}", format!("defined as `{ty}`").fg(Color::BLUE_200))
       }
+      Self::UnclosedComment => write!(
+        f,
+        "{}",
+        "this comment is never closed with `!-`".fg(Color::RED_100)
+      ),
+      Self::UndefinedField(ty) => write!(
+        f,
+        "{}",
+        format!("`{ty}` does not have this field").fg(Color::RED_100)
+      ),
+      Self::UndefinedMacro => write!(
+        f,
+        "{}",
+        "no macro is defined with this name".fg(Color::RED_100)
+      ),
+      Self::UndefinedMethod(ty) => write!(
+        f,
+        "{}",
+        format!("`{ty}` does not have this method").fg(Color::RED_100)
+      ),
       Self::UndefinedName => write!(
         f,
         "{}",
         "i don't know this id. are your sure you defined it correctly?"
           .fg(Color::RED_100)
       ),
+      Self::UndefinedType => write!(
+        f,
+        "{}",
+        "this type is not declared anywhere".fg(Color::RED_100)
+      ),
+      Self::UndefinedVariant(ty) => write!(
+        f,
+        "{}",
+        format!("`{ty}` does not have this variant").fg(Color::RED_100)
+      ),
+      Self::UnknownAttribute => {
+        write!(f, "{}", "this attribute is ignored".fg(Color::YELLOW_100))
+      }
+      Self::UnreachablePattern => write!(
+        f,
+        "{}",
+        "the previous arms already match every value of this one"
+          .fg(Color::YELLOW_100)
+      ),
       Self::WrongAssignOp(t1, t2) => {
         write!(
           f,
@@ -351,6 +917,18 @@ impl fmt::Display for LabelMessage {
           .fg(Color::RED_100)
         )
       }
+      Self::WrongPatternCount(expected, actual) => write!(
+        f,
+        "{}",
+        format!("expected {expected} field(s), found {actual}")
+          .fg(Color::RED_100)
+      ),
+      Self::WrongTyArgCount(expected, actual) => write!(
+        f,
+        "{}",
+        format!("expected {expected} type argument(s), found {actual}")
+          .fg(Color::RED_100)
+      ),
       Self::WrongUnOp(ty) => {
         write!(
           f,
@@ -359,7 +937,25 @@ impl fmt::Display for LabelMessage {
             .fg(Color::RED_100)
         )
       }
-      Self::UnrecognizedToken => write!(f, ""),
+      Self::UnrecognizedEof => write!(
+        f,
+        "{}",
+        "the file ends before this item is complete".fg(Color::RED_100)
+      ),
+      Self::UnrecognizedToken => write!(
+        f,
+        "{}",
+        "this token is not expected here".fg(Color::RED_100)
+      ),
+      Self::UnresolvedLoad(reason) => {
+        write!(f, "{}", reason.fg(Color::RED_100))
+      }
+      Self::UnsatisfiedBound(bound) => write!(
+        f,
+        "{}",
+        format!("required by the bound `{bound}`").fg(Color::RED_100)
+      ),
+      Self::Unsupported(help) => write!(f, "{}", help.fg(Color::RED_100)),
     }
   }
 }
@@ -381,6 +977,7 @@ impl fmt::Display for Note {
 }
 
 pub enum NoteKind {
+  ExpectedTokens(Vec<String>),
   MainHasInputs(String),
   MainNotFound,
   MissingInputs(usize, usize),
@@ -391,6 +988,15 @@ pub enum NoteKind {
 impl fmt::Display for NoteKind {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match self {
+      Self::ExpectedTokens(tokens) => {
+        let tokens = tokens
+          .iter()
+          .map(|token| format!("`{token}`"))
+          .collect::<Vec<_>>()
+          .join(", ");
+
+        write!(f, "expected one of {}", tokens.fg(Color::BLUE_100))
+      }
       Self::MainHasInputs(inputs) => write!(
         f,
         "expected `fun()` \n\t     actual `fun({})`",
@@ -433,18 +1039,52 @@ impl fmt::Display for Help {
 }
 
 pub enum HelpKind {
+  DidYouMean(String),
+  LoadModule(String),
+  MacroDelimiter(String),
+  MakePublic(String),
   MissingInputs(String),
+  NonExhaustiveMatch(String),
 }
 
 impl fmt::Display for HelpKind {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match self {
+      Self::DidYouMean(name) => write!(
+        f,
+        "{}",
+        format!("did you mean `{name}`?").fg(Color::YELLOW_100)
+      ),
+      Self::LoadModule(module) => write!(
+        f,
+        "{}",
+        format!("add `load {module};` to use it here").fg(Color::YELLOW_100)
+      ),
+      Self::MacroDelimiter(call) => write!(
+        f,
+        "{}",
+        format!("this macro is called with `{call}`").fg(Color::YELLOW_100)
+      ),
+      Self::MakePublic(name) => write!(
+        f,
+        "{}",
+        format!("add `pub` to the definition of `{name}` to use it here")
+          .fg(Color::YELLOW_100)
+      ),
       Self::MissingInputs(callee) => write!(
         f,
         "{}",
         format!("This is how you should call this function: {callee}")
           .fg(Color::YELLOW_100)
       ),
+      Self::NonExhaustiveMatch(pat) => write!(
+        f,
+        "{}",
+        format!(
+          "add a `_` arm or an arm for each missing pattern: {pat} => ..."
+        )
+        .fg(Color::YELLOW_100)
+      ),
     }
   }
 }
@@ -1,4 +1,4 @@
-use super::report::Report;
+use super::report::{Label, LabelKind, LabelMessage, Report, ReportKind};
 
 use crate::util::constant::EXIT_FAILURE;
 use crate::util::source::SourceMap;
@@ -6,14 +6,17 @@ use crate::util::span::Span;
 
 use ariadne::sources;
 
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use std::default::Default;
 use std::path::{Path, PathBuf};
 use std::{io, process};
 
 #[derive(Debug)]
 pub struct Reporter {
-  has_errors: Cell<bool>,
+  errors: Cell<usize>,
+  // the call sites of the macros expanded so far, a report inside one of them
+  // points back to the call
+  expansions: RefCell<Vec<Span>>,
   pub source_map: SourceMap,
 }
 
@@ -34,15 +37,68 @@ impl Reporter {
     self.source_map.path(span)
   }
 
+  pub fn offset(&self, source_id: u32) -> usize {
+    self.source_map.offset(source_id)
+  }
+
+  // the path of the source in which the offset is and the offset at which
+  // that source starts
+  fn locate(&self, offset: usize) -> (String, usize) {
+    let source_id = self.source(Span::new(offset, offset));
+    let path = self.source_map.sources[source_id as usize].path.display();
+
+    (path.to_string(), self.offset(source_id))
+  }
+
+  pub fn add_expansion(&self, span: Span) {
+    self.expansions.borrow_mut().push(span);
+  }
+
+  // the innermost call site that contains the offset
+  fn expansion(&self, offset: u32) -> Option<Span> {
+    self
+      .expansions
+      .borrow()
+      .iter()
+      .filter(|span| span.lo <= offset && offset < span.hi)
+      .min_by_key(|span| span.hi - span.lo)
+      .copied()
+  }
+
   pub fn add_report(&self, report: Report, pathname: String, code: &str) {
     let stream = io::stderr();
+    let is_error = matches!(report.kind, ReportKind::Error);
+
+    let report = match self.expansion(report.offset.0) {
+      Some(span) => report.with_label(
+        Label::new(LabelKind::Note, (pathname.clone(), span.into()))
+          .with_message(LabelMessage::InMacroExpansion)
+          .with_order(1),
+      ),
+      None => report,
+    };
+
+    // the spans are offsets in the code of every source, a label can point
+    // into another source than the one of the report
+    let report = report.relocate(|offset| self.locate(offset));
+    let mut codes = vec![(pathname, code)];
+
+    for (source_id, source) in self.source_map.sources.iter().enumerate() {
+      let path = source.path.display().to_string();
+
+      if codes.iter().all(|(pathname, _)| *pathname != path) {
+        codes.push((path, self.code(source_id as u32)));
+      }
+    }
 
     eprintln!();
     ariadne::Report::from(report)
-      .write(sources(vec![(pathname, code)]), stream)
+      .write(sources(codes), stream)
       .unwrap();
 
-    self.has_errors.set(true);
+    if is_error {
+      self.errors.set(self.errors.get() + 1);
+    }
   }
 
   pub fn raise(&self, report: Report, pathname: String, code: &str) -> ! {
@@ -50,8 +106,12 @@ impl Reporter {
     self.abort()
   }
 
+  pub fn error_count(&self) -> usize {
+    self.errors.get()
+  }
+
   pub fn abort_if_has_error(&self) {
-    if self.has_errors.get() {
+    if self.error_count() > 0 {
       self.abort();
     }
   }
@@ -64,7 +124,8 @@ impl Reporter {
 impl Default for Reporter {
   fn default() -> Self {
     Self {
-      has_errors: Cell::new(false),
+      errors: Cell::new(0),
+      expansions: RefCell::new(vec![]),
       source_map: SourceMap::default(),
     }
   }
@@ -39,6 +39,26 @@ pub fn make_exe(path_input: &str, path_output: &str) {
   }
 }
 
+pub fn make_exe_quietly(
+  path_directory: &str,
+  path_object: &str,
+  path_exe: &str,
+  bytes_buf: &[u8],
+) -> Result<(), String> {
+  fs::create_dir_all(path_directory).map_err(|error| error.to_string())?;
+  fs::write(path_object, bytes_buf).map_err(|error| error.to_string())?;
+
+  let output = Command::new("gcc")
+    .args([path_object, "-o", path_exe])
+    .output()
+    .map_err(|error| error.to_string())?;
+
+  match output.status.success() {
+    true => Ok(()),
+    false => Err(String::from_utf8_lossy(&output.stderr).into_owned()),
+  }
+}
+
 fn is_dir_exist(path: &str) -> bool {
   Path::new(path).is_dir()
 }
@@ -47,6 +47,11 @@ impl SourceMap {
     &self.code[self.sources[source_id].id..end]
   }
 
+  // the offset at which the code of the source starts
+  pub fn offset(&self, source_id: u32) -> usize {
+    self.sources[source_id as usize].id
+  }
+
   pub fn source(&self, span: Span) -> u32 {
     self
       .sources